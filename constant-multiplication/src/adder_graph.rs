@@ -0,0 +1,428 @@
+// src/adder_graph.rs
+use crate::{CostTable, Error, GraphType};
+use constant_multiplication_format::Evaluator;
+use std::fmt;
+
+/// Where an adder input comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// The multiplier input
+    Input,
+    /// The output of the node with the given index
+    Node(usize),
+}
+
+/// An adder input, a shifted and possibly negated node output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Operand {
+    pub source: Source,
+    /// Left shift applied before adding
+    pub shift: u32,
+    /// Whether the input is subtracted
+    pub negative: bool,
+}
+
+/// An adder or subtractor
+///
+/// The output is `(±(a << sa) ± (b << sb)) >> right_shift`, with the right
+/// shift removing trailing zeros so that the value is odd.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdderNode {
+    /// Output as a multiple of the multiplier input
+    pub value: u64,
+    pub inputs: [Operand; 2],
+    pub right_shift: u32,
+}
+
+/// Adders realizing a multiplication by a constant
+///
+/// Nodes only use the input and earlier nodes, so they are in a valid
+/// evaluation order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdderGraph {
+    pub nodes: Vec<AdderNode>,
+    pub output: Operand,
+}
+
+impl AdderGraph {
+    /// Expand a structure, building the intermediate fundamentals from the first structure stored for them
+    pub fn from_graph_type(graph_type: &GraphType, table: &CostTable) -> Result<Self, Error> {
+        let mut builder = Builder {
+            table,
+            nodes: Vec::new(),
+            input: Source::Input,
+            graph_type: graph_type.clone(),
+        };
+        let term = graph_type.evaluate(&mut builder)?;
+        Ok(AdderGraph {
+            nodes: builder.nodes,
+            output: Operand {
+                source: term.source,
+                shift: 0,
+                negative: false,
+            },
+        })
+    }
+
+    /// Graph multiplying by n, from the first structure stored for its odd part
+    ///
+    /// Even factors become a shift of the output, so powers of two need no adders.
+    pub fn from_coefficient(n: u64, table: &CostTable) -> Result<Self, Error> {
+        if n == 0 {
+            return Err(Error::OutOfRange(n));
+        }
+        let shift = n.trailing_zeros();
+        let odd = n >> shift;
+        let mut graph = match table.graph_types(odd)?.first() {
+            Some(graph_type) => Self::from_graph_type(graph_type, table)?,
+            None if odd == 1 => AdderGraph {
+                nodes: Vec::new(),
+                output: Operand {
+                    source: Source::Input,
+                    shift: 0,
+                    negative: false,
+                },
+            },
+            None => return Err(Error::MissingStructure(odd)),
+        };
+        graph.output.shift += shift;
+        Ok(graph)
+    }
+
+    /// Constant the graph multiplies by
+    pub fn value(&self) -> u64 {
+        self.source_value(self.output.source) << self.output.shift
+    }
+
+    /// Number of adders and subtractors
+    pub fn adder_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Adder depth of each node, the number of adders on its longest path from the input
+    pub fn node_depths(&self) -> Vec<u32> {
+        let mut depths: Vec<u32> = Vec::with_capacity(self.nodes.len());
+        for node in &self.nodes {
+            let depth = node
+                .inputs
+                .iter()
+                .map(|operand| match operand.source {
+                    Source::Input => 0,
+                    Source::Node(idx) => depths[idx],
+                })
+                .max()
+                .unwrap_or(0);
+            depths.push(depth + 1);
+        }
+        depths
+    }
+
+    /// Number of adders on the longest path from the input to the output
+    ///
+    /// ```
+    /// use constant_multiplication::{GraphType, GraphTypeExt};
+    ///
+    /// let graph = GraphType::Cascade(3, 15).to_adder_graph().unwrap();
+    /// assert_eq!(graph.depth(), 2);
+    /// ```
+    pub fn depth(&self) -> u32 {
+        match self.output.source {
+            Source::Input => 0,
+            Source::Node(idx) => self.node_depths()[idx],
+        }
+    }
+
+    /// Output of the source as a multiple of the input
+    pub fn source_value(&self, source: Source) -> u64 {
+        match source {
+            Source::Input => 1,
+            Source::Node(idx) => self.nodes[idx].value,
+        }
+    }
+}
+
+/// Name of a source, `x` for the input and `t1`, `t2`, ... for the nodes
+pub(crate) fn source_name(source: Source) -> String {
+    match source {
+        Source::Input => "x".to_string(),
+        Source::Node(idx) => format!("t{}", idx + 1),
+    }
+}
+
+pub(crate) fn shifted_name(operand: &Operand) -> String {
+    match operand.shift {
+        0 => source_name(operand.source),
+        shift => format!("({} << {shift})", source_name(operand.source)),
+    }
+}
+
+impl fmt::Display for AdderGraph {
+    /// One line per node, such as `t1 = (x << 2) + x = 5x`, and the output
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, node) in self.nodes.iter().enumerate() {
+            let [a, b] = &node.inputs;
+            let mut expr = format!(
+                "{}{} {} {}",
+                if a.negative { "-" } else { "" },
+                shifted_name(a),
+                if b.negative { "-" } else { "+" },
+                shifted_name(b)
+            );
+            if node.right_shift > 0 {
+                expr = format!("({expr}) >> {}", node.right_shift);
+            }
+            writeln!(
+                f,
+                "{} = {expr} = {}x",
+                source_name(Source::Node(idx)),
+                node.value
+            )?;
+        }
+        write!(f, "y = {}", shifted_name(&self.output))
+    }
+}
+
+/// A multiple of the input, the output of a node shifted left
+#[derive(Clone, Copy)]
+struct Term {
+    source: Source,
+    shift: u32,
+}
+
+/// Evaluates structures into adder nodes
+struct Builder<'a> {
+    table: &'a CostTable,
+    nodes: Vec<AdderNode>,
+    // Structure being expanded and the source its constants multiply
+    graph_type: GraphType,
+    input: Source,
+}
+
+impl Builder<'_> {
+    fn value(&self, source: Source) -> u64 {
+        match source {
+            Source::Input => 1,
+            Source::Node(idx) => self.nodes[idx].value,
+        }
+    }
+
+    /// Build source multiplied by the odd constant c
+    fn fundamental(&mut self, c: u64, source: Source) -> Result<Source, Error> {
+        if c == 1 {
+            return Ok(source);
+        }
+        let graph_type = self
+            .table
+            .graph_types(c)?
+            .first()
+            .ok_or(Error::MissingStructure(c))?;
+
+        let outer_input = std::mem::replace(&mut self.input, source);
+        let outer_graph_type = std::mem::replace(&mut self.graph_type, graph_type.clone());
+        let term = graph_type.evaluate(self);
+        let graph_type = std::mem::replace(&mut self.graph_type, outer_graph_type);
+        self.input = outer_input;
+
+        // Nodes are odd, so any shift of the structure output is dropped
+        let result = term?.source;
+        if self.value(source).checked_mul(c) != Some(self.value(result)) {
+            return Err(Error::InvalidStructure(graph_type));
+        }
+        Ok(result)
+    }
+
+    /// Add a node computing `a ± b` and return it as a term
+    fn node(&mut self, a: Term, b: Term, subtract: bool) -> Result<Term, Error> {
+        let invalid = || Error::InvalidStructure(self.graph_type.clone());
+        let (common, va, vb) = self.aligned(&a, &b)?;
+        let sum = if subtract {
+            va.checked_sub(vb).ok_or_else(invalid)?
+        } else {
+            va + vb
+        };
+        if sum == 0 {
+            return Err(invalid());
+        }
+        let right_shift = sum.trailing_zeros();
+        let value = u64::try_from(sum >> right_shift).map_err(|_| invalid())?;
+        let shift = common.checked_add(right_shift).ok_or_else(invalid)?;
+        let (sa, sb) = (a.shift - common, b.shift - common);
+
+        self.nodes.push(AdderNode {
+            value,
+            inputs: [
+                Operand {
+                    source: a.source,
+                    shift: sa,
+                    negative: false,
+                },
+                Operand {
+                    source: b.source,
+                    shift: sb,
+                    negative: subtract,
+                },
+            ],
+            right_shift,
+        });
+        Ok(Term {
+            source: Source::Node(self.nodes.len() - 1),
+            shift,
+        })
+    }
+
+    /// Shift common to both terms, moved to the output, and the exact values
+    /// of the terms without it
+    ///
+    /// Terms whose remaining shift does not fit an adder input are rejected.
+    fn aligned(&self, a: &Term, b: &Term) -> Result<(u32, u128, u128), Error> {
+        let common = a.shift.min(b.shift);
+        let (sa, sb) = (a.shift - common, b.shift - common);
+        if sa >= u64::BITS || sb >= u64::BITS {
+            return Err(Error::InvalidStructure(self.graph_type.clone()));
+        }
+        let va = u128::from(self.value(a.source)) << sa;
+        let vb = u128::from(self.value(b.source)) << sb;
+        Ok((common, va, vb))
+    }
+}
+
+impl Evaluator for Builder<'_> {
+    type Value = Term;
+    type Error = Error;
+
+    fn constant(&mut self, t: usize) -> Result<Term, Error> {
+        self.multiply(
+            &Term {
+                source: self.input,
+                shift: 0,
+            },
+            t,
+        )
+    }
+
+    fn multiply(&mut self, value: &Term, t: usize) -> Result<Term, Error> {
+        let t = t as u64;
+        if t == 0 {
+            return Err(Error::InvalidStructure(self.graph_type.clone()));
+        }
+        let shift = t.trailing_zeros();
+        let source = self.fundamental(t >> shift, value.source)?;
+        let shift = value
+            .shift
+            .checked_add(shift)
+            .ok_or_else(|| Error::InvalidStructure(self.graph_type.clone()))?;
+        Ok(Term { source, shift })
+    }
+
+    fn add(&mut self, a: &Term, b: &Term) -> Result<Term, Error> {
+        self.node(*a, *b, false)
+    }
+
+    fn abs_diff(&mut self, a: &Term, b: &Term) -> Result<Term, Error> {
+        let (_, va, vb) = self.aligned(a, b)?;
+        if va >= vb {
+            self.node(*a, *b, true)
+        } else {
+            self.node(*b, *a, true)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::assert_multiplies;
+    use crate::{GraphTypeExt, HdlOptions, adder_cost, adder_graph, graph_types};
+
+    /// Node outputs and graph output for the input x, checking that right
+    /// shifts only drop zeros
+    fn evaluate(graph: &AdderGraph, x: i128) -> (Vec<i128>, i128) {
+        let mut outputs: Vec<i128> = Vec::with_capacity(graph.nodes.len());
+        let operand = |operand: &Operand, outputs: &[i128]| {
+            let value = match operand.source {
+                Source::Input => x,
+                Source::Node(idx) => outputs[idx],
+            } << operand.shift;
+            if operand.negative { -value } else { value }
+        };
+        for node in &graph.nodes {
+            let [a, b] = &node.inputs;
+            let sum = operand(a, &outputs) + operand(b, &outputs);
+            assert_eq!(sum & ((1 << node.right_shift) - 1), 0, "{graph}");
+            outputs.push(sum >> node.right_shift);
+        }
+        let output = operand(&graph.output, &outputs);
+        (outputs, output)
+    }
+
+    /// Compares every node and the output to the products for all 8-bit
+    /// inputs, signed and unsigned
+    fn assert_graph_multiplies(graph: &AdderGraph, n: u64) {
+        assert_eq!(graph.value(), n, "{graph}");
+        for signed in [false, true] {
+            assert_multiplies(graph, &HdlOptions::new(8, signed), |x| {
+                let (outputs, output) = evaluate(graph, x);
+                for (node, output) in graph.nodes.iter().zip(outputs) {
+                    assert_eq!(node.value % 2, 1, "{graph}");
+                    assert_eq!(output, x * i128::from(node.value), "{graph}");
+                }
+                output
+            });
+        }
+    }
+
+    #[test]
+    fn stored_structures_multiply() {
+        for n in (3..2048).step_by(2) {
+            let cost = adder_cost(n).unwrap();
+            for graph_type in graph_types(n).unwrap() {
+                let graph = graph_type.to_adder_graph().unwrap();
+                assert_graph_multiplies(&graph, n);
+                assert_eq!(graph.adder_count(), usize::from(cost), "{graph}");
+            }
+        }
+    }
+
+    #[test]
+    fn coefficients_multiply() {
+        for n in (1..4096).chain([65521, 1 << 20, 11467 << 3, 3 << 40]) {
+            assert_graph_multiplies(&adder_graph(n).unwrap(), n);
+        }
+        assert!(matches!(adder_graph(0), Err(Error::OutOfRange(0))));
+    }
+
+    #[test]
+    fn shifts_beyond_an_adder_input_are_rejected() {
+        // (32 + 32) << 61 = 1 << 67 is compared exactly to 32 << 63 = 1 << 68,
+        // with the common shift of 67 moved to the output
+        let graph = GraphType::Leapfrog4_2(32, 32, 1 << 63, 1 << 61)
+            .to_adder_graph()
+            .unwrap();
+        assert_eq!(graph.value(), 1);
+        // (3 + 1) << 62 = 1 << 64 and 3 * 1 would need an input shifted by 64
+        let graph_type = GraphType::Leapfrog4_2(3, 1, 1, 1 << 62);
+        assert!(matches!(
+            graph_type.to_adder_graph(),
+            Err(Error::InvalidStructure(_))
+        ));
+    }
+
+    #[test]
+    fn powers_of_two_need_no_adders() {
+        let graph = adder_graph(64).unwrap();
+        assert_eq!((graph.adder_count(), graph.depth()), (0, 0));
+        assert_eq!(graph.to_string(), "y = (x << 6)");
+    }
+
+    #[test]
+    fn cascade_is_displayed() {
+        let graph = GraphType::Cascade(3, 15).to_adder_graph().unwrap();
+        assert_eq!(
+            graph.to_string(),
+            "t1 = x + (x << 1) = 3x\n\
+             t2 = (t1 << 4) - t1 = 45x\n\
+             y = t2"
+        );
+        assert_eq!(graph.node_depths(), [1, 2]);
+    }
+}
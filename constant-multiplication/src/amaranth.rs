@@ -0,0 +1,231 @@
+// src/amaranth.rs
+use crate::hdl::{HdlGraph, HdlOptions};
+use crate::{AdderGraph, Error};
+use std::fmt::{self, Write};
+
+impl AdderGraph {
+    /// Python source of an Amaranth `Elaboratable` computing `y = value * x`
+    ///
+    /// The class, named `Mult<value>` unless `options.name` is given, has the
+    /// signals `x` and `y` as attributes. Every node gets a signal wide enough
+    /// for any input, and Amaranth widens the operands of each sum. Pipelined
+    /// designs register in the `sync` domain, including the output.
+    pub fn to_amaranth(&self, options: &HdlOptions) -> Result<String, Error> {
+        options.check()?;
+        let mut out = String::new();
+        write_class(&mut out, &HdlGraph::new(self, options))
+            .expect("writing to a String cannot fail");
+        Ok(out)
+    }
+}
+
+fn write_class(out: &mut String, graph: &HdlGraph) -> fmt::Result {
+    let options = graph.options;
+    let kind = if options.signed { "signed" } else { "unsigned" };
+    let shape = |width: u32| format!("{kind}({width})");
+    let name = options
+        .name
+        .clone()
+        .unwrap_or_else(|| format!("Mult{}", graph.value));
+
+    writeln!(
+        out,
+        "# Multiplication by {} using {} adders",
+        graph.value,
+        graph.nodes.len()
+    )?;
+    if let Some(summary) = graph.pipeline_summary() {
+        writeln!(out, "# {summary}")?;
+    }
+    writeln!(
+        out,
+        "from amaranth.hdl import Elaboratable, Module, Signal, {kind}"
+    )?;
+    writeln!(out)?;
+    writeln!(out)?;
+    writeln!(out, "class {name}(Elaboratable):")?;
+    writeln!(out, "    def __init__(self):")?;
+    writeln!(
+        out,
+        "        self.x = Signal({})",
+        shape(options.input_width)
+    )?;
+    writeln!(
+        out,
+        "        self.y = Signal({})",
+        shape(graph.output_width())
+    )?;
+    writeln!(out)?;
+    writeln!(out, "    def elaborate(self, platform):")?;
+    writeln!(out, "        m = Module()")?;
+
+    for node in &graph.nodes {
+        if let Some(sum_width) = node.sum_width {
+            writeln!(
+                out,
+                "        {}_sum = Signal({})",
+                node.name,
+                shape(sum_width)
+            )?;
+        }
+        writeln!(out, "        {} = Signal({})", node.name, shape(node.width))?;
+    }
+    let registers = graph.registers();
+    for (name, _, width) in &registers {
+        writeln!(out, "        {name} = Signal({})", shape(*width))?;
+    }
+
+    for node in &graph.nodes {
+        let [a, b] = &node.inputs;
+        let expr = format!(
+            "{}{} {} {}",
+            if a.negative { "-" } else { "" },
+            shifted(&graph.signal(a.source, node.stage), a.shift),
+            if b.negative { "-" } else { "+" },
+            shifted(&graph.signal(b.source, node.stage), b.shift)
+        );
+        if node.sum_width.is_some() {
+            // Shift the trailing zeros out of the full-width sum
+            writeln!(out, "        m.d.comb += {}_sum.eq({expr})", node.name)?;
+            writeln!(
+                out,
+                "        m.d.comb += {0}.eq({0}_sum >> {1})",
+                node.name, node.right_shift
+            )?;
+        } else {
+            writeln!(out, "        m.d.comb += {}.eq({expr})", node.name)?;
+        }
+    }
+    writeln!(
+        out,
+        "        m.d.comb += self.y.eq({})",
+        match graph.output.shift {
+            0 => signal(&graph.output_signal()),
+            shift => format!("{} << {shift}", signal(&graph.output_signal())),
+        }
+    )?;
+    for (name, input, _) in &registers {
+        writeln!(out, "        m.d.sync += {name}.eq({})", signal(input))?;
+    }
+    writeln!(out, "        return m")
+}
+
+/// Local variable of a signal, or the attribute for the input
+fn signal(name: &str) -> String {
+    match name {
+        "x" => "self.x".to_string(),
+        name => name.to_string(),
+    }
+}
+
+fn shifted(name: &str, shift: u32) -> String {
+    match shift {
+        0 => signal(name),
+        shift => format!("({} << {shift})", signal(name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{TempDir, run, stored_graphs};
+    use crate::{GraphType, GraphTypeExt, HdlOptions};
+    use std::fmt::Write;
+    use std::process::Command;
+
+    #[test]
+    fn pipelined_cascade_is_written() {
+        let options = HdlOptions {
+            pipeline: Some(1),
+            ..HdlOptions::new(8, true)
+        };
+        assert_eq!(
+            GraphType::Cascade(3, 15).to_amaranth(&options).unwrap(),
+            "\
+# Multiplication by 45 using 2 adders
+# Registers after every adder level, latency 2 cycles, 24 register bits
+from amaranth.hdl import Elaboratable, Module, Signal, signed
+
+
+class Mult45(Elaboratable):
+    def __init__(self):
+        self.x = Signal(signed(8))
+        self.y = Signal(signed(14))
+
+    def elaborate(self, platform):
+        m = Module()
+        t1 = Signal(signed(10))
+        t2 = Signal(signed(14))
+        t1_d1 = Signal(signed(10))
+        t2_d1 = Signal(signed(14))
+        m.d.comb += t1.eq(self.x + (self.x << 1))
+        m.d.comb += t2.eq((t1_d1 << 4) - t1_d1)
+        m.d.comb += self.y.eq(t2_d1)
+        m.d.sync += t1_d1.eq(t1)
+        m.d.sync += t2_d1.eq(t2)
+        return m
+"
+        );
+    }
+
+    /// Simulates every design for all inputs with the Amaranth 0.5 simulator
+    const SIMULATION: &str = "
+from amaranth.sim import Simulator
+
+
+def check(design, value, is_signed, latency):
+    dut = design()
+    sim = Simulator(dut)
+    if latency:
+        sim.add_clock(1e-6)
+
+    async def bench(ctx):
+        for x in range(-128, 128) if is_signed else range(256):
+            ctx.set(dut.x, x)
+            if latency:
+                await ctx.tick().repeat(latency)
+            assert ctx.get(dut.y) == x * value, (design.__name__, x)
+
+    sim.add_testbench(bench)
+    sim.run()
+
+
+for design in DESIGNS:
+    check(*design)
+print(\"ok\")
+";
+
+    #[test]
+    #[ignore = "needs Python 3 with Amaranth 0.5, run with --ignored"]
+    fn designs_multiply_in_amaranth() {
+        let dir = TempDir::new("cm-amaranth-test");
+        let mut source = String::new();
+        let mut designs = String::from("DESIGNS = [\n");
+        let mut count = 0;
+        for graph in stored_graphs([3, 43, 45, 683, 1100, 11467]) {
+            for signed in [false, true] {
+                for pipeline in [None, Some(1)] {
+                    let name = format!("M{count}");
+                    count += 1;
+                    let options = HdlOptions {
+                        name: Some(name.clone()),
+                        pipeline,
+                        ..HdlOptions::new(8, signed)
+                    };
+                    source.push_str(&graph.to_amaranth(&options).unwrap());
+                    let latency = graph.pipeline_report(&options).unwrap().latency;
+                    let signed = if signed { "True" } else { "False" };
+                    writeln!(
+                        designs,
+                        "    ({name}, {}, {signed}, {latency}),",
+                        graph.value()
+                    )
+                    .unwrap();
+                }
+            }
+        }
+        designs.push_str("]\n");
+        let path = dir.path().join("mult.py");
+        std::fs::write(&path, source + &designs + SIMULATION).unwrap();
+        assert_eq!(run(Command::new("python3").arg(&path)), "ok\n");
+    }
+}
@@ -0,0 +1,172 @@
+// src/b_asic.rs
+use crate::adder_graph::source_name;
+use crate::{AdderGraph, Source};
+use std::collections::BTreeSet;
+use std::fmt::{self, Write};
+
+impl AdderGraph {
+    /// Python source of a function building a B-ASIC `SFG` computing `value * x`
+    ///
+    /// The function is named `mult_<value>` and returns an SFG with one input
+    /// and one output. Each adder is an `Addition` or `Subtraction` named as in
+    /// [`AdderGraph::to_verilog`], and shifts are `LeftShift` and `RightShift`
+    /// operations, so the SFG can replace a `ConstantMultiplication` through
+    /// `SFG.replace_operation` and then be scheduled.
+    pub fn to_b_asic(&self) -> String {
+        let mut out = String::new();
+        write_function(&mut out, self).expect("writing to a String cannot fail");
+        out
+    }
+}
+
+fn write_function(out: &mut String, graph: &AdderGraph) -> fmt::Result {
+    let value = graph.value();
+    let name = format!("mult_{value}");
+
+    // Subtract from the positive input, as B-ASIC has no negation
+    let adders: Vec<(&str, String, String)> = graph
+        .nodes
+        .iter()
+        .map(|node| {
+            let [a, b] = &node.inputs;
+            let (a, b) = if a.negative { (b, a) } else { (a, b) };
+            let kind = if b.negative {
+                "Subtraction"
+            } else {
+                "Addition"
+            };
+            (kind, shifted(a.source, a.shift), shifted(b.source, b.shift))
+        })
+        .collect();
+    let mut operations: BTreeSet<&str> = adders.iter().map(|&(kind, _, _)| kind).collect();
+    if graph
+        .nodes
+        .iter()
+        .flat_map(|node| &node.inputs)
+        .chain([&graph.output])
+        .any(|operand| operand.shift > 0)
+    {
+        operations.insert("LeftShift");
+    }
+    if graph.nodes.iter().any(|node| node.right_shift > 0) {
+        operations.insert("RightShift");
+    }
+
+    writeln!(
+        out,
+        "# Multiplication by {value} using {} adders",
+        graph.nodes.len()
+    )?;
+    if !operations.is_empty() {
+        let operations: Vec<&str> = operations.into_iter().collect();
+        writeln!(
+            out,
+            "from b_asic.core_operations import {}",
+            operations.join(", ")
+        )?;
+    }
+    writeln!(out, "from b_asic.signal_flow_graph import SFG")?;
+    writeln!(out, "from b_asic.special_operations import Input, Output")?;
+    writeln!(out)?;
+    writeln!(out)?;
+    writeln!(out, "def {name}():")?;
+    writeln!(out, "    x = Input(name=\"x\")")?;
+    for (idx, (node, (kind, a, b))) in graph.nodes.iter().zip(&adders).enumerate() {
+        let node_name = source_name(Source::Node(idx));
+        if node.right_shift > 0 {
+            // Shift the trailing zeros out of the sum
+            writeln!(
+                out,
+                "    {node_name}_sum = {kind}({a}, {b}, name=\"{node_name}_sum\")"
+            )?;
+            writeln!(
+                out,
+                "    {node_name} = RightShift({}, {node_name}_sum, name=\"{node_name}\")",
+                node.right_shift
+            )?;
+        } else {
+            writeln!(
+                out,
+                "    {node_name} = {kind}({a}, {b}, name=\"{node_name}\")"
+            )?;
+        }
+    }
+    writeln!(
+        out,
+        "    y = Output({}, name=\"y\")",
+        shifted(graph.output.source, graph.output.shift)
+    )?;
+    writeln!(
+        out,
+        "    return SFG(inputs=[x], outputs=[y], name=\"{name}\")"
+    )
+}
+
+fn shifted(source: Source, shift: u32) -> String {
+    match shift {
+        0 => source_name(source),
+        shift => format!("LeftShift({shift}, {})", source_name(source)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{TempDir, run, stored_graphs};
+    use crate::{GraphType, GraphTypeExt, to_b_asic};
+    use std::fmt::Write;
+    use std::process::Command;
+
+    #[test]
+    fn right_shifted_sum_is_written() {
+        assert_eq!(
+            GraphType::Adder(1, 85).to_b_asic().unwrap(),
+            "\
+# Multiplication by 43 using 3 adders
+from b_asic.core_operations import Addition, LeftShift, RightShift
+from b_asic.signal_flow_graph import SFG
+from b_asic.special_operations import Input, Output
+
+
+def mult_43():
+    x = Input(name=\"x\")
+    t1 = Addition(x, LeftShift(2, x), name=\"t1\")
+    t2 = Addition(t1, LeftShift(4, t1), name=\"t2\")
+    t3_sum = Addition(x, t2, name=\"t3_sum\")
+    t3 = RightShift(1, t3_sum, name=\"t3\")
+    y = Output(t3, name=\"y\")
+    return SFG(inputs=[x], outputs=[y], name=\"mult_43\")
+"
+        );
+        assert!(
+            to_b_asic(8)
+                .unwrap()
+                .contains("    y = Output(LeftShift(3, x), name=\"y\")\n")
+        );
+    }
+
+    #[test]
+    #[ignore = "needs Python 3 with B-ASIC, run with --ignored"]
+    fn signal_flow_graphs_multiply_in_b_asic() {
+        let dir = TempDir::new("cm-b-asic-test");
+        let mut source = String::new();
+        let graphs = stored_graphs([3, 43, 45, 683, 1100, 11467, 65521])
+            .chain([1, 8, 1100 << 2].map(|n| crate::adder_graph(n).unwrap()));
+        for graph in graphs {
+            let value = graph.value();
+            // Functions of the same value replace each other, so each is checked when defined
+            source.push_str(&graph.to_b_asic());
+            writeln!(
+                source,
+                "\nsfg = mult_{value}()\n\
+                 assert sfg.name == \"mult_{value}\"\n\
+                 for x in range(-128, 256):\n    \
+                 assert sfg.evaluate_output(0, [x]) == x * {value}, x\n"
+            )
+            .unwrap();
+        }
+        source.push_str("print(\"ok\")\n");
+        let path = dir.path().join("mult.py");
+        std::fs::write(&path, source).unwrap();
+        assert_eq!(run(Command::new("python3").arg(&path)), "ok\n");
+    }
+}
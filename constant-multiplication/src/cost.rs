@@ -0,0 +1,272 @@
+// src/cost.rs
+use crate::{
+    AdderCells, AdderGraph, Error, FpgaOptions, GraphType, GraphTypeExt, HdlOptions, graph_types,
+};
+
+/// A metric of an adder graph to minimize, such as its depth or area
+///
+/// Structures are ranked on their expansion by [`GraphTypeExt::to_adder_graph`],
+/// so the adders of intermediate fundamentals are included.
+pub trait CostModel {
+    /// Cost of the graph, lower is better
+    fn cost(&self, graph: &AdderGraph) -> Result<f64, Error>;
+}
+
+impl<T: CostModel + ?Sized> CostModel for &T {
+    fn cost(&self, graph: &AdderGraph) -> Result<f64, Error> {
+        (**self).cost(graph)
+    }
+}
+
+/// Number of adders and subtractors
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AdderCount;
+
+impl CostModel for AdderCount {
+    fn cost(&self, graph: &AdderGraph) -> Result<f64, Error> {
+        Ok(graph.adder_count() as f64)
+    }
+}
+
+/// Adders on the longest path, see [`AdderGraph::depth`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Depth;
+
+impl CostModel for Depth {
+    fn cost(&self, graph: &AdderGraph) -> Result<f64, Error> {
+        Ok(f64::from(graph.depth()))
+    }
+}
+
+/// Full adders for an input of `options.input_width` bits, see [`AdderGraph::full_adders`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FullAdders(pub HdlOptions);
+
+impl CostModel for FullAdders {
+    fn cost(&self, graph: &AdderGraph) -> Result<f64, Error> {
+        Ok(total_cells(graph, &self.0)?.full_adders as f64)
+    }
+}
+
+/// LUTs of the adders on an FPGA with carry chains, see [`AdderGraph::fpga_cost`]
+///
+/// With `fpga.ternary`, pairs of adders are fused into ternary adders, which
+/// take as many LUTs as a binary one of the same width.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FpgaLuts {
+    pub options: HdlOptions,
+    pub fpga: FpgaOptions,
+}
+
+impl FpgaLuts {
+    /// LUTs of binary adders
+    pub fn new(options: HdlOptions) -> Self {
+        FpgaLuts {
+            options,
+            fpga: FpgaOptions::default(),
+        }
+    }
+}
+
+impl CostModel for FpgaLuts {
+    fn cost(&self, graph: &AdderGraph) -> Result<f64, Error> {
+        Ok(f64::from(graph.fpga_cost(&self.options, &self.fpga)?.luts))
+    }
+}
+
+/// Standard cell area of the full adders, half adders and inverters
+///
+/// The default areas are rough NAND2 gate equivalents, to be replaced by the
+/// cell areas of a library for absolute numbers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsicArea {
+    pub options: HdlOptions,
+    pub full_adder: f64,
+    pub half_adder: f64,
+    pub inverter: f64,
+}
+
+impl AsicArea {
+    /// Area with the default cell areas
+    pub fn new(options: HdlOptions) -> Self {
+        AsicArea {
+            options,
+            full_adder: 7.0,
+            half_adder: 3.5,
+            inverter: 0.67,
+        }
+    }
+}
+
+impl CostModel for AsicArea {
+    fn cost(&self, graph: &AdderGraph) -> Result<f64, Error> {
+        let cells = total_cells(graph, &self.options)?;
+        Ok(cells.full_adders as f64 * self.full_adder
+            + cells.half_adders as f64 * self.half_adder
+            + cells.inverters as f64 * self.inverter)
+    }
+}
+
+/// Cost of the graph for the model, which must be a number to be compared
+fn finite_cost(model: &impl CostModel, graph: &AdderGraph) -> Result<f64, Error> {
+    let cost = model.cost(graph)?;
+    if cost.is_finite() {
+        Ok(cost)
+    } else {
+        Err(Error::CostModel(format!("cost {cost} is not finite")))
+    }
+}
+
+fn total_cells(graph: &AdderGraph, options: &HdlOptions) -> Result<AdderCells, Error> {
+    Ok(graph.full_adders(options)?.into_iter().sum())
+}
+
+/// Stored structure of n with the minimum number of adders that is best for the model
+///
+/// Ties are broken by the order of [`graph_types`]. There is no structure for
+/// powers of two, as no adders are needed. Costs that are infinite or NaN are
+/// an error.
+///
+/// ```
+/// use constant_multiplication::{Depth, GraphTypeExt, best_structure};
+///
+/// let best = best_structure(45, Depth).unwrap().unwrap();
+/// assert_eq!(best.depth().unwrap(), 2);
+/// ```
+pub fn best_structure(n: u64, model: impl CostModel) -> Result<Option<GraphType>, Error> {
+    let mut best: Option<(f64, &GraphType)> = None;
+    for graph_type in graph_types(n)? {
+        let cost = finite_cost(&model, &graph_type.to_adder_graph()?)?;
+        if best.is_none_or(|(best_cost, _)| cost < best_cost) {
+            best = Some((cost, graph_type));
+        }
+    }
+    Ok(best.map(|(_, graph_type)| graph_type.clone()))
+}
+
+/// Stored structures of n that are not dominated for the models, with their costs
+///
+/// A structure is dominated when another one is at least as good for every
+/// model and better for one. Structures are in the order of [`graph_types`],
+/// and the costs in the order of the models. Costs that are infinite or NaN
+/// are an error.
+///
+/// ```
+/// use constant_multiplication::{CostModel, Depth, FullAdders, HdlOptions, pareto_structures};
+///
+/// let models: [&dyn CostModel; 2] = [&Depth, &FullAdders(HdlOptions::new(8, false))];
+/// let pareto = pareto_structures(1100, &models).unwrap();
+/// assert!(!pareto.is_empty());
+/// ```
+pub fn pareto_structures(
+    n: u64,
+    models: &[&dyn CostModel],
+) -> Result<Vec<(GraphType, Vec<f64>)>, Error> {
+    let mut costs = Vec::new();
+    for graph_type in graph_types(n)? {
+        let graph = graph_type.to_adder_graph()?;
+        let cost = models
+            .iter()
+            .map(|model| finite_cost(model, &graph))
+            .collect::<Result<Vec<f64>, Error>>()?;
+        costs.push((graph_type.clone(), cost));
+    }
+    let dominates = |a: &[f64], b: &[f64]| {
+        a.iter().zip(b).all(|(a, b)| a <= b) && a.iter().zip(b).any(|(a, b)| a < b)
+    };
+    let pareto = costs
+        .iter()
+        .filter(|(_, cost)| !costs.iter().any(|(_, other)| dominates(other, cost)))
+        .cloned()
+        .collect();
+    Ok(pareto)
+}
+
+/// Stored structures of n with their cells, fewest full adders first
+///
+/// Structures with as many full adders are ordered by their half adders, and
+/// then by the order of [`graph_types`]. The cells are summed over the adders,
+/// see [`AdderGraph::full_adders`].
+///
+/// ```
+/// use constant_multiplication::{HdlOptions, graph_types, rank_by_full_adders};
+///
+/// let ranked = rank_by_full_adders(45, &HdlOptions::new(8, false)).unwrap();
+/// assert_eq!(ranked.len(), graph_types(45).unwrap().len());
+/// assert!(ranked[0].1.full_adders <= ranked[1].1.full_adders);
+/// ```
+pub fn rank_by_full_adders(
+    n: u64,
+    options: &HdlOptions,
+) -> Result<Vec<(GraphType, AdderCells)>, Error> {
+    let mut ranked = graph_types(n)?
+        .iter()
+        .map(|graph_type| {
+            let cells = graph_type.full_adders(options)?.into_iter().sum();
+            Ok((graph_type.clone(), cells))
+        })
+        .collect::<Result<Vec<(GraphType, AdderCells)>, Error>>()?;
+    ranked.sort_by_key(|(_, cells)| (cells.full_adders, cells.half_adders));
+    Ok(ranked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Model with the same cost for every graph
+    struct Constant(f64);
+
+    impl CostModel for Constant {
+        fn cost(&self, _graph: &AdderGraph) -> Result<f64, Error> {
+            Ok(self.0)
+        }
+    }
+
+    #[test]
+    fn non_finite_costs_are_rejected() {
+        for cost in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert!(matches!(
+                best_structure(1100, Constant(cost)),
+                Err(Error::CostModel(_))
+            ));
+            let models: [&dyn CostModel; 2] = [&Depth, &Constant(cost)];
+            assert!(matches!(
+                pareto_structures(1100, &models),
+                Err(Error::CostModel(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn equal_costs_keep_the_first_structure() {
+        let first = graph_types(1100).unwrap()[0].clone();
+        assert_eq!(best_structure(1100, Constant(1.0)).unwrap(), Some(first));
+        let models: [&dyn CostModel; 1] = [&Constant(1.0)];
+        let pareto = pareto_structures(1100, &models).unwrap();
+        assert_eq!(pareto.len(), graph_types(1100).unwrap().len());
+        assert_eq!(best_structure(1024, Depth).unwrap(), None);
+    }
+
+    #[test]
+    fn cell_models_count_the_cascade() {
+        // 16 full adders, 5 half adders and 10 inverters, see AdderGraph::full_adders
+        let graph = GraphType::Cascade(3, 15).to_adder_graph().unwrap();
+        let options = HdlOptions::new(8, false);
+        assert_eq!(FullAdders(options.clone()).cost(&graph).unwrap(), 16.0);
+        let area = AsicArea::new(options).cost(&graph).unwrap();
+        assert!((area - (16.0 * 7.0 + 5.0 * 3.5 + 10.0 * 0.67)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ranks_are_ordered_by_cells() {
+        let options = HdlOptions::new(8, true);
+        let ranked = rank_by_full_adders(1100, &options).unwrap();
+        assert_eq!(ranked.len(), graph_types(1100).unwrap().len());
+        for (graph_type, cells) in &ranked {
+            let total: AdderCells = graph_type.full_adders(&options).unwrap().into_iter().sum();
+            assert_eq!(*cells, total);
+        }
+        assert!(ranked.is_sorted_by_key(|(_, cells)| (cells.full_adders, cells.half_adders)));
+    }
+}
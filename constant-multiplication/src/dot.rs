@@ -0,0 +1,178 @@
+// src/dot.rs
+use crate::adder_graph::source_name;
+use crate::{AdderGraph, Operand, Source};
+use std::fmt::{self, Write};
+
+impl AdderGraph {
+    /// Graphviz DOT description of the adders
+    ///
+    /// Each adder is a node labelled with its fundamental, and a right shift
+    /// removing trailing zeros if any. Edges are labelled with the sign and
+    /// left shift of the input.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::new();
+        write_dot(&mut out, self).expect("writing to a String cannot fail");
+        out
+    }
+}
+
+fn write_dot(out: &mut String, graph: &AdderGraph) -> fmt::Result {
+    let value = graph.value();
+    writeln!(out, "digraph mult_{value} {{")?;
+    writeln!(out, "    rankdir=LR;")?;
+    writeln!(out, "    node [shape=circle];")?;
+    writeln!(out, "    x [label=\"x\", shape=box];")?;
+    for (idx, node) in graph.nodes.iter().enumerate() {
+        let mut label = node.value.to_string();
+        if node.right_shift > 0 {
+            write!(label, "\\n>> {}", node.right_shift)?;
+        }
+        writeln!(
+            out,
+            "    {} [label=\"{label}\"];",
+            source_name(Source::Node(idx))
+        )?;
+    }
+    writeln!(out, "    y [label=\"{value}x\", shape=box];")?;
+
+    for (idx, node) in graph.nodes.iter().enumerate() {
+        for operand in &node.inputs {
+            writeln!(
+                out,
+                "    {} -> {} [label=\"{}\"];",
+                source_name(operand.source),
+                source_name(Source::Node(idx)),
+                edge_label(operand)
+            )?;
+        }
+    }
+    match graph.output.shift {
+        0 => writeln!(out, "    {} -> y;", source_name(graph.output.source))?,
+        shift => writeln!(
+            out,
+            "    {} -> y [label=\"<< {shift}\"];",
+            source_name(graph.output.source)
+        )?,
+    }
+    writeln!(out, "}}")
+}
+
+/// Sign and shift of an adder input, such as `- << 2`
+pub(crate) fn edge_label(operand: &Operand) -> String {
+    let sign = if operand.negative { "-" } else { "+" };
+    match operand.shift {
+        0 => sign.to_string(),
+        shift => format!("{sign} << {shift}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{VALUES, stored_graphs};
+    use crate::{GraphType, GraphTypeExt, to_dot};
+    use std::collections::HashMap;
+
+    /// Labelled edges into each node, by source
+    type Edges<'a> = HashMap<&'a str, Vec<(&'a str, &'a str)>>;
+
+    /// Node labels in order, and the edges into each node
+    fn parse(dot: &str) -> (Vec<(&str, &str)>, Edges<'_>) {
+        let mut nodes = Vec::new();
+        let mut edges = Edges::new();
+        for line in dot.lines().map(str::trim) {
+            let Some(line) = line.strip_suffix(';') else {
+                continue;
+            };
+            let (statement, label) = match line.split_once(" [label=\"") {
+                Some((statement, label)) => (statement, label.split('"').next().unwrap()),
+                None => (line, ""),
+            };
+            match statement.split_once(" -> ") {
+                Some((from, to)) => edges.entry(to).or_default().push((from, label)),
+                None if label.is_empty() => {}
+                None => nodes.push((statement, label)),
+            }
+        }
+        (nodes, edges)
+    }
+
+    /// Sign and left shift of an edge label
+    fn shifted(label: &str) -> (&str, u32) {
+        match label.rsplit_once("<< ") {
+            Some((sign, shift)) => (sign.trim(), shift.parse().unwrap()),
+            None => (label, 0),
+        }
+    }
+
+    /// Recomputes every node label and the output from the labelled edges
+    fn assert_edges_compute_labels(dot: &str, value: u64) {
+        let (nodes, edges) = parse(dot);
+        let mut values = HashMap::from([("x", 1u128)]);
+        for &(node, label) in &nodes {
+            if node == "x" || node == "y" {
+                continue;
+            }
+            let (fundamental, right_shift) = match label.split_once("\\n>> ") {
+                Some((fundamental, shift)) => (fundamental, shift.parse().unwrap()),
+                None => (label, 0),
+            };
+            let [a, b] = edges[node].as_slice() else {
+                panic!("{node} needs two inputs\n{dot}");
+            };
+            let (sign_a, shift_a) = shifted(a.1);
+            let (sign_b, shift_b) = shifted(b.1);
+            assert_eq!(sign_a, "+", "{dot}");
+            let a = values[a.0] << shift_a;
+            let b = values[b.0] << shift_b;
+            let sum = if sign_b == "-" { a - b } else { a + b };
+            assert_eq!(sum.trailing_zeros(), right_shift, "{dot}");
+            assert_eq!((sum >> right_shift).to_string(), fundamental, "{dot}");
+            values.insert(node, sum >> right_shift);
+        }
+        let [(from, label)] = edges["y"].as_slice() else {
+            panic!("one output edge expected\n{dot}");
+        };
+        assert_eq!(values[from] << shifted(label).1, u128::from(value), "{dot}");
+        assert!(nodes.contains(&("y", &format!("{value}x"))), "{dot}");
+    }
+
+    #[test]
+    fn edges_compute_the_labels() {
+        for graph in stored_graphs(VALUES) {
+            assert_edges_compute_labels(&graph.to_dot(), graph.value());
+        }
+        for n in [1, 8, 1100, 11467 << 3] {
+            assert_edges_compute_labels(&to_dot(n).unwrap(), n);
+        }
+    }
+
+    #[test]
+    fn right_shifts_are_labelled() {
+        let graph = GraphType::Adder(1, 85).to_adder_graph().unwrap();
+        assert_eq!(
+            graph.to_dot(),
+            "\
+digraph mult_43 {
+    rankdir=LR;
+    node [shape=circle];
+    x [label=\"x\", shape=box];
+    t1 [label=\"5\"];
+    t2 [label=\"85\"];
+    t3 [label=\"43\\n>> 1\"];
+    y [label=\"43x\", shape=box];
+    x -> t1 [label=\"+\"];
+    x -> t1 [label=\"+ << 2\"];
+    t1 -> t2 [label=\"+\"];
+    t1 -> t2 [label=\"+ << 4\"];
+    x -> t3 [label=\"+\"];
+    t2 -> t3 [label=\"+\"];
+    t3 -> y;
+}
+"
+        );
+        let cascade = GraphType::Cascade(3, 15).to_adder_graph().unwrap().to_dot();
+        assert!(
+            cascade.contains("    t1 -> t2 [label=\"+ << 4\"];\n    t1 -> t2 [label=\"-\"];\n")
+        );
+    }
+}
@@ -0,0 +1,299 @@
+// src/fpga.rs
+use crate::hdl::HdlOptions;
+use crate::{AdderGraph, Error, Source};
+use std::num::NonZeroU32;
+
+/// Target of [`AdderGraph::fpga_cost`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FpgaOptions {
+    /// Bits of carry chain per slice, 8 for `CARRY8` on UltraScale and
+    /// Versal, 4 for `CARRY4` on 7-series
+    pub carry_bits: NonZeroU32,
+    /// Whether to fuse pairs of adders into ternary adders
+    pub ternary: bool,
+}
+
+impl Default for FpgaOptions {
+    fn default() -> Self {
+        FpgaOptions {
+            carry_bits: NonZeroU32::new(8).expect("8 is not zero"),
+            ternary: false,
+        }
+    }
+}
+
+/// Estimated resources of a graph on an FPGA with carry chains
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FpgaCost {
+    pub luts: u32,
+    /// Slices used by the carry chains, each adder starting a new one
+    pub carry_slices: u32,
+    pub binary_adders: usize,
+    /// Ternary adders, each replacing two binary adders
+    pub ternary_adders: usize,
+}
+
+/// An adder with two or three operands, the exponents relative to its sum
+struct FpgaAdder {
+    operands: Vec<(Source, u32, bool)>,
+}
+
+impl AdderGraph {
+    /// LUTs and carry-chain slices of the graph for an input of `options.input_width` bits
+    ///
+    /// Each adder is a carry chain with one LUT per bit, from the lowest bit
+    /// that is not a copy of a single operand to the top of the sum, sized as
+    /// in [`AdderGraph::to_verilog`]. Inverting a subtracted operand is done
+    /// in the LUTs. The chain of a ternary adder is as long, as one LUT
+    /// computes the sum of three bits and feeds the second carry to the next.
+    ///
+    /// With `fpga.ternary`, an adder is fused into the one using it when it
+    /// has no other users, greedily in the order of the adders. Fused adders
+    /// subtract at most one operand, which the fabrics support with a carry
+    /// in. When the fused adder shifts its sum right by k bits, the operands
+    /// are shifted left by up to k bits instead, and the chain includes the low
+    /// bits that are dropped, as the binary adder does. These bits are zero,
+    /// but their carry is needed, so the chain is up to k LUTs longer than the
+    /// result. `options.name` and `options.pipeline` are ignored.
+    ///
+    /// ```
+    /// use constant_multiplication::{FpgaOptions, GraphTypeExt, HdlOptions, graph_types};
+    ///
+    /// let graph = graph_types(1100).unwrap()[0].to_adder_graph().unwrap();
+    /// let options = HdlOptions::new(8, false);
+    /// let binary = graph.fpga_cost(&options, &FpgaOptions::default()).unwrap();
+    /// assert_eq!((binary.binary_adders, binary.ternary_adders), (3, 0));
+    /// let fpga = FpgaOptions {
+    ///     ternary: true,
+    ///     ..FpgaOptions::default()
+    /// };
+    /// let ternary = graph.fpga_cost(&options, &fpga).unwrap();
+    /// assert_eq!((ternary.binary_adders, ternary.ternary_adders), (1, 1));
+    /// assert!(ternary.luts < binary.luts);
+    /// ```
+    pub fn fpga_cost(&self, options: &HdlOptions, fpga: &FpgaOptions) -> Result<FpgaCost, Error> {
+        options.check()?;
+        let mut cost = FpgaCost::default();
+        for adder in self.fpga_adders(fpga.ternary) {
+            let luts = adder.luts(self, options);
+            cost.luts += luts;
+            cost.carry_slices += luts.div_ceil(fpga.carry_bits.get());
+            match adder.operands.len() {
+                2 => cost.binary_adders += 1,
+                _ => cost.ternary_adders += 1,
+            }
+        }
+        Ok(cost)
+    }
+
+    /// Adders of the graph, with each fused pair replaced by one ternary adder
+    fn fpga_adders(&self, ternary: bool) -> Vec<FpgaAdder> {
+        let mut users = vec![0; self.nodes.len()];
+        let used = self
+            .nodes
+            .iter()
+            .flat_map(|node| &node.inputs)
+            .chain([&self.output]);
+        for operand in used {
+            if let Source::Node(idx) = operand.source {
+                users[idx] += 1;
+            }
+        }
+        let is_output = |idx: usize| self.output.source == Source::Node(idx);
+
+        let mut adders: Vec<Option<FpgaAdder>> = Vec::with_capacity(self.nodes.len());
+        for node in &self.nodes {
+            let binary = FpgaAdder {
+                operands: node
+                    .inputs
+                    .iter()
+                    .map(|operand| (operand.source, operand.shift, operand.negative))
+                    .collect(),
+            };
+            let fused = ternary
+                .then(|| {
+                    node.inputs.iter().enumerate().find_map(|(i, operand)| {
+                        let Source::Node(idx) = operand.source else {
+                            return None;
+                        };
+                        let inner = adders[idx].as_ref()?;
+                        if users[idx] != 1 || is_output(idx) || inner.operands.len() != 2 {
+                            return None;
+                        }
+                        // ±(((±x << sx) ± (y << sy)) >> k) << s ± other
+                        let k = self.nodes[idx].right_shift;
+                        let mut operands: Vec<(Source, i64, bool)> = inner
+                            .operands
+                            .iter()
+                            .map(|&(source, shift, negative)| {
+                                (
+                                    source,
+                                    i64::from(shift) + i64::from(operand.shift) - i64::from(k),
+                                    negative != operand.negative,
+                                )
+                            })
+                            .collect();
+                        let other = &node.inputs[1 - i];
+                        operands.push((other.source, i64::from(other.shift), other.negative));
+                        if operands
+                            .iter()
+                            .filter(|&&(_, _, negative)| negative)
+                            .count()
+                            > 1
+                        {
+                            return None;
+                        }
+                        // Shift up to keep all exponents non-negative, the chain then
+                        // computes the bits dropped by the right shift of the fused adder
+                        let low = operands.iter().map(|&(_, shift, _)| shift).min()?.min(0);
+                        let operands = operands
+                            .into_iter()
+                            .map(|(source, shift, negative)| {
+                                (source, (shift - low) as u32, negative)
+                            })
+                            .collect();
+                        Some((idx, FpgaAdder { operands }))
+                    })
+                })
+                .flatten();
+            match fused {
+                Some((idx, adder)) => {
+                    // The fused adder is computed as part of this one
+                    adders[idx] = None;
+                    adders.push(Some(adder));
+                }
+                None => adders.push(Some(binary)),
+            }
+        }
+        adders.into_iter().flatten().collect()
+    }
+}
+
+impl FpgaAdder {
+    fn luts(&self, graph: &AdderGraph, options: &HdlOptions) -> u32 {
+        let sum: i128 = self
+            .operands
+            .iter()
+            .map(|&(source, shift, negative)| {
+                let value = i128::from(graph.source_value(source)) << shift;
+                if negative { -value } else { value }
+            })
+            .sum();
+        let width = options.width(sum.unsigned_abs());
+
+        // Bits of each operand that are not constant zeros, sign extended or
+        // inverted operands reach the top of the sum
+        let ranges: Vec<(u32, u32, bool)> = self
+            .operands
+            .iter()
+            .map(|&(source, shift, negative)| {
+                let top = if options.signed || negative {
+                    width
+                } else {
+                    shift + options.width(graph.source_value(source).into())
+                };
+                (shift, top, negative)
+            })
+            .collect();
+        // The chain starts where two operands overlap, or at a subtracted
+        // operand below all others, and the carry ripples to the top
+        let lowest = ranges.iter().map(|&(low, _, _)| low).min().unwrap_or(0);
+        let negated = ranges
+            .iter()
+            .filter(|&&(low, _, negative)| negative && low == lowest)
+            .map(|&(low, _, _)| low);
+        let overlaps = ranges.iter().enumerate().flat_map(|(i, a)| {
+            ranges[i + 1..]
+                .iter()
+                .map(|b| (a.0.max(b.0), a.1.min(b.1)))
+                .filter(|(low, top)| low < top)
+                .map(|(low, _)| low)
+        });
+        match negated.chain(overlaps).min() {
+            Some(start) => width.saturating_sub(start),
+            None => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::stored_graphs;
+    use crate::{CostModel, FpgaLuts, GraphType, GraphTypeExt};
+
+    fn cost(luts: u32, carry_slices: u32, binary_adders: usize, ternary_adders: usize) -> FpgaCost {
+        FpgaCost {
+            luts,
+            carry_slices,
+            binary_adders,
+            ternary_adders,
+        }
+    }
+
+    fn costs(graph_type: GraphType, carry_bits: u32) -> (FpgaCost, FpgaCost) {
+        let graph = graph_type.to_adder_graph().unwrap();
+        let options = HdlOptions::new(8, false);
+        let binary = FpgaOptions {
+            carry_bits: NonZeroU32::new(carry_bits).unwrap(),
+            ternary: false,
+        };
+        let ternary = FpgaOptions {
+            ternary: true,
+            ..binary
+        };
+        (
+            graph.fpga_cost(&options, &binary).unwrap(),
+            graph.fpga_cost(&options, &ternary).unwrap(),
+        )
+    }
+
+    #[test]
+    fn known_structures_are_costed() {
+        // 3x = x + 2x from bit 1 to 9, and 45x = 48x - 3x from bit 0 to 13;
+        // 3x is used twice, so nothing is fused
+        let cascade = cost(9 + 14, 2 + 2, 2, 0);
+        assert_eq!(costs(GraphType::Cascade(3, 15), 8), (cascade, cascade));
+        let cascade = cost(9 + 14, 3 + 4, 2, 0);
+        assert_eq!(costs(GraphType::Cascade(3, 15), 4), (cascade, cascade));
+
+        // 3x from bit 1 to 9, 19x = 3x + 16x from bit 4 to 12, and
+        // 275x = 19x + 256x from bit 8 to 16; 3x + 16x + x << 1 from bit 1
+        // to 12 when fused
+        assert_eq!(
+            costs(GraphType::Adder(19, 256), 8),
+            (cost(9 + 9 + 9, 2 + 2 + 2, 3, 0), cost(12 + 9, 2 + 2, 1, 1))
+        );
+
+        // 5x from bit 2 to 10, 85x = 5x + 80x from bit 4 to 14, and the sum
+        // x + 85x from bit 0 to 14 before dropping its low bit, as is the
+        // fused x + 5x + 80x
+        assert_eq!(
+            costs(GraphType::Adder(1, 85), 8),
+            (
+                cost(9 + 11 + 15, 2 + 2 + 2, 3, 0),
+                cost(9 + 15, 2 + 2, 1, 1)
+            )
+        );
+    }
+
+    #[test]
+    fn fused_adders_replace_two() {
+        let options = HdlOptions::new(12, true);
+        let fpga = FpgaOptions {
+            ternary: true,
+            ..FpgaOptions::default()
+        };
+        for graph in stored_graphs((3..1024).step_by(2)) {
+            let cost = graph.fpga_cost(&options, &fpga).unwrap();
+            assert_eq!(
+                cost.binary_adders + 2 * cost.ternary_adders,
+                graph.adder_count(),
+                "{graph}"
+            );
+            let binary = graph.fpga_cost(&options, &FpgaOptions::default()).unwrap();
+            let model = FpgaLuts::new(options.clone()).cost(&graph).unwrap();
+            assert_eq!(model, f64::from(binary.luts));
+        }
+    }
+}
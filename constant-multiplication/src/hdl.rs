@@ -0,0 +1,509 @@
+// src/hdl.rs
+use crate::adder_graph::source_name;
+use crate::{AdderGraph, Error, Operand, Source};
+
+/// Options for generated hardware descriptions and functions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HdlOptions {
+    /// Width of the input in bits, 1 to 64
+    pub input_width: u32,
+    /// Whether the input is two's complement
+    pub signed: bool,
+    /// Name of the module or entity, `mult_<n>` if not given
+    ///
+    /// It must be an identifier in every output language: ASCII letters,
+    /// digits and single underscores, starting with a letter and not ending
+    /// with an underscore, and not a reserved word in any of them.
+    pub name: Option<String>,
+    /// Number of adder levels between registers, combinational if not given
+    pub pipeline: Option<u32>,
+}
+
+impl HdlOptions {
+    pub fn new(input_width: u32, signed: bool) -> Self {
+        HdlOptions {
+            input_width,
+            signed,
+            name: None,
+            pipeline: None,
+        }
+    }
+
+    pub(crate) fn check(&self) -> Result<(), Error> {
+        if !(1..=64).contains(&self.input_width) {
+            return Err(Error::InvalidWidth(self.input_width));
+        }
+        if self.pipeline == Some(0) {
+            return Err(Error::InvalidPipeline(0));
+        }
+        if let Some(name) = &self.name
+            && !is_identifier(name)
+        {
+            return Err(Error::InvalidName(name.clone()));
+        }
+        Ok(())
+    }
+
+    /// Name of the module multiplying by value
+    pub(crate) fn name(&self, value: u64) -> String {
+        self.name.clone().unwrap_or_else(|| format!("mult_{value}"))
+    }
+
+    /// Bits needed for the input multiplied by value
+    pub(crate) fn width(&self, value: u128) -> u32 {
+        let bits = u128::BITS - value.leading_zeros();
+        if self.signed {
+            // The most negative input gives the largest magnitude
+            self.input_width + value.next_power_of_two().trailing_zeros()
+        } else {
+            let max_input = (1u128 << self.input_width) - 1;
+            match value.checked_mul(max_input) {
+                Some(max) => u128::BITS - max.leading_zeros(),
+                None => bits + self.input_width,
+            }
+        }
+    }
+}
+
+// Words that cannot name a module, in lower case as VHDL ignores case
+const RESERVED_WORDS: [&str; 5] = [
+    // Verilog
+    "always and assign automatic begin buf bufif0 bufif1 case casex casez cell cmos config \
+     deassign default defparam design disable edge else end endcase endconfig endfunction \
+     endgenerate endmodule endprimitive endspecify endtable endtask event for force forever \
+     fork function generate genvar highz0 highz1 if ifnone incdir include initial inout \
+     input instance integer join large liblist library localparam macromodule medium module \
+     nand negedge nmos nor noshowcancelled not notif0 notif1 or output parameter pmos \
+     posedge primitive pull0 pull1 pulldown pullup pulsestyle_ondetect pulsestyle_onevent \
+     rcmos real realtime reg release repeat rnmos rpmos rtran rtranif0 rtranif1 scalared \
+     showcancelled signed small specify specparam strong0 strong1 supply0 supply1 table \
+     task time tran tranif0 tranif1 tri tri0 tri1 triand trior trireg unsigned use uwire \
+     vectored wait wand weak0 weak1 while wire wor xnor xor",
+    // VHDL
+    "abs access after alias all and architecture array assert assume assume_guarantee \
+     attribute begin block body buffer bus case component configuration constant context \
+     cover default disconnect downto else elsif end entity exit fairness file for force \
+     function generate generic group guarded if impure in inertial inout is label library \
+     linkage literal loop map mod nand new next nor not null of on open or others out \
+     package parameter port postponed procedure process property protected pure range \
+     record register reject release rem report restrict restrict_guarantee return rol ror \
+     select sequence severity shared signal sla sll sra srl strong subtype then to \
+     transport type unaffected units until use variable vmode vprop vunit wait when while \
+     with xnor xor",
+    // Python, and the names imported by the Amaranth output
+    "false none true and as assert async await break class continue def del elif else \
+     except finally for from global if import in is lambda nonlocal not or pass raise \
+     return try while with yield elaboratable module signal signed unsigned",
+    // C, and the integer types of the C output
+    "auto break case char const continue default do double else enum extern float for goto \
+     if inline int long register restrict return short signed sizeof static struct switch \
+     typedef union unsigned void volatile while int8_t int16_t int32_t int64_t uint8_t \
+     uint16_t uint32_t uint64_t",
+    // Rust
+    "as async await break const continue crate dyn else enum extern false fn for if impl in \
+     let loop match mod move mut pub ref return self static struct super trait true type \
+     unsafe use where while abstract become box do final gen macro override priv try typeof \
+     unsized virtual yield",
+];
+
+/// Whether name is an identifier in all output languages, following the
+/// strictest rules, those of VHDL
+fn is_identifier(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && !name.ends_with('_')
+        && !name.contains("__")
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !RESERVED_WORDS
+            .iter()
+            .flat_map(|words| words.split_ascii_whitespace())
+            .any(|word| word.eq_ignore_ascii_case(name))
+}
+
+/// Latency and size of the registers of a pipelined graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PipelineReport {
+    /// Clock cycles from input to output
+    pub latency: u32,
+    /// Total width of all registers, including the ones balancing delays
+    pub register_bits: u64,
+}
+
+/// A node of an adder graph with the widths of its signals
+pub(crate) struct HdlNode {
+    pub name: String,
+    pub width: u32,
+    pub inputs: [Operand; 2],
+    /// Width of the sum before shifting right, if the node shifts right
+    pub sum_width: Option<u32>,
+    pub right_shift: u32,
+    /// Pipeline stage the node is computed in, counted from 0
+    pub stage: u32,
+}
+
+/// Signals of a graph and the registers placed between its pipeline stages
+///
+/// A node at adder depth d is computed in stage `(d - 1) / k` for k levels
+/// per stage, and registered at the end of the stage. Signals used in a later
+/// stage, such as the input of the last adder of a leapfrog structure, are
+/// delayed through one register per stage they skip.
+pub(crate) struct HdlGraph<'a> {
+    pub options: &'a HdlOptions,
+    pub nodes: Vec<HdlNode>,
+    pub output: Operand,
+    pub value: u64,
+    pub latency: u32,
+    // Registers after each source, the input first
+    delays: Vec<u32>,
+}
+
+impl<'a> HdlGraph<'a> {
+    pub fn new(graph: &AdderGraph, options: &'a HdlOptions) -> Self {
+        let stage = |source: Source, stages: &[u32]| match source {
+            Source::Input => 0,
+            Source::Node(idx) => stages[idx],
+        };
+        let stages: Vec<u32> = graph
+            .node_depths()
+            .into_iter()
+            .map(|depth| options.pipeline.map_or(0, |levels| (depth - 1) / levels))
+            .collect();
+        let latency = match (options.pipeline, stages.iter().max()) {
+            (Some(_), Some(last)) => last + 1,
+            _ => 0,
+        };
+
+        // Each source is delayed as much as its latest user needs
+        let mut delays = vec![0; graph.nodes.len() + 1];
+        let mut use_at = |source: Source, user_stage: u32, stages: &[u32]| {
+            let index = delay_index(source);
+            delays[index] = delays[index].max(user_stage - stage(source, stages));
+        };
+        for (node, &node_stage) in graph.nodes.iter().zip(&stages) {
+            for operand in &node.inputs {
+                use_at(operand.source, node_stage, &stages);
+            }
+        }
+        use_at(graph.output.source, latency, &stages);
+
+        let nodes = graph
+            .nodes
+            .iter()
+            .zip(stages)
+            .enumerate()
+            .map(|(idx, (node, stage))| HdlNode {
+                name: source_name(Source::Node(idx)),
+                width: options.width(node.value.into()),
+                inputs: node.inputs,
+                sum_width: (node.right_shift > 0)
+                    .then(|| options.width(u128::from(node.value) << node.right_shift)),
+                right_shift: node.right_shift,
+                stage,
+            })
+            .collect();
+        HdlGraph {
+            options,
+            nodes,
+            output: graph.output,
+            value: graph.value(),
+            latency,
+            delays,
+        }
+    }
+
+    pub fn pipelined(&self) -> bool {
+        self.options.pipeline.is_some()
+    }
+
+    pub fn output_width(&self) -> u32 {
+        self.options.width(self.value.into())
+    }
+
+    pub fn width(&self, source: Source) -> u32 {
+        match source {
+            Source::Input => self.options.input_width,
+            Source::Node(idx) => self.nodes[idx].width,
+        }
+    }
+
+    fn stage(&self, source: Source) -> u32 {
+        match source {
+            Source::Input => 0,
+            Source::Node(idx) => self.nodes[idx].stage,
+        }
+    }
+
+    /// Name of the source as seen by a user in the given stage
+    pub fn signal(&self, source: Source, user_stage: u32) -> String {
+        delayed_name(source, user_stage - self.stage(source))
+    }
+
+    /// Name of the output source, delayed to the end of the pipeline
+    pub fn output_signal(&self) -> String {
+        self.signal(self.output.source, self.latency)
+    }
+
+    /// Registers as (name, input, width), in delay order for each source
+    pub fn registers(&self) -> Vec<(String, String, u32)> {
+        let sources = std::iter::once(Source::Input).chain((0..self.nodes.len()).map(Source::Node));
+        sources
+            .flat_map(|source| {
+                (1..=self.delays[delay_index(source)]).map(move |delay| {
+                    (
+                        delayed_name(source, delay),
+                        delayed_name(source, delay - 1),
+                        self.width(source),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Description of the pipelining for the header comment
+    pub fn pipeline_summary(&self) -> Option<String> {
+        let levels = self.options.pipeline?;
+        let report = self.report();
+        let stage = match levels {
+            1 => "every adder level".to_string(),
+            levels => format!("every {levels} adder levels"),
+        };
+        let cycles = if report.latency == 1 {
+            "cycle"
+        } else {
+            "cycles"
+        };
+        Some(format!(
+            "Registers after {stage}, latency {} {cycles}, {} register bits",
+            report.latency, report.register_bits
+        ))
+    }
+
+    pub fn report(&self) -> PipelineReport {
+        PipelineReport {
+            latency: self.latency,
+            register_bits: self
+                .registers()
+                .iter()
+                .map(|&(_, _, width)| u64::from(width))
+                .sum(),
+        }
+    }
+}
+
+fn delay_index(source: Source) -> usize {
+    match source {
+        Source::Input => 0,
+        Source::Node(idx) => idx + 1,
+    }
+}
+
+/// Name of a source after a number of registers, such as `t1_d2`
+fn delayed_name(source: Source, delay: u32) -> String {
+    match delay {
+        0 => source_name(source),
+        delay => format!("{}_d{delay}", source_name(source)),
+    }
+}
+
+impl AdderGraph {
+    /// Latency and register bits of the pipelined hardware description
+    ///
+    /// Both are zero when `options.pipeline` is not set.
+    ///
+    /// ```
+    /// use constant_multiplication::{GraphType, GraphTypeExt, HdlOptions};
+    ///
+    /// let graph = GraphType::Cascade(3, 15).to_adder_graph().unwrap();
+    /// let options = HdlOptions {
+    ///     pipeline: Some(1),
+    ///     ..HdlOptions::new(8, false)
+    /// };
+    /// let report = graph.pipeline_report(&options).unwrap();
+    /// assert_eq!((report.latency, report.register_bits), (2, 24));
+    /// ```
+    pub fn pipeline_report(&self, options: &HdlOptions) -> Result<PipelineReport, Error> {
+        options.check()?;
+        Ok(HdlGraph::new(self, options).report())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{assert_designs_multiply, stored_graphs};
+    use crate::{GraphType, GraphTypeExt};
+
+    /// Value held by a vector of the given width, wrapping as hardware does
+    fn wrap(value: i128, width: u32, signed: bool) -> i128 {
+        let wrapped = value.rem_euclid(1 << width);
+        if signed && wrapped >= 1 << (width - 1) {
+            wrapped - (1 << width)
+        } else {
+            wrapped
+        }
+    }
+
+    /// Output of the graph computed with every signal at its declared width,
+    /// checking that each signal holds its product
+    fn evaluate_at_widths(
+        graph: &AdderGraph,
+        options: &HdlOptions,
+    ) -> impl Fn(i128) -> i128 + use<> {
+        let hdl = HdlGraph::new(graph, options);
+        let output_width = hdl.output_width();
+        let (nodes, output) = (hdl.nodes, hdl.output);
+        let values: Vec<u64> = graph.nodes.iter().map(|node| node.value).collect();
+        let (graph, signed) = (graph.to_string(), options.signed);
+        move |x| {
+            let mut outputs: Vec<i128> = Vec::with_capacity(nodes.len());
+            let operand = |operand: &Operand, outputs: &[i128]| {
+                let value = match operand.source {
+                    Source::Input => x,
+                    Source::Node(idx) => outputs[idx],
+                } << operand.shift;
+                if operand.negative { -value } else { value }
+            };
+            for (node, &value) in nodes.iter().zip(&values) {
+                let [a, b] = &node.inputs;
+                let sum = operand(a, &outputs) + operand(b, &outputs);
+                let sum = wrap(sum, node.sum_width.unwrap_or(node.width), signed);
+                let output = wrap(sum >> node.right_shift, node.width, signed);
+                assert_eq!(output, x * i128::from(value), "{graph}");
+                outputs.push(output);
+            }
+            wrap(operand(&output, &outputs), output_width, signed)
+        }
+    }
+
+    #[test]
+    fn widths_hold_every_signal() {
+        assert_designs_multiply(&[1, 2, 8, 12], &[None], evaluate_at_widths);
+    }
+
+    #[test]
+    fn names_must_be_identifiers_in_every_language() {
+        let named = |name: &str| HdlOptions {
+            name: Some(name.to_string()),
+            ..HdlOptions::new(8, false)
+        };
+        for name in ["mult_45", "M0", "x", "Fir_tap3", "modules"] {
+            named(name).check().unwrap();
+        }
+        for name in [
+            "", "my mult", "3x", "_x", "x_", "a__b", "a-b", "a$b", "mülti", "module", "Signal",
+            "ENTITY", "class", "None", "fn", "int16_t",
+        ] {
+            assert!(
+                matches!(named(name).check(), Err(Error::InvalidName(_))),
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn output_widths_are_minimal() {
+        for value in (1..4096u64).chain([11467 << 20, 1 << 40]) {
+            for input_width in [2, 8, 16] {
+                let unsigned = HdlOptions::new(input_width, false);
+                let max = ((1i128 << input_width) - 1) * i128::from(value);
+                let width = unsigned.width(value.into());
+                assert!(max < 1 << width && max >= 1 << (width - 1), "{value}");
+
+                // The most negative product needs the most bits
+                let signed = HdlOptions::new(input_width, true);
+                let min = -(1i128 << (input_width - 1)) * i128::from(value);
+                let max = ((1i128 << (input_width - 1)) - 1) * i128::from(value);
+                let width = signed.width(value.into());
+                assert!(
+                    min >= -(1 << (width - 1)) && max < 1 << (width - 1),
+                    "{value}"
+                );
+                assert!(
+                    min < -(1 << (width - 2)) || max >= 1 << (width - 2),
+                    "{value}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn pipelines_are_balanced() {
+        for graph in stored_graphs([3, 45, 683, 1100, 11467, 65521]) {
+            for levels in 1..=3 {
+                let options = HdlOptions {
+                    pipeline: Some(levels),
+                    ..HdlOptions::new(8, true)
+                };
+                let hdl = HdlGraph::new(&graph, &options);
+                let registers = hdl.registers();
+                assert_eq!(hdl.latency, graph.depth().div_ceil(levels), "{graph}");
+
+                // Registers on every path from the input to each signal
+                let mut on_paths = vec![0];
+                let used = |source: Source, user_stage: u32, on_paths: &[u32]| {
+                    let delay = user_stage - hdl.stage(source);
+                    let signal = hdl.signal(source, user_stage);
+                    if delay > 0 {
+                        assert!(registers.iter().any(|(name, _, _)| *name == signal));
+                    }
+                    on_paths[delay_index(source)] + delay
+                };
+                for (node, depth) in hdl.nodes.iter().zip(graph.node_depths()) {
+                    assert_eq!(node.stage, (depth - 1) / levels, "{graph}");
+                    let [a, b] = node
+                        .inputs
+                        .map(|operand| used(operand.source, node.stage, &on_paths));
+                    assert_eq!((a, b), (node.stage, node.stage), "{graph}");
+                    on_paths.push(node.stage);
+                }
+                let output = used(hdl.output.source, hdl.latency, &on_paths);
+                assert_eq!(output, hdl.latency, "{graph}");
+
+                let report = graph.pipeline_report(&options).unwrap();
+                assert_eq!(report.latency, hdl.latency);
+                let bits: u32 = registers.iter().map(|&(_, _, width)| width).sum();
+                assert_eq!(report.register_bits, u64::from(bits));
+            }
+        }
+    }
+
+    #[test]
+    fn delayed_inputs_are_registered() {
+        // The last adder uses the input, which skips the first stage
+        let graph = GraphType::Adder(1, 85).to_adder_graph().unwrap();
+        let options = HdlOptions {
+            pipeline: Some(1),
+            ..HdlOptions::new(8, false)
+        };
+        let hdl = HdlGraph::new(&graph, &options);
+        let registers: Vec<_> = hdl
+            .registers()
+            .into_iter()
+            .map(|(name, input, _)| format!("{name} <= {input}"))
+            .collect();
+        assert_eq!(
+            registers,
+            [
+                "x_d1 <= x",
+                "x_d2 <= x_d1",
+                "t1_d1 <= t1",
+                "t2_d1 <= t2",
+                "t3_d1 <= t3"
+            ]
+        );
+        assert_eq!(
+            graph.pipeline_report(&options).unwrap(),
+            PipelineReport {
+                latency: 3,
+                register_bits: 8 + 8 + 11 + 15 + 14
+            }
+        );
+        let combinational = HdlOptions::new(8, false);
+        assert_eq!(
+            graph.pipeline_report(&combinational).unwrap(),
+            PipelineReport {
+                latency: 0,
+                register_bits: 0
+            }
+        );
+    }
+}
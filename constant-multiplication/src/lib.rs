@@ -1,353 +1,553 @@
 // src/lib.rs
-use pyo3::exceptions::{PyIndexError, PyValueError};
-use pyo3::prelude::*;
-use pyo3::types::PyList;
-use unsigned_varint::decode as varint_decode;
+//! Minimum number of adders and optimal shift-and-add structures for constant multiplication
+//!
+//! The tables are generated by the generator and embedded at compile time.
+//! Even integers are looked up through their odd part, as shifts are free.
+//!
+//! ```
+//! use constant_multiplication::{GraphType, adder_cost, graph_types};
+//!
+//! assert_eq!(adder_cost(45).unwrap(), 2);
+//! assert!(graph_types(45).unwrap().contains(&GraphType::Cascade(3, 15)));
+//! ```
+//!
+//! Larger tables can be generated and used through [`CostTable::open`], or
+//! [`load_tables`] to use them for the free functions as well.
+//!
+//! The Python bindings are built with the `python` feature.
+use constant_multiplication_format::{AdderCostFile, DepthCostFile, FormatError, GraphTypesFile};
+use std::fmt;
+use std::path::Path;
+use std::sync::{LazyLock, PoisonError, RwLock};
+
+pub use adder_graph::{AdderGraph, AdderNode, Operand, Source};
+pub use constant_multiplication_format::GraphType;
+pub use cost::{
+    AdderCount, AsicArea, CostModel, Depth, FpgaLuts, FullAdders, best_structure,
+    pareto_structures, rank_by_full_adders,
+};
+pub use fpga::{FpgaCost, FpgaOptions};
+pub use hdl::{HdlOptions, PipelineReport};
+pub use netlist::AdderCells;
+pub use svg::svg_row;
+pub use table::{
+    ADDER_COST_FILE_NAME, CostTable, DEPTH_COST_BOUND_FILE_NAME, GRAPH_TYPES_FILE_NAME,
+};
+
+mod adder_graph;
+mod amaranth;
+mod b_asic;
+mod cost;
+mod dot;
+mod fpga;
+mod hdl;
+mod netlist;
+mod pag;
+#[cfg(feature = "python")]
+mod python;
+mod software;
+mod svg;
+mod symmetry;
+mod table;
+#[cfg(test)]
+mod test_support;
+mod verilog;
+mod vhdl;
 
 // Include the data files directly at compile time
 const DATA_FILE: &[u8] = include_bytes!("../adder_cost.bin");
+const DEPTH_COST_BOUND_FILE: &[u8] = include_bytes!("../depth_cost_bound.bin");
 const GRAPH_TYPES_FILE: &[u8] = include_bytes!("../graph_types.bin");
 
-// Parse the adder cost data at compile time
-const fn parse_data_header() -> usize {
-    // First 8 bytes are the count
-    u64::from_le_bytes([
-        DATA_FILE[0],
-        DATA_FILE[1],
-        DATA_FILE[2],
-        DATA_FILE[3],
-        DATA_FILE[4],
-        DATA_FILE[5],
-        DATA_FILE[6],
-        DATA_FILE[7],
-    ]) as usize
+// Parse and validate the adder cost data at compile time
+const ADDER_COSTS: AdderCostFile<'static> = match AdderCostFile::parse(DATA_FILE) {
+    Ok(file) => file,
+    Err(_) => panic!("DATA_FILE is corrupt"),
+};
+
+// Parse and validate the depth-bounded adder cost data at compile time
+const DEPTH_COSTS: DepthCostFile<'static> = match DepthCostFile::parse(DEPTH_COST_BOUND_FILE) {
+    Ok(file) => file,
+    Err(_) => panic!("DEPTH_COST_BOUND_FILE is corrupt"),
+};
+
+// Parse and validate the graph types index at compile time
+const GRAPH_TYPES: GraphTypesFile<'static> = match GraphTypesFile::parse(GRAPH_TYPES_FILE) {
+    Ok(file) => file,
+    Err(_) => panic!("GRAPH_TYPES_FILE is corrupt"),
+};
+
+// Compile-time validation that all files come from the same generator run
+const _: () = assert!(
+    ADDER_COSTS.header().max_bits == GRAPH_TYPES.header().max_bits
+        && ADDER_COSTS.header().max_extra_bits == GRAPH_TYPES.header().max_extra_bits,
+    "DATA_FILE and GRAPH_TYPES_FILE are generated for different bit widths"
+);
+const _: () = assert!(
+    DEPTH_COSTS.header().max_bits == GRAPH_TYPES.header().max_bits
+        && DEPTH_COSTS.header().max_extra_bits == GRAPH_TYPES.header().max_extra_bits,
+    "DEPTH_COST_BOUND_FILE and GRAPH_TYPES_FILE are generated for different bit widths"
+);
+
+static EMBEDDED_TABLE: LazyLock<CostTable> =
+    LazyLock::new(|| CostTable::from_embedded(DATA_FILE, DEPTH_COST_BOUND_FILE, GRAPH_TYPES_FILE));
+
+// Table loaded with `load_tables`, used instead of the embedded one when set
+static LOADED_TABLE: RwLock<Option<&'static CostTable>> = RwLock::new(None);
+
+/// Errors when opening a table or looking up an integer
+#[derive(Debug)]
+pub enum Error {
+    /// The integer is larger than the largest one in the tables
+    OutOfRange(u64),
+    /// The table data is corrupt
+    Format(FormatError),
+    /// A table file could not be read
+    Io(std::io::Error),
+    /// The table files come from different generator runs
+    MismatchedTables,
+    /// There is no structure stored for an odd integer with a non-zero cost
+    MissingStructure(u64),
+    /// A structure does not compute the value it is stored for
+    InvalidStructure(GraphType),
+    /// The input width of a hardware description is not between 1 and 64 bits
+    InvalidWidth(u32),
+    /// A pipeline stage must contain at least one adder level
+    InvalidPipeline(u32),
+    /// The name of a hardware description is not an identifier in every output language
+    InvalidName(String),
+    /// An adder graph string cannot be parsed or does not compute its values
+    InvalidAdderGraph(String),
+    /// A cost model could not evaluate an adder graph
+    CostModel(String),
 }
 
-const DATA_COUNT: usize = parse_data_header();
-const DATA_OFFSET: usize = 8;
-const GRAPH_TYPES_BYTES: &[u8] = GRAPH_TYPES_FILE;
-
-// Compile-time validation of DATA_FILE length
-const _: () = assert!(DATA_FILE.len() >= DATA_OFFSET, "DATA_FILE is too small");
-
-// GraphType as a Python class
-#[pyclass]
-#[derive(Clone)]
-struct GraphType {
-    #[pyo3(get)]
-    variant: &'static str,
-    #[pyo3(get)]
-    params: Vec<usize>,
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::OutOfRange(n) => write!(f, "Index out of range: {n}"),
+            Error::Format(e) => write!(f, "Table data is corrupt: {e}"),
+            Error::Io(e) => write!(f, "Cannot read table: {e}"),
+            Error::MismatchedTables => {
+                write!(f, "Tables are generated for different bit widths")
+            }
+            Error::MissingStructure(n) => write!(f, "No structure stored for {n}"),
+            Error::InvalidStructure(graph_type) => {
+                write!(f, "Invalid structure: {graph_type:?}")
+            }
+            Error::InvalidWidth(width) => write!(f, "Invalid input width: {width}"),
+            Error::InvalidName(name) => write!(f, "Invalid name: {name:?}"),
+            Error::InvalidAdderGraph(reason) => write!(f, "Invalid adder graph: {reason}"),
+            Error::CostModel(reason) => write!(f, "Cost model failed: {reason}"),
+            Error::InvalidPipeline(levels) => {
+                write!(
+                    f,
+                    "Invalid number of adder levels per pipeline stage: {levels}"
+                )
+            }
+        }
+    }
 }
 
-fn extract_shift(value: usize) -> String {
-    let shift = value.trailing_zeros();
-    if shift == 0 {
-        value.to_string()
-    } else {
-        let shifted = value >> shift;
-        format!("{shifted} << {shift}")
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Format(e) => Some(e),
+            Error::Io(e) => Some(e),
+            Error::OutOfRange(_)
+            | Error::MismatchedTables
+            | Error::MissingStructure(_)
+            | Error::InvalidStructure(_)
+            | Error::InvalidWidth(_)
+            | Error::InvalidPipeline(_)
+            | Error::InvalidName(_)
+            | Error::InvalidAdderGraph(_)
+            | Error::CostModel(_) => None,
+        }
     }
 }
 
-#[pymethods]
-impl GraphType {
-    fn __repr__(&self) -> String {
-        let shifted_params: Vec<String> = self.params.iter().map(|&v| extract_shift(v)).collect();
-        format!("{}({})", self.variant, shifted_params.join(", "))
+impl From<FormatError> for Error {
+    fn from(e: FormatError) -> Self {
+        Error::Format(e)
     }
 }
 
-/// Helper function to decode multiple usize parameters from varint-encoded data
-fn decode_params(remaining: &mut &[u8], count: usize) -> Result<Vec<usize>, String> {
-    let mut params = Vec::with_capacity(count);
-    for _ in 0..count {
-        let (param, rest) =
-            varint_decode::usize(remaining).map_err(|e| format!("Failed to decode usize: {e}"))?;
-        *remaining = rest;
-        params.push(param);
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
     }
-    Ok(params)
 }
 
-/// Deserialization with varint decoding
-fn deserialize_graph_types(data: &[u8]) -> Result<Vec<Vec<GraphType>>, String> {
-    let mut remaining = data;
-
-    // Read number of entries
-    let (count, rest) =
-        varint_decode::usize(remaining).map_err(|e| format!("Failed to decode count: {e}"))?;
-    remaining = rest;
+/// The table embedded in the library
+pub fn embedded_table() -> &'static CostTable {
+    &EMBEDDED_TABLE
+}
 
-    let mut result = Vec::with_capacity(count);
+/// The table used by the free functions, the embedded one unless [`load_tables`] is called
+pub fn table() -> &'static CostTable {
+    let loaded = *LOADED_TABLE.read().unwrap_or_else(PoisonError::into_inner);
+    loaded.unwrap_or_else(embedded_table)
+}
 
-    for _ in 0..count {
-        // Read length of this Vec
-        let (vec_len, rest) = varint_decode::usize(remaining)
-            .map_err(|e| format!("Failed to decode vec length: {e}"))?;
-        remaining = rest;
+/// Open the tables in a directory and use them for the free functions from now on
+///
+/// Tables loaded earlier stay mapped for the rest of the process, as results
+/// borrowed from them may still be in use.
+pub fn load_tables(path: impl AsRef<Path>) -> Result<&'static CostTable, Error> {
+    let table: &'static CostTable = Box::leak(Box::new(CostTable::open(path)?));
+    *LOADED_TABLE.write().unwrap_or_else(PoisonError::into_inner) = Some(table);
+    Ok(table)
+}
 
-        let mut type_vec = Vec::with_capacity(vec_len);
+/// Methods on [`GraphType`] using the tables
+pub trait GraphTypeExt {
+    /// Expand the structure into adders, building intermediate fundamentals
+    /// from the first structure stored for them in [`table`]
+    ///
+    /// ```
+    /// use constant_multiplication::{GraphType, GraphTypeExt};
+    ///
+    /// let graph = GraphType::Cascade(3, 15).to_adder_graph().unwrap();
+    /// assert_eq!(graph.value(), 45);
+    /// assert_eq!(graph.adder_count(), 2);
+    /// ```
+    fn to_adder_graph(&self) -> Result<AdderGraph, Error>;
+
+    /// Whether the structure computes the odd part of n with [`adder_cost`] adders
+    ///
+    /// ```
+    /// use constant_multiplication::{GraphType, GraphTypeExt};
+    ///
+    /// assert!(GraphType::Cascade(3, 15).verify(90).unwrap());
+    /// assert!(!GraphType::Cascade(3, 15).verify(47).unwrap());
+    /// ```
+    fn verify(&self, n: u64) -> Result<bool, Error>;
+
+    /// Other structures computing the same value with the same number of adders,
+    /// which the generator may leave out as symmetric to this one
+    ///
+    /// Only leapfrogs of up to five terms and cascades have mirrored forms.
+    ///
+    /// ```
+    /// use constant_multiplication::{GraphType, GraphTypeExt};
+    ///
+    /// let equivalents = GraphType::Cascade(3, 15).equivalents().unwrap();
+    /// assert_eq!(equivalents, [GraphType::Cascade(15, 3)]);
+    /// ```
+    fn equivalents(&self) -> Result<Vec<GraphType>, Error>;
+
+    /// Adders on the longest path of the structure, through the intermediate
+    /// fundamentals built by [`GraphTypeExt::to_adder_graph`]
+    ///
+    /// ```
+    /// use constant_multiplication::{GraphType, GraphTypeExt};
+    ///
+    /// assert_eq!(GraphType::Cascade(3, 15).depth().unwrap(), 2);
+    /// ```
+    fn depth(&self) -> Result<u32, Error>;
+
+    /// Verilog module multiplying by the value of the structure
+    ///
+    /// ```
+    /// use constant_multiplication::{GraphType, GraphTypeExt, HdlOptions};
+    ///
+    /// let verilog = GraphType::Cascade(3, 15).to_verilog(&HdlOptions::new(8, true)).unwrap();
+    /// assert!(verilog.contains("module mult_45"));
+    /// ```
+    fn to_verilog(&self, options: &HdlOptions) -> Result<String, Error>;
+
+    /// VHDL-2008 entity multiplying by the value of the structure
+    ///
+    /// ```
+    /// use constant_multiplication::{GraphType, GraphTypeExt, HdlOptions};
+    ///
+    /// let vhdl = GraphType::Cascade(3, 15).to_vhdl(&HdlOptions::new(8, false)).unwrap();
+    /// assert!(vhdl.contains("entity mult_45 is"));
+    /// ```
+    fn to_vhdl(&self, options: &HdlOptions) -> Result<String, Error>;
+
+    /// Python source of an Amaranth module multiplying by the value of the structure
+    ///
+    /// ```
+    /// use constant_multiplication::{GraphType, GraphTypeExt, HdlOptions};
+    ///
+    /// let source = GraphType::Cascade(3, 15).to_amaranth(&HdlOptions::new(8, true)).unwrap();
+    /// assert!(source.contains("class Mult45(Elaboratable):"));
+    /// ```
+    fn to_amaranth(&self, options: &HdlOptions) -> Result<String, Error>;
+
+    /// C function multiplying by the value of the structure
+    ///
+    /// ```
+    /// use constant_multiplication::{GraphType, GraphTypeExt, HdlOptions};
+    ///
+    /// let c = GraphType::Cascade(3, 15).to_c(&HdlOptions::new(8, true)).unwrap();
+    /// assert!(c.contains("int16_t mult_45(int8_t x)"));
+    /// ```
+    fn to_c(&self, options: &HdlOptions) -> Result<String, Error>;
+
+    /// Rust function multiplying by the value of the structure
+    ///
+    /// ```
+    /// use constant_multiplication::{GraphType, GraphTypeExt, HdlOptions};
+    ///
+    /// let rust = GraphType::Cascade(3, 15).to_rust(&HdlOptions::new(8, false)).unwrap();
+    /// assert!(rust.contains("pub fn mult_45(x: u8) -> u16 {"));
+    /// ```
+    fn to_rust(&self, options: &HdlOptions) -> Result<String, Error>;
+
+    /// Vitis HLS C++ function multiplying by the value of the structure
+    ///
+    /// ```
+    /// use constant_multiplication::{GraphType, GraphTypeExt, HdlOptions};
+    ///
+    /// let cpp = GraphType::Cascade(3, 15).to_hls_cpp(&HdlOptions::new(8, true)).unwrap();
+    /// assert!(cpp.contains("ap_int<14> mult_45(ap_int<8> x)"));
+    /// ```
+    fn to_hls_cpp(&self, options: &HdlOptions) -> Result<String, Error>;
+
+    /// Graphviz DOT description of the adders of the structure
+    ///
+    /// ```
+    /// use constant_multiplication::{GraphType, GraphTypeExt};
+    ///
+    /// let dot = GraphType::Cascade(3, 15).to_dot().unwrap();
+    /// assert!(dot.contains("t1 -> t2 [label=\"+ << 4\"];"));
+    /// ```
+    fn to_dot(&self) -> Result<String, Error>;
+
+    /// BLIF netlist of full and half adders multiplying by the value of the structure
+    ///
+    /// ```
+    /// use constant_multiplication::{GraphType, GraphTypeExt, HdlOptions};
+    ///
+    /// let blif = GraphType::Cascade(3, 15).to_blif(&HdlOptions::new(8, false)).unwrap();
+    /// assert!(blif.contains(".model mult_45"));
+    /// ```
+    fn to_blif(&self, options: &HdlOptions) -> Result<String, Error>;
+
+    /// Yosys RTLIL netlist of full and half adders, see [`AdderGraph::to_rtlil`]
+    fn to_rtlil(&self, options: &HdlOptions) -> Result<String, Error>;
+
+    /// Full adders, half adders and inverters of each adder of the structure,
+    /// see [`AdderGraph::full_adders`]
+    fn full_adders(&self, options: &HdlOptions) -> Result<Vec<AdderCells>, Error>;
+
+    /// LUTs and carry-chain slices of the structure on an FPGA, see [`AdderGraph::fpga_cost`]
+    fn fpga_cost(&self, options: &HdlOptions, fpga: &FpgaOptions) -> Result<FpgaCost, Error>;
+
+    /// Python source building a B-ASIC SFG of the structure, see [`AdderGraph::to_b_asic`]
+    ///
+    /// ```
+    /// use constant_multiplication::{GraphType, GraphTypeExt};
+    ///
+    /// let source = GraphType::Cascade(3, 15).to_b_asic().unwrap();
+    /// assert!(source.contains("t2 = Subtraction(LeftShift(4, t1), t1, name=\"t2\")"));
+    /// ```
+    fn to_b_asic(&self) -> Result<String, Error>;
+
+    /// SVG drawing of the adders of the structure, see [`AdderGraph::to_svg`]
+    fn to_svg(&self) -> Result<String, Error>;
+
+    /// Adder graph string for PAGSuite and FloPoCo, see [`AdderGraph::to_pag`]
+    fn to_pag(&self) -> Result<String, Error>;
+}
 
-        for _ in 0..vec_len {
-            if remaining.is_empty() {
-                return Err("Unexpected end of data".to_string());
-            }
+impl GraphTypeExt for GraphType {
+    fn to_adder_graph(&self) -> Result<AdderGraph, Error> {
+        AdderGraph::from_graph_type(self, table())
+    }
 
-            let variant_tag = remaining[0];
-            remaining = &remaining[1..];
-
-            let graph_type = match variant_tag {
-                0 => {
-                    let params = decode_params(&mut remaining, 2)?;
-                    GraphType {
-                        variant: "Adder",
-                        params,
-                    }
-                }
-                1 => {
-                    let params = decode_params(&mut remaining, 2)?;
-                    GraphType {
-                        variant: "Subtractor",
-                        params,
-                    }
-                }
-                2 => {
-                    let params = decode_params(&mut remaining, 2)?;
-                    GraphType {
-                        variant: "Cascade",
-                        params,
-                    }
-                }
-                3 => {
-                    let params = decode_params(&mut remaining, 4)?;
-                    GraphType {
-                        variant: "Leapfrog4_1",
-                        params,
-                    }
-                }
-                4 => {
-                    let params = decode_params(&mut remaining, 4)?;
-                    GraphType {
-                        variant: "Leapfrog4_2",
-                        params,
-                    }
-                }
-                5 => {
-                    let params = decode_params(&mut remaining, 4)?;
-                    GraphType {
-                        variant: "Leapfrog4_3",
-                        params,
-                    }
-                }
-                6 => {
-                    let params = decode_params(&mut remaining, 4)?;
-                    GraphType {
-                        variant: "Leapfrog4_4",
-                        params,
-                    }
-                }
-                7 => {
-                    let params = decode_params(&mut remaining, 5)?;
-                    GraphType {
-                        variant: "Leapfrog5_1",
-                        params,
-                    }
-                }
-                8 => {
-                    let params = decode_params(&mut remaining, 5)?;
-                    GraphType {
-                        variant: "Leapfrog5_2",
-                        params,
-                    }
-                }
-                9 => {
-                    let params = decode_params(&mut remaining, 5)?;
-                    GraphType {
-                        variant: "Leapfrog5_3",
-                        params,
-                    }
-                }
-                10 => {
-                    let params = decode_params(&mut remaining, 5)?;
-                    GraphType {
-                        variant: "Leapfrog5_4",
-                        params,
-                    }
-                }
-                11 => {
-                    let params = decode_params(&mut remaining, 7)?;
-                    GraphType {
-                        variant: "Leapfrog7_1",
-                        params,
-                    }
-                }
-                12 => {
-                    let params = decode_params(&mut remaining, 7)?;
-                    GraphType {
-                        variant: "Leapfrog7_2",
-                        params,
-                    }
-                }
-                13 => {
-                    let params = decode_params(&mut remaining, 7)?;
-                    GraphType {
-                        variant: "Leapfrog7_3",
-                        params,
-                    }
-                }
-                14 => {
-                    let params = decode_params(&mut remaining, 7)?;
-                    GraphType {
-                        variant: "Leapfrog7_4",
-                        params,
-                    }
-                }
-                15 => {
-                    let params = decode_params(&mut remaining, 7)?;
-                    GraphType {
-                        variant: "Leapfrog7_5",
-                        params,
-                    }
-                }
-                16 => {
-                    let params = decode_params(&mut remaining, 7)?;
-                    GraphType {
-                        variant: "Leapfrog7_6",
-                        params,
-                    }
-                }
-                17 => {
-                    let params = decode_params(&mut remaining, 7)?;
-                    GraphType {
-                        variant: "Leapfrog7_7",
-                        params,
-                    }
-                }
-                18 => {
-                    let params = decode_params(&mut remaining, 7)?;
-                    GraphType {
-                        variant: "Leapfrog7_8",
-                        params,
-                    }
-                }
-                _ => return Err(format!("Unknown variant tag: {variant_tag}")),
-            };
-
-            type_vec.push(graph_type);
+    fn verify(&self, n: u64) -> Result<bool, Error> {
+        let odd = if n > 0 { n >> n.trailing_zeros() } else { n };
+        if self.value() != Some(odd) {
+            return Ok(false);
+        }
+        let cost = adder_cost(n)?;
+        match self.to_adder_graph() {
+            Ok(graph) => Ok(graph.adder_count() == usize::from(cost)),
+            Err(Error::InvalidStructure(_)) => Ok(false),
+            Err(e) => Err(e),
         }
+    }
 
-        result.push(type_vec);
+    fn equivalents(&self) -> Result<Vec<GraphType>, Error> {
+        symmetry::equivalents(self, table())
     }
 
-    Ok(result)
-}
+    fn depth(&self) -> Result<u32, Error> {
+        Ok(self.to_adder_graph()?.depth())
+    }
 
-/// Get adder cost at index (right-shifts even indices until odd)
-#[pyfunction]
-fn adder_cost(mut idx: usize) -> PyResult<u8> {
-    // Right-shift even indices until odd
-    if idx > 0 {
-        let shift = idx.trailing_zeros();
-        idx >>= shift;
+    fn to_verilog(&self, options: &HdlOptions) -> Result<String, Error> {
+        self.to_adder_graph()?.to_verilog(options)
     }
 
-    let value_position = idx / 2;
-    if value_position >= DATA_COUNT {
-        return Err(PyIndexError::new_err("Index out of range"));
+    fn to_vhdl(&self, options: &HdlOptions) -> Result<String, Error> {
+        self.to_adder_graph()?.to_vhdl(options)
     }
 
-    let bit_offset = value_position * 3;
-    let byte_offset = bit_offset / 8;
-    let bit_in_byte = bit_offset % 8;
+    fn to_amaranth(&self, options: &HdlOptions) -> Result<String, Error> {
+        self.to_adder_graph()?.to_amaranth(options)
+    }
 
-    if byte_offset >= DATA_FILE.len() - DATA_OFFSET {
-        return Err(PyValueError::new_err("Data corruption"));
+    fn to_c(&self, options: &HdlOptions) -> Result<String, Error> {
+        self.to_adder_graph()?.to_c(options)
     }
 
-    let mut val = (DATA_FILE[byte_offset + DATA_OFFSET] >> bit_in_byte) & 0b111;
+    fn to_rust(&self, options: &HdlOptions) -> Result<String, Error> {
+        self.to_adder_graph()?.to_rust(options)
+    }
 
-    // Handle values that span two bytes
-    if bit_in_byte > 5 && byte_offset + 1 < DATA_FILE.len() - DATA_OFFSET {
-        let bits_from_next = 3 - (8 - bit_in_byte);
-        val |= (DATA_FILE[byte_offset + 1 + DATA_OFFSET] & ((1 << bits_from_next) - 1))
-            << (8 - bit_in_byte);
+    fn to_hls_cpp(&self, options: &HdlOptions) -> Result<String, Error> {
+        self.to_adder_graph()?.to_hls_cpp(options)
     }
 
-    Ok(val & 0b111)
-}
+    fn to_dot(&self) -> Result<String, Error> {
+        Ok(self.to_adder_graph()?.to_dot())
+    }
 
-/// Get info about the embedded data
-#[pyfunction]
-fn info() -> String {
-    format!(
-        "Embedded data: {} elements, {} bytes packed, graph types: {} bytes compressed",
-        DATA_COUNT * 2,
-        DATA_FILE.len() - DATA_OFFSET,
-        GRAPH_TYPES_BYTES.len()
-    )
-}
+    fn to_blif(&self, options: &HdlOptions) -> Result<String, Error> {
+        self.to_adder_graph()?.to_blif(options)
+    }
 
-/// Get graph types at index (right-shifts even indices until odd)
-#[pyfunction]
-fn get_graph_types(py: Python, mut idx: usize) -> PyResult<Py<PyAny>> {
-    // Right-shift even indices until odd
-    if idx > 0 {
-        let shift = idx.trailing_zeros();
-        idx >>= shift;
+    fn to_rtlil(&self, options: &HdlOptions) -> Result<String, Error> {
+        self.to_adder_graph()?.to_rtlil(options)
     }
 
-    let all_types = get_graph_types_data()?;
+    fn full_adders(&self, options: &HdlOptions) -> Result<Vec<AdderCells>, Error> {
+        self.to_adder_graph()?.full_adders(options)
+    }
 
-    // Convert odd index to position in the compact array
-    // index 1 -> position 0, index 3 -> position 1, index 5 -> position 2, etc.
-    let position = idx / 2;
+    fn fpga_cost(&self, options: &HdlOptions, fpga: &FpgaOptions) -> Result<FpgaCost, Error> {
+        self.to_adder_graph()?.fpga_cost(options, fpga)
+    }
 
-    if position >= all_types.len() {
-        return Err(PyIndexError::new_err("Index out of range"));
+    fn to_b_asic(&self) -> Result<String, Error> {
+        Ok(self.to_adder_graph()?.to_b_asic())
     }
 
-    let types = &all_types[position];
-    let list = PyList::empty(py);
-    for gt in types {
-        list.append(gt.clone())?;
+    fn to_svg(&self) -> Result<String, Error> {
+        Ok(self.to_adder_graph()?.to_svg())
+    }
+
+    fn to_pag(&self) -> Result<String, Error> {
+        Ok(self.to_adder_graph()?.to_pag())
     }
-    Ok(list.into())
 }
 
-/// Get all graph types as a list
-#[pyfunction]
-fn get_all_graph_types(py: Python) -> PyResult<Py<PyAny>> {
-    let all_types = get_graph_types_data()?;
-    let result = PyList::empty(py);
+/// Minimum number of adders and subtractors to multiply by n
+pub fn adder_cost(n: u64) -> Result<u8, Error> {
+    table().adder_cost(n)
+}
 
-    for types in all_types {
-        let inner_list = PyList::empty(py);
-        for gt in types {
-            inner_list.append(gt)?;
-        }
-        result.append(inner_list)?;
-    }
+/// Minimum number of adders to multiply by n with at most max_depth adders on any path
+///
+/// `None` when no structure that shallow is known, see
+/// [`CostTable::adder_cost_within_depth`].
+///
+/// ```
+/// use constant_multiplication::{adder_cost, adder_cost_within_depth};
+///
+/// assert_eq!(adder_cost(45).unwrap(), 2);
+/// assert_eq!(adder_cost_within_depth(45, 2).unwrap(), Some(2));
+/// assert_eq!(adder_cost_within_depth(45, 1).unwrap(), None);
+///
+/// // One more adder gives a shallower structure
+/// assert_eq!(adder_cost(175795).unwrap(), 5);
+/// assert_eq!(adder_cost_within_depth(175795, 4).unwrap(), Some(6));
+/// ```
+pub fn adder_cost_within_depth(n: u64, max_depth: u32) -> Result<Option<u8>, Error> {
+    table().adder_cost_within_depth(n, max_depth)
+}
+
+/// All stored structures multiplying by n with the minimum number of adders
+///
+/// There are no structures for 1, or any power of two, as no adders are needed.
+pub fn graph_types(n: u64) -> Result<&'static [GraphType], Error> {
+    table().graph_types(n)
+}
+
+/// All structures multiplying by n with the minimum number of adders, including
+/// the ones left out of the tables by symmetry
+///
+/// The stored structures come first, followed by their [`GraphTypeExt::equivalents`].
+pub fn expand_symmetric(n: u64) -> Result<Vec<GraphType>, Error> {
+    table().expand_symmetric(n)
+}
+
+/// Adders multiplying by n, from the first stored structure of its odd part
+pub fn adder_graph(n: u64) -> Result<AdderGraph, Error> {
+    AdderGraph::from_coefficient(n, table())
+}
 
-    Ok(result.into())
+/// Verilog module multiplying by n, see [`AdderGraph::to_verilog`]
+pub fn to_verilog(n: u64, options: &HdlOptions) -> Result<String, Error> {
+    adder_graph(n)?.to_verilog(options)
 }
 
-fn get_graph_types_data() -> PyResult<Vec<Vec<GraphType>>> {
-    // Decompress the LZ4 data
-    let decompressed = lz4_flex::decompress_size_prepended(GRAPH_TYPES_BYTES)
-        .map_err(|e| PyValueError::new_err(format!("Failed to decompress: {e}")))?;
+/// VHDL-2008 entity multiplying by n, see [`AdderGraph::to_vhdl`]
+pub fn to_vhdl(n: u64, options: &HdlOptions) -> Result<String, Error> {
+    adder_graph(n)?.to_vhdl(options)
+}
+
+/// Python source of an Amaranth module multiplying by n, see [`AdderGraph::to_amaranth`]
+pub fn to_amaranth(n: u64, options: &HdlOptions) -> Result<String, Error> {
+    adder_graph(n)?.to_amaranth(options)
+}
+
+/// C function multiplying by n, see [`AdderGraph::to_c`]
+pub fn to_c(n: u64, options: &HdlOptions) -> Result<String, Error> {
+    adder_graph(n)?.to_c(options)
+}
+
+/// Rust function multiplying by n, see [`AdderGraph::to_rust`]
+pub fn to_rust(n: u64, options: &HdlOptions) -> Result<String, Error> {
+    adder_graph(n)?.to_rust(options)
+}
+
+/// Vitis HLS C++ function multiplying by n, see [`AdderGraph::to_hls_cpp`]
+pub fn to_hls_cpp(n: u64, options: &HdlOptions) -> Result<String, Error> {
+    adder_graph(n)?.to_hls_cpp(options)
+}
+
+/// Graphviz DOT description of the adders multiplying by n, see [`AdderGraph::to_dot`]
+pub fn to_dot(n: u64) -> Result<String, Error> {
+    Ok(adder_graph(n)?.to_dot())
+}
+
+/// BLIF netlist of full and half adders multiplying by n, see [`AdderGraph::to_blif`]
+pub fn to_blif(n: u64, options: &HdlOptions) -> Result<String, Error> {
+    adder_graph(n)?.to_blif(options)
+}
+
+/// Yosys RTLIL netlist of full and half adders multiplying by n, see [`AdderGraph::to_rtlil`]
+pub fn to_rtlil(n: u64, options: &HdlOptions) -> Result<String, Error> {
+    adder_graph(n)?.to_rtlil(options)
+}
+
+/// Python source building a B-ASIC SFG multiplying by n, see [`AdderGraph::to_b_asic`]
+pub fn to_b_asic(n: u64) -> Result<String, Error> {
+    Ok(adder_graph(n)?.to_b_asic())
+}
+
+/// Adder graph string multiplying by n for PAGSuite and FloPoCo, see [`AdderGraph::to_pag`]
+pub fn to_pag(n: u64) -> Result<String, Error> {
+    Ok(adder_graph(n)?.to_pag())
+}
+
+/// Structures of all odd integers in the tables, the ones of n at position `n / 2`
+pub fn all_graph_types() -> Result<Vec<&'static [GraphType]>, Error> {
+    table().all_graph_types()
+}
 
-    // Deserialize with varint decoding
-    deserialize_graph_types(&decompressed)
-        .map_err(|e| PyValueError::new_err(format!("Failed to deserialize: {e}")))
+/// Largest odd integer in the tables
+pub fn max_coefficient() -> u64 {
+    table().max_coefficient()
 }
 
-#[pymodule]
-fn constant_multiplication(m: &Bound<'_, PyModule>) -> PyResult<()> {
-    m.add_class::<GraphType>()?;
-    m.add_function(wrap_pyfunction!(adder_cost, m)?)?;
-    m.add_function(wrap_pyfunction!(info, m)?)?;
-    m.add_function(wrap_pyfunction!(get_graph_types, m)?)?;
-    m.add_function(wrap_pyfunction!(get_all_graph_types, m)?)?;
-    Ok(())
+/// Description of the table data
+pub fn info() -> String {
+    table().info()
 }
@@ -0,0 +1,786 @@
+// src/netlist.rs
+use crate::hdl::{HdlGraph, HdlOptions};
+use crate::{AdderGraph, Error, Operand, Source};
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+use std::iter::Sum;
+use std::ops::Range;
+
+/// Cells of one or more adders in the netlists of [`AdderGraph::to_blif`]
+///
+/// Cells are only counted for bits that are not constant, so zeros shifted in
+/// below an operand need none, and the carry of a subtractor is propagated
+/// through the bits where the inverted operand is constant.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AdderCells {
+    pub full_adders: usize,
+    pub half_adders: usize,
+    /// Inverters of subtracted operands
+    pub inverters: usize,
+}
+
+impl Sum for AdderCells {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(AdderCells::default(), |total, cells| AdderCells {
+            full_adders: total.full_adders + cells.full_adders,
+            half_adders: total.half_adders + cells.half_adders,
+            inverters: total.inverters + cells.inverters,
+        })
+    }
+}
+
+/// A bit of a signal, either constant or a net
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Bit {
+    Zero,
+    One,
+    Net(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CellKind {
+    /// Inputs a, b and c, outputs sum and carry
+    FullAdder,
+    /// Inputs a and b, outputs sum and carry
+    HalfAdder,
+    /// Input a, output the inverse
+    Inverter,
+}
+
+pub(crate) struct Cell {
+    pub kind: CellKind,
+    pub inputs: Vec<Bit>,
+    pub outputs: Vec<usize>,
+}
+
+/// Ripple-carry adders of a graph, built from full and half adders
+///
+/// Signals are as wide as in [`AdderGraph::to_verilog`]. Bits that are
+/// constant, such as the zeros shifted in or the high bits of unsigned
+/// operands, are propagated instead of adding them, so an adder only gets
+/// cells for the bits where its operands overlap and the carry chain beyond.
+/// Subtractors invert the subtracted operand and add one through the carry.
+pub(crate) struct Netlist<'a> {
+    pub options: &'a HdlOptions,
+    pub value: u64,
+    pub adder_count: usize,
+    pub cells: Vec<Cell>,
+    /// Range of the cells of each adder
+    pub node_cells: Vec<Range<usize>>,
+    pub output: Vec<Bit>,
+    /// Nets are numbered from 0, the input bits first
+    pub net_count: usize,
+    // Inverse of each net that is inverted, both ways
+    inverted: HashMap<usize, usize>,
+}
+
+impl<'a> Netlist<'a> {
+    pub fn new(graph: &AdderGraph, options: &'a HdlOptions) -> Self {
+        let widths = HdlGraph::new(graph, options);
+        let input_width = options.input_width as usize;
+        let mut netlist = Netlist {
+            options,
+            value: graph.value(),
+            adder_count: graph.nodes.len(),
+            cells: Vec::new(),
+            node_cells: Vec::with_capacity(graph.nodes.len()),
+            output: Vec::new(),
+            net_count: input_width,
+            inverted: HashMap::new(),
+        };
+
+        let mut signals: Vec<Vec<Bit>> = Vec::with_capacity(graph.nodes.len());
+        let input: Vec<Bit> = (0..input_width).map(Bit::Net).collect();
+        let bits = |signals: &[Vec<Bit>], source: Source| match source {
+            Source::Input => input.clone(),
+            Source::Node(idx) => signals[idx].clone(),
+        };
+        for (node, hdl_node) in graph.nodes.iter().zip(&widths.nodes) {
+            let first_cell = netlist.cells.len();
+            let width = hdl_node.sum_width.unwrap_or(hdl_node.width) as usize;
+            // Add the positive operand to the inverse of the negative one
+            let [a, b] = &node.inputs;
+            let (a, b) = if a.negative { (b, a) } else { (a, b) };
+            let a_bits = netlist.extended(&bits(&signals, a.source), a, width);
+            let mut b_bits = netlist.extended(&bits(&signals, b.source), b, width);
+            let mut carry = Bit::Zero;
+            if b.negative {
+                b_bits = b_bits.into_iter().map(|bit| netlist.inverse(bit)).collect();
+                carry = Bit::One;
+            }
+            let mut sum = Vec::with_capacity(width);
+            for (a, b) in a_bits.into_iter().zip(b_bits) {
+                let (bit, next) = netlist.add_bits([a, b, carry]);
+                sum.push(bit);
+                carry = next;
+            }
+            // The bits shifted out are known to be zero
+            signals.push(sum.split_off(node.right_shift as usize));
+            netlist.node_cells.push(first_cell..netlist.cells.len());
+        }
+        let output_width = widths.output_width() as usize;
+        netlist.output = netlist.extended(
+            &bits(&signals, graph.output.source),
+            &graph.output,
+            output_width,
+        );
+        netlist
+    }
+
+    pub fn count(&self, kind: CellKind) -> usize {
+        self.cells.iter().filter(|cell| cell.kind == kind).count()
+    }
+
+    /// Cells of each adder
+    pub fn adder_cells(&self) -> Vec<AdderCells> {
+        self.node_cells
+            .iter()
+            .map(|range| {
+                let cells = &self.cells[range.clone()];
+                let count = |kind| cells.iter().filter(|cell| cell.kind == kind).count();
+                AdderCells {
+                    full_adders: count(CellKind::FullAdder),
+                    half_adders: count(CellKind::HalfAdder),
+                    inverters: count(CellKind::Inverter),
+                }
+            })
+            .collect()
+    }
+
+    fn new_net(&mut self) -> usize {
+        self.net_count += 1;
+        self.net_count - 1
+    }
+
+    /// Bits of a shifted operand, extended or truncated to a width
+    fn extended(&self, bits: &[Bit], operand: &Operand, width: usize) -> Vec<Bit> {
+        let high = match bits.last() {
+            Some(&msb) if self.options.signed => msb,
+            _ => Bit::Zero,
+        };
+        std::iter::repeat_n(Bit::Zero, operand.shift as usize)
+            .chain(bits.iter().copied())
+            .chain(std::iter::repeat(high))
+            .take(width)
+            .collect()
+    }
+
+    fn inverse(&mut self, bit: Bit) -> Bit {
+        match bit {
+            Bit::Zero => Bit::One,
+            Bit::One => Bit::Zero,
+            Bit::Net(net) => {
+                if let Some(&inverse) = self.inverted.get(&net) {
+                    return Bit::Net(inverse);
+                }
+                let inverse = self.new_net();
+                self.cells.push(Cell {
+                    kind: CellKind::Inverter,
+                    inputs: vec![bit],
+                    outputs: vec![inverse],
+                });
+                self.inverted.insert(net, inverse);
+                self.inverted.insert(inverse, net);
+                Bit::Net(inverse)
+            }
+        }
+    }
+
+    /// Sum and carry of three bits, with cells only for the bits that are not constant
+    fn add_bits(&mut self, bits: [Bit; 3]) -> (Bit, Bit) {
+        let ones = bits.iter().filter(|&&bit| bit == Bit::One).count();
+        let nets: Vec<Bit> = bits
+            .into_iter()
+            .filter(|bit| matches!(bit, Bit::Net(_)))
+            .collect();
+        let constant = |set: bool| if set { Bit::One } else { Bit::Zero };
+        let kind = match (nets.len(), ones) {
+            (0, _) => return (constant(ones % 2 == 1), constant(ones >= 2)),
+            (1, 0) => return (nets[0], Bit::Zero),
+            (1, 1) => return (self.inverse(nets[0]), nets[0]),
+            (1, _) => return (nets[0], Bit::One),
+            (2, 0) => CellKind::HalfAdder,
+            _ => CellKind::FullAdder,
+        };
+        let mut inputs = nets;
+        if kind == CellKind::FullAdder && inputs.len() == 2 {
+            inputs.push(Bit::One);
+        }
+        let outputs = vec![self.new_net(), self.new_net()];
+        let (sum, carry) = (Bit::Net(outputs[0]), Bit::Net(outputs[1]));
+        self.cells.push(Cell {
+            kind,
+            inputs,
+            outputs,
+        });
+        (sum, carry)
+    }
+
+    fn summary(&self) -> String {
+        format!(
+            "Multiplication by {} using {} adders: {} full adders, {} half adders, {} inverters",
+            self.value,
+            self.adder_count,
+            self.count(CellKind::FullAdder),
+            self.count(CellKind::HalfAdder),
+            self.count(CellKind::Inverter)
+        )
+    }
+}
+
+impl AdderGraph {
+    /// Full adders, half adders and inverters of each adder for an input of
+    /// `options.input_width` bits, as in [`AdderGraph::to_blif`]
+    ///
+    /// Adders with the same operands can need very different numbers of
+    /// cells, depending on how much their shifted operands overlap.
+    /// `options.name` and `options.pipeline` are ignored.
+    ///
+    /// ```
+    /// use constant_multiplication::{AdderCells, GraphType, GraphTypeExt, HdlOptions};
+    ///
+    /// let graph = GraphType::Cascade(3, 15).to_adder_graph().unwrap();
+    /// let cells = graph.full_adders(&HdlOptions::new(8, false)).unwrap();
+    /// assert_eq!(cells.len(), 2);
+    /// let total: AdderCells = cells.into_iter().sum();
+    /// assert!(total.full_adders > 0);
+    /// ```
+    pub fn full_adders(&self, options: &HdlOptions) -> Result<Vec<AdderCells>, Error> {
+        options.check()?;
+        Ok(Netlist::new(self, options).adder_cells())
+    }
+
+    /// BLIF netlist of full and half adders computing `y = value * x`
+    ///
+    /// Adders are ripple-carry chains of `FA` and `HA` subcircuits, defined in
+    /// the same file, with inverters for subtracted operands. No cells are
+    /// used for constant bits, such as zeros shifted in. The result can be
+    /// read by ABC and Yosys. `options.pipeline` is ignored.
+    pub fn to_blif(&self, options: &HdlOptions) -> Result<String, Error> {
+        options.check()?;
+        let mut out = String::new();
+        write_blif(&mut out, &Netlist::new(self, options))
+            .expect("writing to a String cannot fail");
+        Ok(out)
+    }
+
+    /// Yosys RTLIL netlist of full and half adders computing `y = value * x`
+    ///
+    /// The cells are the same as in [`AdderGraph::to_blif`], with `FA` and
+    /// `HA` modules built from gate cells. `options.pipeline` is ignored.
+    pub fn to_rtlil(&self, options: &HdlOptions) -> Result<String, Error> {
+        options.check()?;
+        let mut out = String::new();
+        write_rtlil(&mut out, &Netlist::new(self, options))
+            .expect("writing to a String cannot fail");
+        Ok(out)
+    }
+}
+
+fn write_blif(out: &mut String, netlist: &Netlist) -> fmt::Result {
+    let options = netlist.options;
+    let net = |bit: Bit| match bit {
+        Bit::Zero => "zero".to_string(),
+        Bit::One => "one".to_string(),
+        Bit::Net(net) if net < options.input_width as usize => format!("x[{net}]"),
+        Bit::Net(net) => format!("n{net}"),
+    };
+
+    writeln!(out, "# {}", netlist.summary())?;
+    writeln!(out, ".model {}", options.name(netlist.value))?;
+    let ports = |name: &str, width: usize| {
+        (0..width)
+            .map(|bit| format!("{name}[{bit}]"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    writeln!(out, ".inputs {}", ports("x", options.input_width as usize))?;
+    writeln!(out, ".outputs {}", ports("y", netlist.output.len()))?;
+    // Constant nets, only driven if used
+    let uses = |constant: Bit| {
+        netlist
+            .cells
+            .iter()
+            .any(|cell| cell.inputs.contains(&constant))
+    };
+    if uses(Bit::One) {
+        writeln!(out, ".names one")?;
+        writeln!(out, "1")?;
+    }
+    for cell in &netlist.cells {
+        let inputs: Vec<String> = cell.inputs.iter().map(|&bit| net(bit)).collect();
+        let outputs: Vec<String> = cell
+            .outputs
+            .iter()
+            .map(|&output| net(Bit::Net(output)))
+            .collect();
+        match cell.kind {
+            CellKind::FullAdder => writeln!(
+                out,
+                ".subckt FA a={} b={} c={} s={} co={}",
+                inputs[0], inputs[1], inputs[2], outputs[0], outputs[1]
+            )?,
+            CellKind::HalfAdder => writeln!(
+                out,
+                ".subckt HA a={} b={} s={} co={}",
+                inputs[0], inputs[1], outputs[0], outputs[1]
+            )?,
+            CellKind::Inverter => {
+                writeln!(out, ".names {} {}", inputs[0], outputs[0])?;
+                writeln!(out, "0 1")?;
+            }
+        }
+    }
+    for (idx, &bit) in netlist.output.iter().enumerate() {
+        match bit {
+            Bit::Zero => writeln!(out, ".names y[{idx}]")?,
+            Bit::One => writeln!(out, ".names y[{idx}]\n1")?,
+            Bit::Net(_) => writeln!(out, ".names {} y[{idx}]\n1 1", net(bit))?,
+        }
+    }
+    writeln!(out, ".end")?;
+    writeln!(out)?;
+
+    writeln!(out, ".model FA")?;
+    writeln!(out, ".inputs a b c")?;
+    writeln!(out, ".outputs s co")?;
+    writeln!(out, ".names a b c s\n100 1\n010 1\n001 1\n111 1")?;
+    writeln!(out, ".names a b c co\n11- 1\n1-1 1\n-11 1")?;
+    writeln!(out, ".end")?;
+    writeln!(out)?;
+    writeln!(out, ".model HA")?;
+    writeln!(out, ".inputs a b")?;
+    writeln!(out, ".outputs s co")?;
+    writeln!(out, ".names a b s\n10 1\n01 1")?;
+    writeln!(out, ".names a b co\n11 1")?;
+    writeln!(out, ".end")
+}
+
+fn write_rtlil(out: &mut String, netlist: &Netlist) -> fmt::Result {
+    let options = netlist.options;
+    let input_width = options.input_width as usize;
+    let net = |bit: Bit| match bit {
+        Bit::Zero => "1'0".to_string(),
+        Bit::One => "1'1".to_string(),
+        Bit::Net(net) if net < input_width => format!("\\x [{net}]"),
+        Bit::Net(net) => format!("\\n{net}"),
+    };
+
+    writeln!(out, "# {}", netlist.summary())?;
+    writeln!(out, "module \\{}", options.name(netlist.value))?;
+    writeln!(out, "  wire width {input_width} input 1 \\x")?;
+    writeln!(out, "  wire width {} output 2 \\y", netlist.output.len())?;
+    for net in input_width..netlist.net_count {
+        writeln!(out, "  wire \\n{net}")?;
+    }
+    for (idx, cell) in netlist.cells.iter().enumerate() {
+        let (kind, inputs, outputs): (&str, &[&str], &[&str]) = match cell.kind {
+            CellKind::FullAdder => ("\\FA", &["\\a", "\\b", "\\c"], &["\\s", "\\co"]),
+            CellKind::HalfAdder => ("\\HA", &["\\a", "\\b"], &["\\s", "\\co"]),
+            CellKind::Inverter => ("$_NOT_", &["\\A"], &["\\Y"]),
+        };
+        writeln!(out, "  cell {kind} \\c{idx}")?;
+        for (port, &bit) in inputs.iter().zip(&cell.inputs) {
+            writeln!(out, "    connect {port} {}", net(bit))?;
+        }
+        for (port, &output) in outputs.iter().zip(&cell.outputs) {
+            writeln!(out, "    connect {port} {}", net(Bit::Net(output)))?;
+        }
+        writeln!(out, "  end")?;
+    }
+    for (idx, &bit) in netlist.output.iter().enumerate() {
+        writeln!(out, "  connect \\y [{idx}] {}", net(bit))?;
+    }
+    writeln!(out, "end")?;
+
+    for (name, inputs) in [("FA", 3), ("HA", 2)] {
+        writeln!(out)?;
+        writeln!(out, "module \\{name}")?;
+        for (idx, port) in ["a", "b", "c"][..inputs].iter().enumerate() {
+            writeln!(out, "  wire input {} \\{port}", idx + 1)?;
+        }
+        writeln!(out, "  wire output {} \\s", inputs + 1)?;
+        writeln!(out, "  wire output {} \\co", inputs + 2)?;
+        let gates: &[(&str, &str, &str, &str)] = if inputs == 3 {
+            writeln!(out, "  wire \\p")?;
+            writeln!(out, "  wire \\g")?;
+            writeln!(out, "  wire \\t")?;
+            // s = a ^ b ^ c, co = a & b | (a ^ b) & c
+            &[
+                ("$_XOR_", "\\a", "\\b", "\\p"),
+                ("$_XOR_", "\\p", "\\c", "\\s"),
+                ("$_AND_", "\\a", "\\b", "\\g"),
+                ("$_AND_", "\\p", "\\c", "\\t"),
+                ("$_OR_", "\\g", "\\t", "\\co"),
+            ]
+        } else {
+            &[
+                ("$_XOR_", "\\a", "\\b", "\\s"),
+                ("$_AND_", "\\a", "\\b", "\\co"),
+            ]
+        };
+        for (idx, (kind, a, b, y)) in gates.iter().enumerate() {
+            writeln!(out, "  cell {kind} \\g{idx}")?;
+            writeln!(out, "    connect \\A {a}")?;
+            writeln!(out, "    connect \\B {b}")?;
+            writeln!(out, "    connect \\Y {y}")?;
+            writeln!(out, "  end")?;
+        }
+        writeln!(out, "end")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{assert_designs_multiply, assert_multiplies};
+    use crate::{GraphType, GraphTypeExt, adder_graph};
+    use std::collections::HashSet;
+
+    /// A gate given by the input patterns setting its output, or an instance
+    /// of another module with its port connections
+    enum Gate {
+        Table {
+            inputs: Vec<String>,
+            output: String,
+            rows: Vec<String>,
+        },
+        Instance {
+            module: String,
+            ports: Vec<(String, String)>,
+        },
+    }
+
+    #[derive(Default)]
+    struct Module {
+        inputs: Vec<String>,
+        outputs: Vec<String>,
+        gates: Vec<Gate>,
+    }
+
+    fn last(modules: &mut [(String, Module)]) -> &mut Module {
+        &mut modules.last_mut().unwrap().1
+    }
+
+    /// Modules of a BLIF netlist, the main one first
+    fn parse_blif(blif: &str) -> Vec<(String, Module)> {
+        let mut modules: Vec<(String, Module)> = Vec::new();
+        for line in blif
+            .lines()
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        {
+            let words: Vec<String> = line.split_whitespace().map(str::to_string).collect();
+            match words[0].as_str() {
+                ".model" => modules.push((words[1].clone(), Module::default())),
+                ".inputs" => last(&mut modules).inputs = words[1..].to_vec(),
+                ".outputs" => last(&mut modules).outputs = words[1..].to_vec(),
+                ".names" => {
+                    let (output, inputs) = words[1..].split_last().unwrap();
+                    last(&mut modules).gates.push(Gate::Table {
+                        inputs: inputs.to_vec(),
+                        output: output.clone(),
+                        rows: Vec::new(),
+                    });
+                }
+                ".subckt" => {
+                    let ports = words[2..]
+                        .iter()
+                        .map(|port| {
+                            let (formal, actual) = port.split_once('=').unwrap();
+                            (formal.to_string(), actual.to_string())
+                        })
+                        .collect();
+                    last(&mut modules).gates.push(Gate::Instance {
+                        module: words[1].clone(),
+                        ports,
+                    });
+                }
+                ".end" => {}
+                _ => {
+                    let Some(Gate::Table { inputs, rows, .. }) =
+                        last(&mut modules).gates.last_mut()
+                    else {
+                        panic!("unexpected line {line}");
+                    };
+                    let (pattern, output) = match words.as_slice() {
+                        [output] if inputs.is_empty() => ("", output.as_str()),
+                        [pattern, output] => (pattern.as_str(), output.as_str()),
+                        _ => panic!("unexpected line {line}"),
+                    };
+                    assert_eq!(output, "1", "{line}");
+                    assert_eq!(pattern.len(), inputs.len(), "{line}");
+                    rows.push(pattern.to_string());
+                }
+            }
+        }
+        modules
+    }
+
+    /// Modules of an RTLIL netlist, the main one first, with the gate cells as tables
+    fn parse_rtlil(rtlil: &str) -> Vec<(String, Module)> {
+        let mut modules: Vec<(String, Module)> = Vec::new();
+        let mut cell: Option<(String, Vec<(String, String)>)> = None;
+        for line in rtlil.lines().map(str::trim) {
+            let module = modules.last_mut().map(|(_, module)| module);
+            if let Some(name) = line.strip_prefix("module \\") {
+                modules.push((name.to_string(), Module::default()));
+            } else if let Some(wire) = line.strip_prefix("wire ") {
+                let words: Vec<&str> = wire.split_whitespace().collect();
+                let name = words.last().unwrap().to_string();
+                let width = match words.as_slice() {
+                    ["width", width, ..] => Some(width.parse::<usize>().unwrap()),
+                    _ => None,
+                };
+                let nets: Vec<String> = match width {
+                    Some(width) => (0..width).map(|bit| format!("{name} [{bit}]")).collect(),
+                    None => vec![name],
+                };
+                let module = module.unwrap();
+                if words.contains(&"input") {
+                    module.inputs.extend(nets);
+                } else if words.contains(&"output") {
+                    module.outputs.extend(nets);
+                }
+            } else if let Some(rest) = line.strip_prefix("cell ") {
+                let (kind, _) = rest.split_once(' ').unwrap();
+                cell = Some((kind.to_string(), Vec::new()));
+            } else if let Some(rest) = line.strip_prefix("connect ") {
+                match &mut cell {
+                    Some((_, ports)) => {
+                        let (port, net) = rest.split_once(' ').unwrap();
+                        ports.push((port.to_string(), net.to_string()));
+                    }
+                    // A bit of the output, such as `\y [2] \n7`
+                    None => {
+                        let (output, net) = rest.split_once("] ").unwrap();
+                        module.unwrap().gates.push(Gate::Table {
+                            inputs: vec![net.to_string()],
+                            output: format!("{output}]"),
+                            rows: vec!["1".to_string()],
+                        });
+                    }
+                }
+            } else if line == "end" {
+                let Some((kind, ports)) = cell.take() else {
+                    continue;
+                };
+                let rows: &[&str] = match kind.as_str() {
+                    "$_NOT_" => &["0"],
+                    "$_AND_" => &["11"],
+                    "$_OR_" => &["1-", "-1"],
+                    "$_XOR_" => &["10", "01"],
+                    _ => {
+                        let module = kind.strip_prefix('\\').unwrap().to_string();
+                        let ports = ports
+                            .into_iter()
+                            .map(|(port, net)| (port.trim_start_matches('\\').to_string(), net))
+                            .collect();
+                        last(&mut modules)
+                            .gates
+                            .push(Gate::Instance { module, ports });
+                        continue;
+                    }
+                };
+                let (output, inputs) = ports.split_last().unwrap();
+                last(&mut modules).gates.push(Gate::Table {
+                    inputs: inputs.iter().map(|(_, net)| net.clone()).collect(),
+                    output: output.1.clone(),
+                    rows: rows.iter().map(|row| row.to_string()).collect(),
+                });
+            }
+        }
+        // Ports of the submodules are referred to without the backslash
+        for (_, module) in &mut modules[1..] {
+            for port in module.inputs.iter_mut().chain(&mut module.outputs) {
+                *port = port.trim_start_matches('\\').to_string();
+            }
+            for gate in &mut module.gates {
+                if let Gate::Table { inputs, output, .. } = gate {
+                    for net in inputs.iter_mut().chain([output]) {
+                        *net = net.trim_start_matches('\\').to_string();
+                    }
+                }
+            }
+        }
+        modules
+    }
+
+    /// Gates of a module as (inputs, output, rows), with its instances expanded
+    ///
+    /// Nets inside an instance are prefixed with its path, except the ports.
+    fn flatten(
+        modules: &[(String, Module)],
+        name: &str,
+        net: &dyn Fn(&str) -> String,
+        tables: &mut Vec<(Vec<String>, String, Vec<String>)>,
+    ) {
+        let (_, module) = modules.iter().find(|(module, _)| module == name).unwrap();
+        for (idx, gate) in module.gates.iter().enumerate() {
+            match gate {
+                Gate::Table {
+                    inputs,
+                    output,
+                    rows,
+                } => tables.push((
+                    inputs.iter().map(|input| net(input)).collect(),
+                    net(output),
+                    rows.clone(),
+                )),
+                Gate::Instance { module, ports } => {
+                    let inner = |port: &str| match ports.iter().find(|(formal, _)| formal == port) {
+                        Some((_, actual)) => net(actual),
+                        None => net(&format!("{idx}/{port}")),
+                    };
+                    flatten(modules, module, &inner, tables);
+                }
+            }
+        }
+    }
+
+    /// A flattened netlist with its gates in evaluation order and nets numbered
+    struct Circuit {
+        inputs: Vec<usize>,
+        outputs: Vec<usize>,
+        gates: Vec<(Vec<usize>, usize, Vec<String>)>,
+        net_count: usize,
+    }
+
+    impl Circuit {
+        /// Circuit of the main module, panicking on nets that are never driven
+        fn new(modules: &[(String, Module)]) -> Self {
+            let (name, module) = &modules[0];
+            let mut tables = Vec::new();
+            flatten(modules, name, &str::to_string, &mut tables);
+            // The constants of RTLIL are the first nets
+            let mut nets = HashMap::from([("1'0".to_string(), 0), ("1'1".to_string(), 1)]);
+            let number = |net: &str, nets: &mut HashMap<String, usize>| {
+                let count = nets.len();
+                *nets.entry(net.to_string()).or_insert(count)
+            };
+            let mut driven: HashSet<String> = nets.keys().chain(&module.inputs).cloned().collect();
+            let mut gates = Vec::with_capacity(tables.len());
+            while !tables.is_empty() {
+                let (ready, waiting) =
+                    tables.into_iter().partition::<Vec<_>, _>(|(inputs, _, _)| {
+                        inputs.iter().all(|net| driven.contains(net))
+                    });
+                assert!(!ready.is_empty(), "undriven nets in {name}");
+                for (inputs, output, rows) in ready {
+                    let inputs = inputs.iter().map(|net| number(net, &mut nets)).collect();
+                    gates.push((inputs, number(&output, &mut nets), rows));
+                    driven.insert(output);
+                }
+                tables = waiting;
+            }
+            Circuit {
+                inputs: module
+                    .inputs
+                    .iter()
+                    .map(|net| number(net, &mut nets))
+                    .collect(),
+                outputs: module
+                    .outputs
+                    .iter()
+                    .map(|net| number(net, &mut nets))
+                    .collect(),
+                gates,
+                net_count: nets.len(),
+            }
+        }
+
+        fn run(&self, inputs: &[bool]) -> Vec<bool> {
+            let mut values = vec![false; self.net_count];
+            values[1] = true;
+            for (&net, &bit) in self.inputs.iter().zip(inputs) {
+                values[net] = bit;
+            }
+            for (inputs, output, rows) in &self.gates {
+                values[*output] = rows.iter().any(|row| {
+                    row.chars()
+                        .zip(inputs)
+                        .all(|(pattern, &net)| match pattern {
+                            '1' => values[net],
+                            '0' => !values[net],
+                            _ => true,
+                        })
+                });
+            }
+            self.outputs.iter().map(|&net| values[net]).collect()
+        }
+    }
+
+    /// Output of the main module of a netlist as a function of the input
+    fn simulate(
+        modules: &[(String, Module)],
+        options: &HdlOptions,
+    ) -> impl Fn(i128) -> i128 + use<> {
+        let width = options.input_width;
+        let signed = options.signed;
+        let circuit = Circuit::new(modules);
+        assert_eq!(circuit.inputs.len(), width as usize);
+        move |x| {
+            let bits: Vec<bool> = (0..width).map(|bit| (x >> bit) & 1 == 1).collect();
+            let outputs = circuit.run(&bits);
+            let y = outputs
+                .iter()
+                .rev()
+                .fold(0i128, |y, &bit| (y << 1) | i128::from(bit));
+            if signed && outputs.last() == Some(&true) {
+                y - (1 << outputs.len())
+            } else {
+                y
+            }
+        }
+    }
+
+    #[test]
+    fn netlists_multiply() {
+        assert_designs_multiply(&[8], &[None], |graph, options| {
+            simulate(&parse_blif(&graph.to_blif(options).unwrap()), options)
+        });
+        assert_designs_multiply(&[8], &[None], |graph, options| {
+            simulate(&parse_rtlil(&graph.to_rtlil(options).unwrap()), options)
+        });
+        for n in [1, 8, 1100] {
+            let options = HdlOptions::new(4, true);
+            let blif = parse_blif(&crate::to_blif(n, &options).unwrap());
+            assert_multiplies(
+                &adder_graph(n).unwrap(),
+                &options,
+                simulate(&blif, &options),
+            );
+        }
+    }
+
+    fn cells(full_adders: usize, half_adders: usize, inverters: usize) -> AdderCells {
+        AdderCells {
+            full_adders,
+            half_adders,
+            inverters,
+        }
+    }
+
+    #[test]
+    fn cells_are_counted_per_adder() {
+        let options = HdlOptions::new(8, false);
+        // 3x = x + 2x overlaps in bits 1 to 7, with half adders at both ends,
+        // and 45x = 48x - 3x inverts the 10 bits of 3x, adds through the carry
+        // in bits 1 to 3 and needs full adders up to bit 13
+        let cascade = GraphType::Cascade(3, 15).to_adder_graph().unwrap();
+        assert_eq!(
+            cascade.full_adders(&options).unwrap(),
+            [cells(6, 2, 0), cells(10, 3, 10)]
+        );
+        // 19x = 3x + 16x overlaps in bits 4 to 9, and 275x = 19x + 256x in
+        // bits 8 to 12
+        let graph = GraphType::Adder(19, 256).to_adder_graph().unwrap();
+        assert_eq!(
+            graph.full_adders(&options).unwrap(),
+            [cells(6, 2, 0), cells(5, 3, 0), cells(4, 4, 0)]
+        );
+        assert!(crate::to_blif(1100, &options).unwrap().starts_with(
+            "# Multiplication by 1100 using 3 adders: 15 full adders, 9 half adders, 0 inverters\n"
+        ));
+    }
+}
@@ -0,0 +1,573 @@
+// src/pag.rs
+//! Adder graph strings as used by PAGSuite and FloPoCo
+//!
+//! A graph is a list of nodes such as `{{'A',[5],1,[1],0,2,[1],0,0},{'O',[5],1,[5],1,0}}`.
+//! Adders are `{'A',[value],stage,[a],stage_a,shift_a,[b],stage_b,shift_b}`,
+//! with subtracted inputs given as negative values and right shifts as
+//! negative shifts. Registers are `{'R',[value],stage,[value],stage_in}` and
+//! outputs `{'O',[value],stage,[input],stage_in,shift}`. The input is the
+//! value 1 in stage 0.
+use crate::{AdderGraph, AdderNode, Error, Operand, Source};
+use std::collections::HashMap;
+
+impl AdderGraph {
+    /// Adder graph string with one `'A'` node per adder and an `'O'` node for
+    /// the output, the stage of each adder being its adder depth
+    ///
+    /// ```
+    /// use constant_multiplication::{GraphType, GraphTypeExt};
+    ///
+    /// let graph = GraphType::Cascade(3, 15).to_adder_graph().unwrap();
+    /// assert_eq!(
+    ///     graph.to_pag(),
+    ///     "{{'A',[3],1,[1],0,0,[1],0,1},{'A',[45],2,[3],1,4,[-3],1,0},{'O',[45],2,[45],2,0}}"
+    /// );
+    /// ```
+    pub fn to_pag(&self) -> String {
+        let mut stages: Vec<u32> = Vec::with_capacity(self.nodes.len());
+        let stage = |stages: &[u32], source: Source| match source {
+            Source::Input => 0,
+            Source::Node(idx) => stages[idx],
+        };
+        let mut nodes = Vec::with_capacity(self.nodes.len() + 1);
+        for node in &self.nodes {
+            let node_stage = 1 + node
+                .inputs
+                .iter()
+                .map(|operand| stage(&stages, operand.source))
+                .max()
+                .unwrap_or(0);
+            let mut text = format!("{{'A',[{}],{node_stage}", node.value);
+            for operand in &node.inputs {
+                // The right shift of the node is moved to its inputs
+                let shift = i64::from(operand.shift) - i64::from(node.right_shift);
+                text.push_str(&format!(
+                    ",[{}{}],{},{shift}",
+                    if operand.negative { "-" } else { "" },
+                    self.source_value(operand.source),
+                    stage(&stages, operand.source)
+                ));
+            }
+            text.push('}');
+            nodes.push(text);
+            stages.push(node_stage);
+        }
+        let output_stage = stage(&stages, self.output.source);
+        nodes.push(format!(
+            "{{'O',[{}],{output_stage},[{}],{output_stage},{}}}",
+            self.value(),
+            self.source_value(self.output.source),
+            self.output.shift
+        ));
+        format!("{{{}}}", nodes.join(","))
+    }
+
+    /// Parse and validate an adder graph string with a single output
+    ///
+    /// Every adder must compute its value from nodes in earlier stages, and
+    /// all values must be positive and odd, except the output which may be
+    /// shifted. Registers are followed to the node they delay. Without an
+    /// `'O'` node, the only node in the last stage is the output.
+    ///
+    /// ```
+    /// use constant_multiplication::AdderGraph;
+    ///
+    /// let graph = AdderGraph::from_pag("{{'A',[5],1,[1],0,2,[1],0,0},{'O',[10],1,[5],1,1}}").unwrap();
+    /// assert_eq!((graph.value(), graph.adder_count()), (10, 1));
+    ///
+    /// let graph = AdderGraph::from_pag("{{'A',[5],1,[1],0,2,[1],0,0}}").unwrap();
+    /// assert_eq!(graph.value(), 5);
+    /// ```
+    pub fn from_pag(text: &str) -> Result<Self, Error> {
+        let Value::List(items) = Parser::new(text).parse()? else {
+            return Err(invalid("expected a list of nodes"));
+        };
+        let pag_nodes = items
+            .iter()
+            .map(PagNode::new)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut reader = Reader {
+            graph: AdderGraph {
+                nodes: Vec::new(),
+                output: Operand {
+                    source: Source::Input,
+                    shift: 0,
+                    negative: false,
+                },
+            },
+            sources: HashMap::from([((1, 0), Source::Input)]),
+            outputs: 0,
+        };
+        // Nodes are added in the given order, except that nodes using later
+        // ones wait until their inputs are added
+        let mut pending: Vec<&PagNode> = pag_nodes.iter().collect();
+        while !pending.is_empty() {
+            let count = pending.len();
+            let mut deferred = Vec::new();
+            for node in pending {
+                if reader.is_ready(node) {
+                    reader.add(node)?;
+                } else {
+                    deferred.push(node);
+                }
+            }
+            if deferred.len() == count {
+                // Report the missing input of the first node
+                reader.add(deferred[0])?;
+            }
+            pending = deferred;
+        }
+
+        if reader.outputs == 0 {
+            // Without output nodes the only node in the last stage is the output
+            let last = pag_nodes.iter().map(|node| node.stage).max().unwrap_or(0);
+            let mut last_nodes = reader
+                .sources
+                .iter()
+                .filter(|&(&(_, stage), _)| stage == last);
+            match (last_nodes.next(), last_nodes.next()) {
+                (Some((_, &source)), None) => reader.graph.output.source = source,
+                _ => {
+                    return Err(invalid(
+                        "no output node, and several nodes in the last stage",
+                    ));
+                }
+            }
+        } else if reader.outputs > 1 {
+            return Err(invalid(&format!(
+                "expected one output, found {}",
+                reader.outputs
+            )));
+        }
+        Ok(reader.graph)
+    }
+}
+
+/// Graph built from the nodes of an adder graph string
+struct Reader {
+    graph: AdderGraph,
+    // Source of each (value, stage) pair added so far
+    sources: HashMap<(i64, i64), Source>,
+    outputs: usize,
+}
+
+impl Reader {
+    /// Whether all inputs of the node have been added
+    fn is_ready(&self, node: &PagNode) -> bool {
+        let inputs = match node.kind {
+            NodeKind::Adder => &node.inputs[..],
+            NodeKind::Register | NodeKind::Output => &node.inputs[..1],
+        };
+        inputs.iter().all(|&(value, stage, _)| {
+            value
+                .checked_abs()
+                .is_some_and(|value| self.sources.contains_key(&(value, stage)))
+        })
+    }
+
+    /// Source of an input of the node, from an earlier stage, or the same
+    /// stage for outputs
+    fn find(&self, node: &PagNode, value: i64, stage: i64) -> Result<Source, Error> {
+        let same_stage = matches!(node.kind, NodeKind::Output) && stage == node.stage;
+        if stage >= node.stage && !same_stage {
+            return Err(invalid(&format!(
+                "{value} in stage {stage} is not before stage {}",
+                node.stage
+            )));
+        }
+        value
+            .checked_abs()
+            .and_then(|value| self.sources.get(&(value, stage)))
+            .copied()
+            .ok_or_else(|| {
+                invalid(&format!(
+                    "no node {} in stage {stage}",
+                    value.unsigned_abs()
+                ))
+            })
+    }
+
+    fn add(&mut self, node: &PagNode) -> Result<(), Error> {
+        match node.kind {
+            NodeKind::Adder => {
+                let [(a, stage_a, shift_a), (b, stage_b, shift_b)] = node.inputs;
+                let (source_a, source_b) =
+                    (self.find(node, a, stage_a)?, self.find(node, b, stage_b)?);
+                let right_shift = shift_a
+                    .min(shift_b)
+                    .checked_neg()
+                    .filter(|&right_shift| right_shift < 64)
+                    .ok_or_else(|| invalid("right shift out of range"))?
+                    .max(0);
+                let operand = |source: Source, value: i64, shift: i64| {
+                    shift
+                        .checked_add(right_shift)
+                        .and_then(|shift| u32::try_from(shift).ok())
+                        .filter(|&shift| shift < 64)
+                        .map(|shift| Operand {
+                            source,
+                            shift,
+                            negative: value < 0,
+                        })
+                        .ok_or_else(|| invalid(&format!("shift {shift} out of range")))
+                };
+                let inputs = [
+                    operand(source_a, a, shift_a)?,
+                    operand(source_b, b, shift_b)?,
+                ];
+                let sum = inputs
+                    .iter()
+                    .map(|operand| {
+                        let value =
+                            i128::from(self.graph.source_value(operand.source)) << operand.shift;
+                        if operand.negative { -value } else { value }
+                    })
+                    .sum::<i128>();
+                if sum != i128::from(node.value) << right_shift {
+                    return Err(invalid(&format!(
+                        "adder {} in stage {} computes {sum} >> {right_shift}",
+                        node.value, node.stage
+                    )));
+                }
+                let value = odd_value(node.value)?;
+                self.graph.nodes.push(AdderNode {
+                    value,
+                    inputs,
+                    right_shift: right_shift as u32,
+                });
+                self.sources.insert(
+                    (node.value, node.stage),
+                    Source::Node(self.graph.nodes.len() - 1),
+                );
+            }
+            NodeKind::Register => {
+                let (value, stage, _) = node.inputs[0];
+                if value != node.value {
+                    return Err(invalid(&format!(
+                        "register {} in stage {} delays {value}",
+                        node.value, node.stage
+                    )));
+                }
+                let source = self.find(node, value, stage)?;
+                self.sources.insert((node.value, node.stage), source);
+            }
+            NodeKind::Output => {
+                let (value, stage, shift) = node.inputs[0];
+                let source = self.find(node, value, stage)?;
+                let shift = u32::try_from(shift)
+                    .ok()
+                    .filter(|&shift| shift < 64)
+                    .ok_or_else(|| invalid(&format!("output shift {shift} out of range")))?;
+                if i128::from(value) << shift != i128::from(node.value) || value < 0 {
+                    return Err(invalid(&format!(
+                        "output {} is not {value} << {shift}",
+                        node.value
+                    )));
+                }
+                self.graph.output = Operand {
+                    source,
+                    shift,
+                    negative: false,
+                };
+                self.outputs += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn invalid(reason: &str) -> Error {
+    Error::InvalidAdderGraph(reason.to_string())
+}
+
+/// Adder outputs are positive and odd, shifts are applied to their inputs and outputs
+fn odd_value(value: i64) -> Result<u64, Error> {
+    u64::try_from(value)
+        .ok()
+        .filter(|value| value % 2 == 1)
+        .ok_or_else(|| invalid(&format!("adder value {value} is not positive and odd")))
+}
+
+enum NodeKind {
+    Adder,
+    Register,
+    Output,
+}
+
+/// A node of an adder graph string, with inputs as (value, stage, shift)
+struct PagNode {
+    kind: NodeKind,
+    value: i64,
+    stage: i64,
+    inputs: [(i64, i64, i64); 2],
+}
+
+impl PagNode {
+    fn new(item: &Value) -> Result<Self, Error> {
+        let Value::List(fields) = item else {
+            return Err(invalid("expected a node in braces"));
+        };
+        let kind = match fields.first() {
+            Some(Value::Name(name)) if name == "A" => NodeKind::Adder,
+            Some(Value::Name(name)) if name == "R" => NodeKind::Register,
+            Some(Value::Name(name)) if name == "O" => NodeKind::Output,
+            Some(Value::Name(name)) => {
+                return Err(invalid(&format!("unsupported node type '{name}'")));
+            }
+            _ => return Err(invalid("expected a node type")),
+        };
+        let expected = match kind {
+            NodeKind::Adder => 9,
+            NodeKind::Register => 5,
+            NodeKind::Output => 6,
+        };
+        if fields.len() != expected {
+            return Err(invalid(&format!(
+                "expected {expected} fields in node, found {} (only single-output \
+                 two-input adders are supported)",
+                fields.len()
+            )));
+        }
+        let constant = |idx: usize| match &fields[idx] {
+            Value::Vector(values) if values.len() == 1 => Ok(values[0]),
+            _ => Err(invalid("expected a single constant in brackets")),
+        };
+        let integer = |idx: usize| match fields.get(idx) {
+            Some(Value::Integer(value)) => Ok(*value),
+            // Registers have no shift
+            None => Ok(0),
+            _ => Err(invalid("expected an integer")),
+        };
+        let input =
+            |idx: usize| Ok::<_, Error>((constant(idx)?, integer(idx + 1)?, integer(idx + 2)?));
+        let first = input(3)?;
+        let second = match kind {
+            NodeKind::Adder => input(6)?,
+            _ => (0, 0, 0),
+        };
+        Ok(PagNode {
+            kind,
+            value: constant(1)?,
+            stage: integer(2)?,
+            inputs: [first, second],
+        })
+    }
+}
+
+/// Parsed element of an adder graph string
+enum Value {
+    /// Elements in braces
+    List(Vec<Value>),
+    /// Integers in brackets
+    Vector(Vec<i64>),
+    Integer(i64),
+    /// Quoted node type
+    Name(String),
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        Parser { text, position: 0 }
+    }
+
+    fn parse(mut self) -> Result<Value, Error> {
+        let value = self.value()?;
+        self.skip_whitespace();
+        if self.position != self.text.len() {
+            return Err(self.error("unexpected text after the graph"));
+        }
+        Ok(value)
+    }
+
+    fn error(&self, reason: &str) -> Error {
+        invalid(&format!("{reason} at position {}", self.position))
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.text[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.text[self.position..].chars().next()
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), Error> {
+        if self.peek() == Some(expected) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{expected}'")))
+        }
+    }
+
+    /// Comma-separated elements up to the closing character
+    fn sequence<T>(
+        &mut self,
+        close: char,
+        mut element: impl FnMut(&mut Self) -> Result<T, Error>,
+    ) -> Result<Vec<T>, Error> {
+        let mut elements = Vec::new();
+        if self.peek() == Some(close) {
+            self.position += 1;
+            return Ok(elements);
+        }
+        loop {
+            elements.push(element(self)?);
+            if self.peek() == Some(close) {
+                self.position += 1;
+                return Ok(elements);
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, Error> {
+        match self.peek() {
+            Some('{') => {
+                self.position += 1;
+                Ok(Value::List(self.sequence('}', Self::value)?))
+            }
+            Some('[') => {
+                self.position += 1;
+                Ok(Value::Vector(self.sequence(']', Self::integer)?))
+            }
+            Some('\'') => {
+                self.position += 1;
+                let rest = &self.text[self.position..];
+                let end = rest
+                    .find('\'')
+                    .ok_or_else(|| self.error("unterminated quote"))?;
+                self.position += end + 1;
+                Ok(Value::Name(rest[..end].to_string()))
+            }
+            _ => Ok(Value::Integer(self.integer()?)),
+        }
+    }
+
+    fn integer(&mut self) -> Result<i64, Error> {
+        self.skip_whitespace();
+        let rest = &self.text[self.position..];
+        let end = rest
+            .char_indices()
+            .find(|&(idx, c)| !(c.is_ascii_digit() || (idx == 0 && c == '-')))
+            .map_or(rest.len(), |(idx, _)| idx);
+        let value = rest[..end]
+            .parse()
+            .map_err(|_| self.error("expected an integer"))?;
+        self.position += end;
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::stored_graphs;
+    use crate::{GraphType, GraphTypeExt, adder_graph};
+
+    fn reason(text: &str) -> String {
+        match AdderGraph::from_pag(text) {
+            Err(Error::InvalidAdderGraph(reason)) => reason,
+            other => panic!("expected an invalid graph for {text}, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn out_of_range_shifts_are_rejected() {
+        for text in [
+            "{{'A',[5],1,[1],0,-9223372036854775808,[1],0,0},{'O',[5],1,[5],1,0}}",
+            "{{'A',[5],1,[1],0,9223372036854775807,[1],0,-1},{'O',[5],1,[5],1,0}}",
+            "{{'A',[5],1,[1],0,64,[1],0,0},{'O',[5],1,[5],1,0}}",
+            "{{'A',[5],1,[1],0,2,[1],0,0},{'O',[5],1,[5],1,-1}}",
+            "{{'A',[5],1,[1],0,2,[1],0,0},{'O',[5],1,[5],1,9223372036854775807}}",
+        ] {
+            assert!(reason(text).contains("out of range"), "{text}");
+        }
+    }
+
+    #[test]
+    fn extreme_values_are_rejected() {
+        for text in [
+            "{{'A',[5],1,[-9223372036854775808],0,2,[1],0,0},{'O',[5],1,[5],1,0}}",
+            "{{'A',[-9223372036854775808],1,[1],0,2,[1],0,0}}",
+            "{{'O',[5],1,[-9223372036854775808],1,0}}",
+        ] {
+            reason(text);
+        }
+    }
+
+    #[test]
+    fn malformed_strings_are_rejected() {
+        let cases = [
+            ("{{'A',[5],1,[1],0,2,[1],0,0}", "expected"),
+            ("{{'A',[5],1,[1],0,2,[1],0,0},{'O',[5],1,[5]", "expected"),
+            ("{{'A',[5],1,[1],0,2,[1],0,0}} x", "unexpected text"),
+            ("{{'A,[5]}}", "unterminated quote"),
+            ("{{'X',[5],1,[1],0,0}}", "unsupported node type 'X'"),
+            ("{{'A',[5],1,[1],0,2}}", "expected 9 fields"),
+            ("{{'A',[5],1,[3],0,2,[1],0,0}}", "no node 3 in stage 0"),
+            ("{{'A',[5],1,[1],1,2,[1],0,0}}", "is not before stage 1"),
+            ("{{'A',[7],1,[1],0,2,[1],0,0}}", "computes"),
+            ("{{'A',[4],1,[1],0,1,[1],0,1}}", "not positive and odd"),
+            ("{{'R',[3],1,[1],0}}", "delays"),
+            ("{{'O',[6],1,[1],0,1}}", "is not 1 << 1"),
+            (
+                "{{'A',[5],1,[1],0,2,[1],0,0},{'A',[3],1,[1],0,1,[1],0,0}}",
+                "several nodes in the last stage",
+            ),
+            (
+                "{{'A',[5],1,[1],0,2,[1],0,0},{'O',[5],1,[5],1,0},{'O',[10],1,[5],1,1}}",
+                "expected one output, found 2",
+            ),
+        ];
+        for (text, expected) in cases {
+            let reason = reason(text);
+            assert!(reason.contains(expected), "{text}: {reason}");
+        }
+    }
+
+    #[test]
+    fn right_shifts_and_registers_are_read() {
+        // 3 = (1 + 5) >> 1, delayed by a register and shifted at the output
+        let graph = AdderGraph::from_pag(
+            "{{'A',[5],1,[1],0,2,[1],0,0},{'A',[3],2,[1],0,-1,[5],1,-1},\
+             {'R',[3],3,[3],2},{'O',[6],3,[3],3,1}}",
+        )
+        .unwrap();
+        assert_eq!((graph.value(), graph.adder_count()), (6, 2));
+        assert_eq!(graph.nodes[1].right_shift, 1);
+        assert_eq!(AdderGraph::from_pag(&graph.to_pag()).unwrap(), graph);
+    }
+
+    #[test]
+    fn stored_structures_round_trip() {
+        for graph in stored_graphs((3..4096).step_by(2).chain([11467, 65521])) {
+            let pag = graph.to_pag();
+            assert_eq!(AdderGraph::from_pag(&pag).unwrap(), graph, "{pag}");
+        }
+        for n in [1, 2, 1100, 11467 << 3, 1 << 40] {
+            let graph = adder_graph(n).unwrap();
+            assert_eq!(AdderGraph::from_pag(&graph.to_pag()).unwrap(), graph);
+        }
+    }
+
+    #[test]
+    fn stages_are_adder_depths() {
+        // 43 = (1 + 85) >> 1 takes its input from stage 0 into stage 3
+        let graph = GraphType::Adder(1, 85).to_adder_graph().unwrap();
+        assert_eq!(
+            graph.to_pag(),
+            "{{'A',[5],1,[1],0,0,[1],0,2},{'A',[85],2,[5],1,0,[5],1,4},\
+             {'A',[43],3,[1],0,-1,[85],2,-1},{'O',[43],3,[43],3,0}}"
+        );
+        assert_eq!(adder_graph(8).unwrap().to_pag(), "{{'O',[8],0,[1],0,3}}");
+    }
+}
@@ -0,0 +1,1204 @@
+// src/python.rs
+use crate::{CostModel, Error, GraphTypeExt, HdlOptions, Operand, Source};
+use pyo3::exceptions::{PyIndexError, PyOverflowError, PyRuntimeError, PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyIterator, PyList, PySlice, PyString};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::num::NonZeroU32;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, PoisonError};
+
+impl From<Error> for PyErr {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::OutOfRange(_) => PyIndexError::new_err(e.to_string()),
+            Error::Format(_)
+            | Error::MismatchedTables
+            | Error::MissingStructure(_)
+            | Error::InvalidStructure(_)
+            | Error::InvalidWidth(_)
+            | Error::InvalidPipeline(_)
+            | Error::InvalidName(_)
+            | Error::InvalidAdderGraph(_) => PyValueError::new_err(e.to_string()),
+            Error::Io(e) => e.into(),
+            // Re-raise the exception of the Python callback
+            Error::CostModel(_) => CALLBACK_ERROR
+                .take()
+                .unwrap_or_else(|| PyRuntimeError::new_err(e.to_string())),
+        }
+    }
+}
+
+// GraphType as a Python class
+#[pyclass(name = "GraphType")]
+#[derive(Clone)]
+struct PyGraphType {
+    inner: crate::GraphType,
+}
+
+fn extract_shift(value: usize) -> String {
+    let shift = value.trailing_zeros();
+    if shift == 0 {
+        value.to_string()
+    } else {
+        let shifted = value >> shift;
+        format!("{shifted} << {shift}")
+    }
+}
+
+fn hdl_options(
+    width: u32,
+    signed: bool,
+    name: Option<String>,
+    pipeline: Option<u32>,
+) -> HdlOptions {
+    HdlOptions {
+        name,
+        pipeline,
+        ..HdlOptions::new(width, signed)
+    }
+}
+
+#[pymethods]
+impl PyGraphType {
+    #[getter]
+    fn variant(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    #[getter]
+    fn params(&self) -> Vec<usize> {
+        self.inner.params()
+    }
+
+    /// Odd integer computed by the structure
+    fn value(&self) -> PyResult<u64> {
+        self.inner
+            .value()
+            .ok_or_else(|| PyOverflowError::new_err("Value does not fit in 64 bits"))
+    }
+
+    /// Check that the structure computes n with the minimum number of adders
+    fn verify(&self, n: u64) -> PyResult<bool> {
+        Ok(self.inner.verify(n)?)
+    }
+
+    /// Equivalent structures left out of the tables by symmetry
+    fn equivalents(&self, py: Python) -> PyResult<Py<PyAny>> {
+        Ok(graph_type_list(py, &self.inner.equivalents()?)?.into())
+    }
+
+    /// Adders on the longest path, through the intermediate fundamentals
+    #[getter]
+    fn depth(&self) -> PyResult<u32> {
+        Ok(self.inner.depth()?)
+    }
+
+    /// Expand into adders, building intermediate fundamentals from the tables
+    fn to_adder_graph(&self) -> PyResult<PyAdderGraph> {
+        Ok(PyAdderGraph {
+            inner: self.inner.to_adder_graph()?,
+        })
+    }
+
+    /// Verilog module multiplying by the value of the structure
+    #[pyo3(signature = (width, signed=false, name=None, pipeline=None))]
+    fn to_verilog(
+        &self,
+        width: u32,
+        signed: bool,
+        name: Option<String>,
+        pipeline: Option<u32>,
+    ) -> PyResult<String> {
+        Ok(self
+            .inner
+            .to_verilog(&hdl_options(width, signed, name, pipeline))?)
+    }
+
+    /// VHDL-2008 entity multiplying by the value of the structure
+    #[pyo3(signature = (width, signed=false, name=None, pipeline=None))]
+    fn to_vhdl(
+        &self,
+        width: u32,
+        signed: bool,
+        name: Option<String>,
+        pipeline: Option<u32>,
+    ) -> PyResult<String> {
+        Ok(self
+            .inner
+            .to_vhdl(&hdl_options(width, signed, name, pipeline))?)
+    }
+
+    /// Python source of an Amaranth module multiplying by the value of the structure
+    #[pyo3(signature = (width, signed=false, name=None, pipeline=None))]
+    fn to_amaranth(
+        &self,
+        width: u32,
+        signed: bool,
+        name: Option<String>,
+        pipeline: Option<u32>,
+    ) -> PyResult<String> {
+        Ok(self
+            .inner
+            .to_amaranth(&hdl_options(width, signed, name, pipeline))?)
+    }
+
+    /// C function multiplying by the value of the structure
+    #[pyo3(signature = (width, signed=false, name=None))]
+    fn to_c(&self, width: u32, signed: bool, name: Option<String>) -> PyResult<String> {
+        Ok(self.inner.to_c(&hdl_options(width, signed, name, None))?)
+    }
+
+    /// Rust function multiplying by the value of the structure
+    #[pyo3(signature = (width, signed=false, name=None))]
+    fn to_rust(&self, width: u32, signed: bool, name: Option<String>) -> PyResult<String> {
+        Ok(self
+            .inner
+            .to_rust(&hdl_options(width, signed, name, None))?)
+    }
+
+    /// Vitis HLS C++ function multiplying by the value of the structure
+    #[pyo3(signature = (width, signed=false, name=None))]
+    fn to_hls_cpp(&self, width: u32, signed: bool, name: Option<String>) -> PyResult<String> {
+        Ok(self
+            .inner
+            .to_hls_cpp(&hdl_options(width, signed, name, None))?)
+    }
+
+    /// Graphviz DOT description of the adders of the structure
+    fn to_dot(&self) -> PyResult<String> {
+        Ok(self.inner.to_dot()?)
+    }
+
+    /// BLIF netlist of full and half adders multiplying by the value of the structure
+    #[pyo3(signature = (width, signed=false, name=None))]
+    fn to_blif(&self, width: u32, signed: bool, name: Option<String>) -> PyResult<String> {
+        Ok(self
+            .inner
+            .to_blif(&hdl_options(width, signed, name, None))?)
+    }
+
+    /// Yosys RTLIL netlist of full and half adders multiplying by the value of the structure
+    #[pyo3(signature = (width, signed=false, name=None))]
+    fn to_rtlil(&self, width: u32, signed: bool, name: Option<String>) -> PyResult<String> {
+        Ok(self
+            .inner
+            .to_rtlil(&hdl_options(width, signed, name, None))?)
+    }
+
+    /// Full adders, half adders and inverters of each adder for an input of wordlength bits
+    #[pyo3(signature = (wordlength, signed=false))]
+    fn full_adders(&self, wordlength: u32, signed: bool) -> PyResult<Vec<PyAdderCells>> {
+        let options = hdl_options(wordlength, signed, None, None);
+        Ok(adder_cells_list(self.inner.full_adders(&options)?))
+    }
+
+    /// LUTs and carry-chain slices on an FPGA for an input of wordlength bits
+    ///
+    /// With ternary, pairs of adders are fused into ternary adders where possible.
+    #[pyo3(signature = (wordlength, signed=false, ternary=false, carry_bits=8))]
+    fn fpga_cost(
+        &self,
+        wordlength: u32,
+        signed: bool,
+        ternary: bool,
+        carry_bits: u32,
+    ) -> PyResult<PyFpgaCost> {
+        let options = hdl_options(wordlength, signed, None, None);
+        Ok(PyFpgaCost {
+            inner: self
+                .inner
+                .fpga_cost(&options, &fpga_options(ternary, carry_bits)?)?,
+        })
+    }
+
+    /// Python source building a B-ASIC SFG of the structure
+    fn to_b_asic(&self) -> PyResult<String> {
+        Ok(self.inner.to_b_asic()?)
+    }
+
+    /// Adder graph string for PAGSuite and FloPoCo
+    fn to_pag(&self) -> PyResult<String> {
+        Ok(self.inner.to_pag()?)
+    }
+
+    /// SVG drawing of the adders, shown by Jupyter
+    fn _repr_svg_(&self) -> PyResult<String> {
+        Ok(self.inner.to_svg()?)
+    }
+
+    /// Drawing of the adders with the structure as caption, shown by Jupyter
+    fn _repr_html_(&self) -> PyResult<String> {
+        Ok(figure(&self.__repr__(), &self.inner.to_svg()?))
+    }
+
+    fn __repr__(&self) -> String {
+        let shifted_params: Vec<String> = self.params().iter().map(|&v| extract_shift(v)).collect();
+        format!("{}({})", self.variant(), shifted_params.join(", "))
+    }
+}
+
+/// HTML figure of a drawing with a caption
+fn figure(caption: &str, svg: &str) -> String {
+    format!(
+        "<figure style=\"display: inline-block; margin: 4px; text-align: center\">{svg}\
+         <figcaption><code>{}</code></figcaption></figure>",
+        crate::svg::escape(caption)
+    )
+}
+
+// List of structures drawn side by side in Jupyter, wrapping a list such as
+// the one returned by `get_graph_types`
+#[pyclass(name = "GraphTypeList", sequence)]
+struct PyGraphTypeList {
+    items: Vec<PyGraphType>,
+}
+
+impl PyGraphTypeList {
+    /// Captions and adder graphs of the structures
+    fn graphs(&self) -> PyResult<Vec<(String, crate::AdderGraph)>> {
+        self.items
+            .iter()
+            .map(|item| Ok((item.__repr__(), item.inner.to_adder_graph()?)))
+            .collect()
+    }
+}
+
+#[pymethods]
+impl PyGraphTypeList {
+    #[new]
+    fn new(items: Vec<PyGraphType>) -> Self {
+        PyGraphTypeList { items }
+    }
+
+    fn __len__(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Structure at an index, or a GraphTypeList for a slice
+    fn __getitem__<'py>(&self, idx: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+        let py = idx.py();
+        if let Ok(slice) = idx.cast::<PySlice>() {
+            let indices = slice.indices(self.items.len() as isize)?;
+            let items = (0..indices.slicelength)
+                .map(|i| {
+                    let position = indices.start + i as isize * indices.step;
+                    self.items[position as usize].clone()
+                })
+                .collect();
+            return Ok(Bound::new(py, PyGraphTypeList { items })?.into_any());
+        }
+        let idx: isize = idx.extract()?;
+        let len = self.items.len() as isize;
+        let position = if idx < 0 { idx + len } else { idx };
+        if (0..len).contains(&position) {
+            Ok(Bound::new(py, self.items[position as usize].clone())?.into_any())
+        } else {
+            Err(PyIndexError::new_err("GraphTypeList index out of range"))
+        }
+    }
+
+    fn __iter__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyIterator>> {
+        PyList::new(py, self.items.clone())?.try_iter()
+    }
+
+    /// SVG drawing of all structures side by side, shown by Jupyter
+    fn _repr_svg_(&self) -> PyResult<String> {
+        Ok(crate::svg_row(&self.graphs()?))
+    }
+
+    /// Drawings of all structures with captions, wrapping to the notebook width
+    fn _repr_html_(&self) -> PyResult<String> {
+        let figures: Vec<String> = self
+            .graphs()?
+            .iter()
+            .map(|(caption, graph)| figure(caption, &graph.to_svg()))
+            .collect();
+        Ok(format!(
+            "<div style=\"display: flex; flex-wrap: wrap; align-items: flex-start\">{}</div>",
+            figures.concat()
+        ))
+    }
+
+    fn __repr__(&self) -> String {
+        let items: Vec<String> = self.items.iter().map(PyGraphType::__repr__).collect();
+        format!("GraphTypeList([{}])", items.join(", "))
+    }
+}
+
+/// An adder input as (node, shift, negative), with node None for the multiplier input
+type PyOperand = (Option<usize>, u32, bool);
+
+fn operand_tuple(operand: &Operand) -> PyOperand {
+    let node = match operand.source {
+        Source::Input => None,
+        Source::Node(idx) => Some(idx),
+    };
+    (node, operand.shift, operand.negative)
+}
+
+// AdderNode as a Python class
+#[pyclass(name = "AdderNode", frozen)]
+struct PyAdderNode {
+    inner: crate::AdderNode,
+}
+
+#[pymethods]
+impl PyAdderNode {
+    #[getter]
+    fn value(&self) -> u64 {
+        self.inner.value
+    }
+
+    #[getter]
+    fn inputs(&self) -> Vec<PyOperand> {
+        self.inner.inputs.iter().map(operand_tuple).collect()
+    }
+
+    #[getter]
+    fn right_shift(&self) -> u32 {
+        self.inner.right_shift
+    }
+
+    fn __repr__(&self) -> String {
+        format!("AdderNode(value={})", self.inner.value)
+    }
+}
+
+// AdderGraph as a Python class
+#[pyclass(name = "AdderGraph", frozen)]
+struct PyAdderGraph {
+    inner: crate::AdderGraph,
+}
+
+#[pymethods]
+impl PyAdderGraph {
+    #[getter]
+    fn nodes(&self) -> Vec<PyAdderNode> {
+        self.inner
+            .nodes
+            .iter()
+            .map(|node| PyAdderNode {
+                inner: node.clone(),
+            })
+            .collect()
+    }
+
+    #[getter]
+    fn output(&self) -> PyOperand {
+        operand_tuple(&self.inner.output)
+    }
+
+    #[getter]
+    fn value(&self) -> u64 {
+        self.inner.value()
+    }
+
+    /// Adders on the longest path from the input to the output
+    #[getter]
+    fn depth(&self) -> u32 {
+        self.inner.depth()
+    }
+
+    /// Verilog module multiplying by the value of the graph
+    #[pyo3(signature = (width, signed=false, name=None, pipeline=None))]
+    fn to_verilog(
+        &self,
+        width: u32,
+        signed: bool,
+        name: Option<String>,
+        pipeline: Option<u32>,
+    ) -> PyResult<String> {
+        Ok(self
+            .inner
+            .to_verilog(&hdl_options(width, signed, name, pipeline))?)
+    }
+
+    /// VHDL-2008 entity multiplying by the value of the graph
+    #[pyo3(signature = (width, signed=false, name=None, pipeline=None))]
+    fn to_vhdl(
+        &self,
+        width: u32,
+        signed: bool,
+        name: Option<String>,
+        pipeline: Option<u32>,
+    ) -> PyResult<String> {
+        Ok(self
+            .inner
+            .to_vhdl(&hdl_options(width, signed, name, pipeline))?)
+    }
+
+    /// Python source of an Amaranth module multiplying by the value of the graph
+    #[pyo3(signature = (width, signed=false, name=None, pipeline=None))]
+    fn to_amaranth(
+        &self,
+        width: u32,
+        signed: bool,
+        name: Option<String>,
+        pipeline: Option<u32>,
+    ) -> PyResult<String> {
+        Ok(self
+            .inner
+            .to_amaranth(&hdl_options(width, signed, name, pipeline))?)
+    }
+
+    /// C function multiplying by the value of the graph
+    #[pyo3(signature = (width, signed=false, name=None))]
+    fn to_c(&self, width: u32, signed: bool, name: Option<String>) -> PyResult<String> {
+        Ok(self.inner.to_c(&hdl_options(width, signed, name, None))?)
+    }
+
+    /// Rust function multiplying by the value of the graph
+    #[pyo3(signature = (width, signed=false, name=None))]
+    fn to_rust(&self, width: u32, signed: bool, name: Option<String>) -> PyResult<String> {
+        Ok(self
+            .inner
+            .to_rust(&hdl_options(width, signed, name, None))?)
+    }
+
+    /// Vitis HLS C++ function multiplying by the value of the graph
+    #[pyo3(signature = (width, signed=false, name=None))]
+    fn to_hls_cpp(&self, width: u32, signed: bool, name: Option<String>) -> PyResult<String> {
+        Ok(self
+            .inner
+            .to_hls_cpp(&hdl_options(width, signed, name, None))?)
+    }
+
+    /// Graphviz DOT description of the adders
+    fn to_dot(&self) -> String {
+        self.inner.to_dot()
+    }
+
+    /// BLIF netlist of full and half adders multiplying by the value of the graph
+    #[pyo3(signature = (width, signed=false, name=None))]
+    fn to_blif(&self, width: u32, signed: bool, name: Option<String>) -> PyResult<String> {
+        Ok(self
+            .inner
+            .to_blif(&hdl_options(width, signed, name, None))?)
+    }
+
+    /// Yosys RTLIL netlist of full and half adders multiplying by the value of the graph
+    #[pyo3(signature = (width, signed=false, name=None))]
+    fn to_rtlil(&self, width: u32, signed: bool, name: Option<String>) -> PyResult<String> {
+        Ok(self
+            .inner
+            .to_rtlil(&hdl_options(width, signed, name, None))?)
+    }
+
+    /// Full adders, half adders and inverters of each adder for an input of wordlength bits
+    #[pyo3(signature = (wordlength, signed=false))]
+    fn full_adders(&self, wordlength: u32, signed: bool) -> PyResult<Vec<PyAdderCells>> {
+        let options = hdl_options(wordlength, signed, None, None);
+        Ok(adder_cells_list(self.inner.full_adders(&options)?))
+    }
+
+    /// LUTs and carry-chain slices on an FPGA for an input of wordlength bits
+    ///
+    /// With ternary, pairs of adders are fused into ternary adders where possible.
+    #[pyo3(signature = (wordlength, signed=false, ternary=false, carry_bits=8))]
+    fn fpga_cost(
+        &self,
+        wordlength: u32,
+        signed: bool,
+        ternary: bool,
+        carry_bits: u32,
+    ) -> PyResult<PyFpgaCost> {
+        let options = hdl_options(wordlength, signed, None, None);
+        Ok(PyFpgaCost {
+            inner: self
+                .inner
+                .fpga_cost(&options, &fpga_options(ternary, carry_bits)?)?,
+        })
+    }
+
+    /// Python source building a B-ASIC SFG of the graph
+    fn to_b_asic(&self) -> String {
+        self.inner.to_b_asic()
+    }
+
+    /// Adder graph string for PAGSuite and FloPoCo
+    fn to_pag(&self) -> String {
+        self.inner.to_pag()
+    }
+
+    /// Parse and validate an adder graph string from PAGSuite or FloPoCo
+    #[staticmethod]
+    fn from_pag(text: &str) -> PyResult<Self> {
+        Ok(PyAdderGraph {
+            inner: crate::AdderGraph::from_pag(text)?,
+        })
+    }
+
+    /// SVG drawing of the adders, shown by Jupyter
+    fn _repr_svg_(&self) -> String {
+        self.inner.to_svg()
+    }
+
+    /// Latency and register bits of the pipelined hardware description
+    #[pyo3(signature = (width, signed=false, pipeline=1))]
+    fn pipeline_report(
+        &self,
+        width: u32,
+        signed: bool,
+        pipeline: u32,
+    ) -> PyResult<PyPipelineReport> {
+        let options = hdl_options(width, signed, None, Some(pipeline));
+        Ok(PyPipelineReport {
+            inner: self.inner.pipeline_report(&options)?,
+        })
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.adder_count()
+    }
+
+    fn __str__(&self) -> String {
+        self.inner.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "AdderGraph(value={}, adders={})",
+            self.inner.value(),
+            self.inner.adder_count()
+        )
+    }
+}
+
+// PipelineReport as a Python class
+#[pyclass(name = "PipelineReport", frozen)]
+struct PyPipelineReport {
+    inner: crate::PipelineReport,
+}
+
+#[pymethods]
+impl PyPipelineReport {
+    #[getter]
+    fn latency(&self) -> u32 {
+        self.inner.latency
+    }
+
+    #[getter]
+    fn register_bits(&self) -> u64 {
+        self.inner.register_bits
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "PipelineReport(latency={}, register_bits={})",
+            self.inner.latency, self.inner.register_bits
+        )
+    }
+}
+
+// AdderCells as a Python class
+#[pyclass(name = "AdderCells", frozen)]
+struct PyAdderCells {
+    inner: crate::AdderCells,
+}
+
+#[pymethods]
+impl PyAdderCells {
+    #[getter]
+    fn full_adders(&self) -> usize {
+        self.inner.full_adders
+    }
+
+    #[getter]
+    fn half_adders(&self) -> usize {
+        self.inner.half_adders
+    }
+
+    #[getter]
+    fn inverters(&self) -> usize {
+        self.inner.inverters
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "AdderCells(full_adders={}, half_adders={}, inverters={})",
+            self.inner.full_adders, self.inner.half_adders, self.inner.inverters
+        )
+    }
+}
+
+// FpgaCost as a Python class
+#[pyclass(name = "FpgaCost", frozen)]
+struct PyFpgaCost {
+    inner: crate::FpgaCost,
+}
+
+#[pymethods]
+impl PyFpgaCost {
+    #[getter]
+    fn luts(&self) -> u32 {
+        self.inner.luts
+    }
+
+    #[getter]
+    fn carry_slices(&self) -> u32 {
+        self.inner.carry_slices
+    }
+
+    #[getter]
+    fn binary_adders(&self) -> usize {
+        self.inner.binary_adders
+    }
+
+    #[getter]
+    fn ternary_adders(&self) -> usize {
+        self.inner.ternary_adders
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "FpgaCost(luts={}, carry_slices={}, binary_adders={}, ternary_adders={})",
+            self.inner.luts,
+            self.inner.carry_slices,
+            self.inner.binary_adders,
+            self.inner.ternary_adders
+        )
+    }
+}
+
+fn fpga_options(ternary: bool, carry_bits: u32) -> PyResult<crate::FpgaOptions> {
+    let carry_bits = NonZeroU32::new(carry_bits)
+        .ok_or_else(|| PyValueError::new_err("Carry chain bits must be positive"))?;
+    Ok(crate::FpgaOptions {
+        carry_bits,
+        ternary,
+    })
+}
+
+fn adder_cells_list(cells: Vec<crate::AdderCells>) -> Vec<PyAdderCells> {
+    cells
+        .into_iter()
+        .map(|inner| PyAdderCells { inner })
+        .collect()
+}
+
+/// Build a Python list of graph types
+fn graph_type_list<'py>(
+    py: Python<'py>,
+    types: &[crate::GraphType],
+) -> PyResult<Bound<'py, PyList>> {
+    let list = PyList::empty(py);
+    for gt in types {
+        list.append(PyGraphType { inner: gt.clone() })?;
+    }
+    Ok(list)
+}
+
+/// Build a Python list with the graph types of all odd integers in a table
+fn all_graph_types_list<'py>(
+    py: Python<'py>,
+    table: &crate::CostTable,
+) -> PyResult<Bound<'py, PyList>> {
+    let result = PyList::empty(py);
+    for types in table.all_graph_types()? {
+        result.append(graph_type_list(py, types)?)?;
+    }
+    Ok(result)
+}
+
+// Tables memory-mapped from a directory written by the generator
+#[pyclass(name = "CostTable", frozen)]
+struct PyCostTable {
+    inner: crate::CostTable,
+}
+
+#[pymethods]
+impl PyCostTable {
+    /// Open the tables in a directory, validating headers and checksums
+    #[staticmethod]
+    fn open(path: PathBuf) -> PyResult<Self> {
+        Ok(PyCostTable {
+            inner: crate::CostTable::open(path)?,
+        })
+    }
+
+    /// Largest odd integer in the tables
+    #[getter]
+    fn max_coefficient(&self) -> u64 {
+        self.inner.max_coefficient()
+    }
+
+    /// Get adder cost at index (right-shifts even indices until odd)
+    ///
+    /// With max_depth, the minimum cost with at most max_depth adders on any
+    /// path, or None if no structure that shallow is known.
+    #[pyo3(signature = (idx, max_depth=None))]
+    fn adder_cost(&self, idx: u64, max_depth: Option<u32>) -> PyResult<Option<u8>> {
+        Ok(match max_depth {
+            Some(max_depth) => self.inner.adder_cost_within_depth(idx, max_depth)?,
+            None => Some(self.inner.adder_cost(idx)?),
+        })
+    }
+
+    /// Get info about the table data
+    fn info(&self) -> String {
+        self.inner.info()
+    }
+
+    /// Get graph types at index (right-shifts even indices until odd)
+    fn get_graph_types(&self, py: Python, idx: u64) -> PyResult<Py<PyAny>> {
+        Ok(graph_type_list(py, self.inner.graph_types(idx)?)?.into())
+    }
+
+    /// Get all graph types as a list
+    fn get_all_graph_types(&self, py: Python) -> PyResult<Py<PyAny>> {
+        Ok(all_graph_types_list(py, &self.inner)?.into())
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "CostTable(max_coefficient={})",
+            self.inner.max_coefficient()
+        )
+    }
+}
+
+/// Use the tables in a directory for the module-level functions from now on
+#[pyfunction]
+fn load_tables(path: PathBuf) -> PyResult<()> {
+    crate::load_tables(path)?;
+    Ok(())
+}
+
+/// Get adder cost at index (right-shifts even indices until odd)
+///
+/// With max_depth, the minimum cost with at most max_depth adders on any path,
+/// or None if no structure that shallow is known.
+#[pyfunction]
+#[pyo3(signature = (idx, max_depth=None))]
+fn adder_cost(idx: u64, max_depth: Option<u32>) -> PyResult<Option<u8>> {
+    Ok(match max_depth {
+        Some(max_depth) => crate::adder_cost_within_depth(idx, max_depth)?,
+        None => Some(crate::adder_cost(idx)?),
+    })
+}
+
+/// Get info about the embedded data
+#[pyfunction]
+fn info() -> String {
+    crate::info()
+}
+
+/// Get graph types at index (right-shifts even indices until odd)
+#[pyfunction]
+fn get_graph_types(py: Python, idx: u64) -> PyResult<Py<PyAny>> {
+    Ok(graph_type_list(py, crate::graph_types(idx)?)?.into())
+}
+
+thread_local! {
+    // Exception raised by the last failing cost callback, taken when converting its error
+    static CALLBACK_ERROR: RefCell<Option<PyErr>> = const { RefCell::new(None) };
+}
+
+// Callbacks registered with `register_cost_model`
+static REGISTERED_MODELS: Mutex<BTreeMap<String, Py<PyAny>>> = Mutex::new(BTreeMap::new());
+
+type SharedCostModel = Arc<dyn CostModel + Send + Sync>;
+
+/// Cost model calling a Python function with the AdderGraph, returning a float
+struct Callback(Py<PyAny>);
+
+impl CostModel for Callback {
+    fn cost(&self, graph: &crate::AdderGraph) -> Result<f64, Error> {
+        // An exception left by an error that was never converted must not be
+        // raised for a later one
+        CALLBACK_ERROR.take();
+        Python::attach(|py| {
+            let graph = PyAdderGraph {
+                inner: graph.clone(),
+            };
+            self.0.call1(py, (graph,))?.extract::<f64>(py)
+        })
+        .map_err(|e| {
+            let message = e.to_string();
+            CALLBACK_ERROR.set(Some(e));
+            Error::CostModel(message)
+        })
+    }
+}
+
+// A cost model to rank structures by
+#[pyclass(name = "CostModel", frozen)]
+struct PyCostModel {
+    inner: SharedCostModel,
+    description: String,
+}
+
+impl PyCostModel {
+    fn new(inner: impl CostModel + Send + Sync + 'static, description: String) -> Self {
+        PyCostModel {
+            inner: Arc::new(inner),
+            description,
+        }
+    }
+}
+
+#[pymethods]
+impl PyCostModel {
+    /// Number of adders and subtractors
+    #[staticmethod]
+    fn adder_count() -> Self {
+        Self::new(crate::AdderCount, "CostModel.adder_count()".to_string())
+    }
+
+    /// Adders on the longest path
+    #[staticmethod]
+    fn depth() -> Self {
+        Self::new(crate::Depth, "CostModel.depth()".to_string())
+    }
+
+    /// Full adders for an input of wordlength bits
+    #[staticmethod]
+    #[pyo3(signature = (wordlength, signed=false))]
+    fn full_adders(wordlength: u32, signed: bool) -> Self {
+        Self::new(
+            crate::FullAdders(HdlOptions::new(wordlength, signed)),
+            format!(
+                "CostModel.full_adders({wordlength}, signed={})",
+                py_bool(signed)
+            ),
+        )
+    }
+
+    /// LUTs of the adders on an FPGA with carry chains, fusing ternary adders with ternary
+    #[staticmethod]
+    #[pyo3(signature = (wordlength, signed=false, ternary=false))]
+    fn fpga_luts(wordlength: u32, signed: bool, ternary: bool) -> Self {
+        Self::new(
+            crate::FpgaLuts {
+                options: HdlOptions::new(wordlength, signed),
+                fpga: crate::FpgaOptions {
+                    ternary,
+                    ..crate::FpgaOptions::default()
+                },
+            },
+            format!(
+                "CostModel.fpga_luts({wordlength}, signed={}, ternary={})",
+                py_bool(signed),
+                py_bool(ternary)
+            ),
+        )
+    }
+
+    /// Standard cell area of the full adders, half adders and inverters
+    #[staticmethod]
+    #[pyo3(signature = (wordlength, signed=false, full_adder=7.0, half_adder=3.5, inverter=0.67))]
+    fn asic_area(
+        wordlength: u32,
+        signed: bool,
+        full_adder: f64,
+        half_adder: f64,
+        inverter: f64,
+    ) -> Self {
+        Self::new(
+            crate::AsicArea {
+                full_adder,
+                half_adder,
+                inverter,
+                ..crate::AsicArea::new(HdlOptions::new(wordlength, signed))
+            },
+            format!(
+                "CostModel.asic_area({wordlength}, signed={}, full_adder={full_adder:?}, \
+                 half_adder={half_adder:?}, inverter={inverter:?})",
+                py_bool(signed)
+            ),
+        )
+    }
+
+    /// A function taking an AdderGraph and returning its cost as a float
+    #[staticmethod]
+    fn custom(callback: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if !callback.is_callable() {
+            return Err(PyTypeError::new_err("Cost callback is not callable"));
+        }
+        Ok(Self::new(
+            Callback(callback.clone().unbind()),
+            format!("CostModel.custom({})", callback.repr()?),
+        ))
+    }
+
+    /// Cost of an adder graph, lower is better
+    fn cost(&self, graph: &PyAdderGraph) -> PyResult<f64> {
+        Ok(self.inner.cost(&graph.inner)?)
+    }
+
+    fn __repr__(&self) -> String {
+        self.description.clone()
+    }
+}
+
+fn py_bool(value: bool) -> &'static str {
+    if value { "True" } else { "False" }
+}
+
+/// Cost model from a CostModel, the name of a built-in or registered model, or a callback
+fn cost_model(model: &Bound<'_, PyAny>) -> PyResult<SharedCostModel> {
+    if let Ok(model) = model.cast::<PyCostModel>() {
+        return Ok(model.get().inner.clone());
+    }
+    if let Ok(name) = model.cast::<PyString>() {
+        let name = name.to_cow()?;
+        return match name.as_ref() {
+            "adder_count" => Ok(Arc::new(crate::AdderCount)),
+            "depth" => Ok(Arc::new(crate::Depth)),
+            name => {
+                let models = REGISTERED_MODELS
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner);
+                match models.get(name) {
+                    Some(callback) => Ok(Arc::new(Callback(callback.clone_ref(model.py())))),
+                    None => Err(PyValueError::new_err(format!("Unknown cost model: {name}"))),
+                }
+            }
+        };
+    }
+    if model.is_callable() {
+        return Ok(Arc::new(Callback(model.clone().unbind())));
+    }
+    Err(PyTypeError::new_err(
+        "A cost model must be a CostModel, a model name or a callable",
+    ))
+}
+
+/// Register a function taking an AdderGraph and returning its cost, to be used by name
+///
+/// The name can then be passed as the model of best_structure and
+/// pareto_structures. Registering a name again replaces the function.
+#[pyfunction]
+fn register_cost_model(name: String, callback: &Bound<'_, PyAny>) -> PyResult<()> {
+    if matches!(name.as_str(), "adder_count" | "depth") {
+        return Err(PyValueError::new_err(format!(
+            "Cannot replace the built-in cost model {name}"
+        )));
+    }
+    if !callback.is_callable() {
+        return Err(PyTypeError::new_err("Cost callback is not callable"));
+    }
+    REGISTERED_MODELS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(name, callback.clone().unbind());
+    Ok(())
+}
+
+/// Stored structure of n with the minimum number of adders that is best for the model
+///
+/// The model is a CostModel, "adder_count", "depth" (the default), the name of
+/// a model added with register_cost_model, or a function taking an AdderGraph
+/// and returning a float. Returns None for powers of two.
+#[pyfunction]
+#[pyo3(signature = (n, model=None), text_signature = "(n, model=\"depth\")")]
+fn best_structure(n: u64, model: Option<&Bound<'_, PyAny>>) -> PyResult<Option<PyGraphType>> {
+    let model = match model {
+        Some(model) => cost_model(model)?,
+        None => Arc::new(crate::Depth),
+    };
+    Ok(crate::best_structure(n, &*model)?.map(|inner| PyGraphType { inner }))
+}
+
+/// Stored structures of n not dominated for the models, with their costs
+///
+/// Returns a list of (GraphType, costs) pairs, with the costs in the order of
+/// the models, given as for best_structure.
+#[pyfunction]
+fn pareto_structures(
+    n: u64,
+    models: Vec<Bound<'_, PyAny>>,
+) -> PyResult<Vec<(PyGraphType, Vec<f64>)>> {
+    let models = models
+        .iter()
+        .map(cost_model)
+        .collect::<PyResult<Vec<SharedCostModel>>>()?;
+    let models: Vec<&dyn CostModel> = models
+        .iter()
+        .map(|model| &**model as &dyn CostModel)
+        .collect();
+    Ok(crate::pareto_structures(n, &models)?
+        .into_iter()
+        .map(|(inner, costs)| (PyGraphType { inner }, costs))
+        .collect())
+}
+
+/// Stored structures of n with their total cells, fewest full adders first
+///
+/// Returns a list of (GraphType, AdderCells) pairs for an input of wordlength
+/// bits, ordered by full adders and then half adders.
+#[pyfunction]
+#[pyo3(signature = (n, wordlength, signed=false))]
+fn rank_by_full_adders(
+    n: u64,
+    wordlength: u32,
+    signed: bool,
+) -> PyResult<Vec<(PyGraphType, PyAdderCells)>> {
+    let options = hdl_options(wordlength, signed, None, None);
+    Ok(crate::rank_by_full_adders(n, &options)?
+        .into_iter()
+        .map(|(inner, cells)| (PyGraphType { inner }, PyAdderCells { inner: cells }))
+        .collect())
+}
+
+/// Get graph types at index including the ones left out by symmetry
+#[pyfunction]
+fn expand_symmetric(py: Python, idx: u64) -> PyResult<Py<PyAny>> {
+    Ok(graph_type_list(py, &crate::expand_symmetric(idx)?)?.into())
+}
+
+/// Adders multiplying by n, from the first stored structure of its odd part
+#[pyfunction]
+fn adder_graph(n: u64) -> PyResult<PyAdderGraph> {
+    Ok(PyAdderGraph {
+        inner: crate::adder_graph(n)?,
+    })
+}
+
+/// Verilog module multiplying by n
+#[pyfunction]
+#[pyo3(signature = (n, width, signed=false, name=None, pipeline=None))]
+fn to_verilog(
+    n: u64,
+    width: u32,
+    signed: bool,
+    name: Option<String>,
+    pipeline: Option<u32>,
+) -> PyResult<String> {
+    Ok(crate::to_verilog(
+        n,
+        &hdl_options(width, signed, name, pipeline),
+    )?)
+}
+
+/// VHDL-2008 entity multiplying by n
+#[pyfunction]
+#[pyo3(signature = (n, width, signed=false, name=None, pipeline=None))]
+fn to_vhdl(
+    n: u64,
+    width: u32,
+    signed: bool,
+    name: Option<String>,
+    pipeline: Option<u32>,
+) -> PyResult<String> {
+    Ok(crate::to_vhdl(
+        n,
+        &hdl_options(width, signed, name, pipeline),
+    )?)
+}
+
+/// Python source of an Amaranth module multiplying by n
+#[pyfunction]
+#[pyo3(signature = (n, width, signed=false, name=None, pipeline=None))]
+fn to_amaranth(
+    n: u64,
+    width: u32,
+    signed: bool,
+    name: Option<String>,
+    pipeline: Option<u32>,
+) -> PyResult<String> {
+    Ok(crate::to_amaranth(
+        n,
+        &hdl_options(width, signed, name, pipeline),
+    )?)
+}
+
+/// C function multiplying by n
+#[pyfunction]
+#[pyo3(signature = (n, width, signed=false, name=None))]
+fn to_c(n: u64, width: u32, signed: bool, name: Option<String>) -> PyResult<String> {
+    Ok(crate::to_c(n, &hdl_options(width, signed, name, None))?)
+}
+
+/// Rust function multiplying by n
+#[pyfunction]
+#[pyo3(signature = (n, width, signed=false, name=None))]
+fn to_rust(n: u64, width: u32, signed: bool, name: Option<String>) -> PyResult<String> {
+    Ok(crate::to_rust(n, &hdl_options(width, signed, name, None))?)
+}
+
+/// Vitis HLS C++ function multiplying by n
+#[pyfunction]
+#[pyo3(signature = (n, width, signed=false, name=None))]
+fn to_hls_cpp(n: u64, width: u32, signed: bool, name: Option<String>) -> PyResult<String> {
+    Ok(crate::to_hls_cpp(
+        n,
+        &hdl_options(width, signed, name, None),
+    )?)
+}
+
+/// Graphviz DOT description of the adders multiplying by n
+#[pyfunction]
+fn to_dot(n: u64) -> PyResult<String> {
+    Ok(crate::to_dot(n)?)
+}
+
+/// BLIF netlist of full and half adders multiplying by n
+#[pyfunction]
+#[pyo3(signature = (n, width, signed=false, name=None))]
+fn to_blif(n: u64, width: u32, signed: bool, name: Option<String>) -> PyResult<String> {
+    Ok(crate::to_blif(n, &hdl_options(width, signed, name, None))?)
+}
+
+/// Yosys RTLIL netlist of full and half adders multiplying by n
+#[pyfunction]
+#[pyo3(signature = (n, width, signed=false, name=None))]
+fn to_rtlil(n: u64, width: u32, signed: bool, name: Option<String>) -> PyResult<String> {
+    Ok(crate::to_rtlil(n, &hdl_options(width, signed, name, None))?)
+}
+
+/// Python source building a B-ASIC SFG multiplying by n
+#[pyfunction]
+fn to_b_asic(n: u64) -> PyResult<String> {
+    Ok(crate::to_b_asic(n)?)
+}
+
+/// Adder graph string multiplying by n for PAGSuite and FloPoCo
+#[pyfunction]
+fn to_pag(n: u64) -> PyResult<String> {
+    Ok(crate::to_pag(n)?)
+}
+
+/// Get all graph types as a list
+#[pyfunction]
+fn get_all_graph_types(py: Python) -> PyResult<Py<PyAny>> {
+    Ok(all_graph_types_list(py, crate::table())?.into())
+}
+
+#[pymodule]
+fn constant_multiplication(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyGraphType>()?;
+    m.add_class::<PyCostTable>()?;
+    m.add_class::<PyAdderGraph>()?;
+    m.add_class::<PyAdderNode>()?;
+    m.add_class::<PyPipelineReport>()?;
+    m.add_class::<PyAdderCells>()?;
+    m.add_class::<PyCostModel>()?;
+    m.add_class::<PyFpgaCost>()?;
+    m.add_class::<PyGraphTypeList>()?;
+    m.add_function(wrap_pyfunction!(load_tables, m)?)?;
+    m.add_function(wrap_pyfunction!(adder_cost, m)?)?;
+    m.add_function(wrap_pyfunction!(info, m)?)?;
+    m.add_function(wrap_pyfunction!(get_graph_types, m)?)?;
+    m.add_function(wrap_pyfunction!(get_all_graph_types, m)?)?;
+    m.add_function(wrap_pyfunction!(expand_symmetric, m)?)?;
+    m.add_function(wrap_pyfunction!(best_structure, m)?)?;
+    m.add_function(wrap_pyfunction!(pareto_structures, m)?)?;
+    m.add_function(wrap_pyfunction!(register_cost_model, m)?)?;
+    m.add_function(wrap_pyfunction!(rank_by_full_adders, m)?)?;
+    m.add_function(wrap_pyfunction!(adder_graph, m)?)?;
+    m.add_function(wrap_pyfunction!(to_verilog, m)?)?;
+    m.add_function(wrap_pyfunction!(to_vhdl, m)?)?;
+    m.add_function(wrap_pyfunction!(to_amaranth, m)?)?;
+    m.add_function(wrap_pyfunction!(to_c, m)?)?;
+    m.add_function(wrap_pyfunction!(to_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(to_hls_cpp, m)?)?;
+    m.add_function(wrap_pyfunction!(to_dot, m)?)?;
+    m.add_function(wrap_pyfunction!(to_blif, m)?)?;
+    m.add_function(wrap_pyfunction!(to_rtlil, m)?)?;
+    m.add_function(wrap_pyfunction!(to_b_asic, m)?)?;
+    m.add_function(wrap_pyfunction!(to_pag, m)?)?;
+    Ok(())
+}
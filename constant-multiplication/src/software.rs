@@ -0,0 +1,419 @@
+// src/software.rs
+use crate::adder_graph::source_name;
+use crate::hdl::{HdlGraph, HdlNode, HdlOptions};
+use crate::{AdderGraph, Error, Operand};
+use std::fmt::{self, Write};
+
+/// Languages of the generated functions
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Language {
+    C,
+    Rust,
+    /// C++ with the `ap_int` types of Vitis HLS
+    HlsCpp,
+}
+
+impl AdderGraph {
+    /// C function computing `value * x` with shifts and adds
+    ///
+    /// Temporaries are `<stdint.h>` types wide enough for any input of
+    /// `options.input_width` bits, so no intermediate result overflows. Signed
+    /// functions multiply by powers of two instead of shifting left, as left
+    /// shifts of negative values are undefined, and rely on right shifts of
+    /// negative values being arithmetic, which is implementation-defined but
+    /// done by all common compilers. Widths above 64 bits are not supported,
+    /// and `options.pipeline` is ignored.
+    pub fn to_c(&self, options: &HdlOptions) -> Result<String, Error> {
+        write_code(self, options, Language::C)
+    }
+
+    /// Rust function computing `value * x` with shifts and adds
+    ///
+    /// Temporaries are primitive integers wide enough for any input of
+    /// `options.input_width` bits, so no intermediate result overflows.
+    /// `options.pipeline` is ignored.
+    pub fn to_rust(&self, options: &HdlOptions) -> Result<String, Error> {
+        write_code(self, options, Language::Rust)
+    }
+
+    /// C++ function for Vitis HLS computing `value * x` with shifts and adds
+    ///
+    /// Every temporary is an `ap_int` or `ap_uint` of the exact width needed
+    /// for any input, as in [`AdderGraph::to_verilog`]. `options.pipeline` is
+    /// ignored.
+    pub fn to_hls_cpp(&self, options: &HdlOptions) -> Result<String, Error> {
+        write_code(self, options, Language::HlsCpp)
+    }
+}
+
+fn write_code(
+    graph: &AdderGraph,
+    options: &HdlOptions,
+    language: Language,
+) -> Result<String, Error> {
+    options.check()?;
+    let options = HdlOptions {
+        pipeline: None,
+        ..options.clone()
+    };
+    let graph = HdlGraph::new(graph, &options);
+    // Temporaries are computed wide enough for their shifted operands, as
+    // overflowing a signed type is undefined in C and panics in Rust
+    let sum_widths: Vec<u32> = graph
+        .nodes
+        .iter()
+        .map(|node| {
+            node.inputs
+                .iter()
+                .map(|operand| graph.width(operand.source) + operand.shift)
+                .fold(node.sum_width.unwrap_or(node.width), u32::max)
+        })
+        .collect();
+    let max_width = sum_widths
+        .iter()
+        .copied()
+        .chain([graph.output_width()])
+        .max()
+        .unwrap_or(0);
+    if language.type_width(max_width).is_none() {
+        return Err(Error::InvalidWidth(options.input_width));
+    }
+    let mut out = String::new();
+    Writer {
+        graph: &graph,
+        language,
+        sum_widths,
+    }
+    .write(&mut out)
+    .expect("writing to a String cannot fail");
+    Ok(out)
+}
+
+impl Language {
+    /// Width of the type holding a number of bits, if there is one
+    fn type_width(self, width: u32) -> Option<u32> {
+        let max = match self {
+            Language::C => 64,
+            Language::Rust => 128,
+            Language::HlsCpp => return Some(width),
+        };
+        Some(width.next_power_of_two().max(8)).filter(|&width| width <= max)
+    }
+
+    fn type_name(self, signed: bool, width: u32) -> String {
+        let width = self
+            .type_width(width)
+            .expect("widths are checked before writing");
+        match (self, signed) {
+            (Language::C, true) => format!("int{width}_t"),
+            (Language::C, false) => format!("uint{width}_t"),
+            (Language::Rust, true) => format!("i{width}"),
+            (Language::Rust, false) => format!("u{width}"),
+            (Language::HlsCpp, true) => format!("ap_int<{width}>"),
+            (Language::HlsCpp, false) => format!("ap_uint<{width}>"),
+        }
+    }
+
+    fn comment(self) -> &'static str {
+        match self {
+            Language::C | Language::HlsCpp => "//",
+            Language::Rust => "///",
+        }
+    }
+
+    /// Declaration of a temporary such as `int16_t t1 = x;`
+    fn declaration(self, type_name: &str, name: &str, expr: &str) -> String {
+        match self {
+            Language::C | Language::HlsCpp => format!("{type_name} {name} = {expr};"),
+            Language::Rust => format!("let {name}: {type_name} = {expr};"),
+        }
+    }
+}
+
+struct Writer<'a> {
+    graph: &'a HdlGraph<'a>,
+    language: Language,
+    // Width each node is computed in before shifting right
+    sum_widths: Vec<u32>,
+}
+
+impl Writer<'_> {
+    fn write(&self, out: &mut String) -> fmt::Result {
+        let graph = self.graph;
+        let options = graph.options;
+        let language = self.language;
+        let input_type = self.type_name(options.input_width);
+        let output_type = self.type_name(graph.output_width());
+
+        writeln!(
+            out,
+            "{} Multiplication by {} using {} adders",
+            language.comment(),
+            graph.value,
+            graph.nodes.len()
+        )?;
+        let name = options.name(graph.value);
+        match language {
+            Language::C | Language::HlsCpp => {
+                let header = if language == Language::C {
+                    "stdint.h"
+                } else {
+                    "ap_int.h"
+                };
+                writeln!(out, "#include <{header}>")?;
+                writeln!(out)?;
+                writeln!(out, "{output_type} {name}({input_type} x)")?;
+                writeln!(out, "{{")?;
+            }
+            Language::Rust => {
+                writeln!(out, "pub fn {name}(x: {input_type}) -> {output_type} {{")?;
+            }
+        }
+
+        for (node, &sum_width) in graph.nodes.iter().zip(&self.sum_widths) {
+            self.write_node(out, node, sum_width)?;
+        }
+
+        let output = &graph.output;
+        let expr = self.operand(output, graph.output_width());
+        match language {
+            Language::C | Language::HlsCpp => writeln!(out, "    return {expr};")?,
+            Language::Rust => writeln!(out, "    {expr}")?,
+        }
+        writeln!(out, "}}")
+    }
+
+    fn write_node(&self, out: &mut String, node: &HdlNode, sum_width: u32) -> fmt::Result {
+        // Subtract from the positive input, as unsigned types have no negation
+        let [a, b] = &node.inputs;
+        let (a, b) = if a.negative { (b, a) } else { (a, b) };
+        let sum = format!(
+            "{} {} {}",
+            self.term(a, sum_width),
+            if b.negative { "-" } else { "+" },
+            self.term(b, sum_width)
+        );
+        let sum_type = self.type_name(sum_width);
+        let node_type = self.type_name(node.width);
+        let expr = if node.right_shift > 0 {
+            let sum_name = format!("{}_sum", node.name);
+            writeln!(
+                out,
+                "    {}",
+                self.language.declaration(&sum_type, &sum_name, &sum)
+            )?;
+            format!("{sum_name} >> {}", node.right_shift)
+        } else {
+            sum
+        };
+        let expr = self.narrowed(&expr, &sum_type, &node_type);
+        writeln!(
+            out,
+            "    {}",
+            self.language.declaration(&node_type, &node.name, &expr)
+        )
+    }
+
+    fn type_name(&self, width: u32) -> String {
+        self.language.type_name(self.graph.options.signed, width)
+    }
+
+    /// Operand converted to the type of a width and shifted left
+    fn operand(&self, operand: &Operand, width: u32) -> String {
+        let name = source_name(operand.source);
+        let source_type = self.type_name(self.graph.width(operand.source));
+        let target_type = self.type_name(width);
+        let expr = if source_type == target_type {
+            name
+        } else {
+            match self.language {
+                Language::C => format!("({target_type}){name}"),
+                Language::Rust => format!("{target_type}::from({name})"),
+                Language::HlsCpp => format!("{target_type}({name})"),
+            }
+        };
+        match (operand.shift, self.language, self.graph.options.signed) {
+            (0, _, _) => expr,
+            // Only 0 and -1 can be shifted to the sign bit, and 2^63 is no int64_t
+            (63, Language::C, true) => format!("-{expr} * INT64_MIN"),
+            (shift, Language::C, true) => format!("{expr} * {}", 1u64 << shift),
+            (shift, _, _) => format!("{expr} << {shift}"),
+        }
+    }
+
+    /// Operand of a sum, in parentheses if shifted as shifts bind less than sums
+    fn term(&self, operand: &Operand, width: u32) -> String {
+        match operand.shift {
+            0 => self.operand(operand, width),
+            _ => format!("({})", self.operand(operand, width)),
+        }
+    }
+
+    /// Expression converted to a narrower type, which its value fits in
+    fn narrowed(&self, expr: &str, from: &str, to: &str) -> String {
+        if from == to {
+            return expr.to_string();
+        }
+        match self.language {
+            Language::C => format!("({to})({expr})"),
+            Language::Rust => format!("({expr}) as {to}"),
+            // Assigning to a narrower ap_int drops the high bits
+            Language::HlsCpp => expr.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{TempDir, VALUES, run, stored_graphs};
+    use crate::{GraphType, GraphTypeExt, HdlOptions};
+    use std::fmt::Write;
+    use std::process::Command;
+
+    /// Compiles the C functions of every stored structure of the values with
+    /// the undefined behaviour sanitizer, and compares them to the products for
+    /// all 8-bit inputs
+    #[test]
+    #[ignore = "needs a C compiler with the undefined behaviour sanitizer, run with --ignored"]
+    fn c_functions_multiply() {
+        let dir = TempDir::new("cm-c-test");
+        let mut source = String::new();
+        let mut main = String::from("#include <stdio.h>\n\nint main(void)\n{\n");
+        let mut expected = String::new();
+        let mut count = 0;
+        for graph in stored_graphs(VALUES) {
+            let value = graph.value();
+            for signed in [false, true] {
+                let name = format!("m{count}");
+                count += 1;
+                let options = HdlOptions {
+                    name: Some(name.clone()),
+                    ..HdlOptions::new(8, signed)
+                };
+                source.push_str(&graph.to_c(&options).unwrap());
+                let (low, high) = if signed { (-128, 127) } else { (0, 255) };
+                writeln!(
+                    main,
+                    "    for (long long x = {low}; x <= {high}; x++) \
+                         printf(\"%lld\\n\", (long long){name}(x));"
+                )
+                .unwrap();
+                for x in low..=high {
+                    writeln!(expected, "{}", i128::from(x) * i128::from(value)).unwrap();
+                }
+            }
+        }
+        main.push_str("    return 0;\n}\n");
+        let path = dir.path().join("mult.c");
+        std::fs::write(&path, source + &main).unwrap();
+        let binary = dir.path().join("mult");
+        run(Command::new("cc")
+            .args([
+                "-std=c99",
+                "-O1",
+                "-fsanitize=undefined",
+                "-fno-sanitize-recover=all",
+            ])
+            .arg(&path)
+            .arg("-o")
+            .arg(&binary));
+        // The sanitizer reports to standard error without failing when
+        // recovery is enabled, so it is checked to be empty as well
+        let output = Command::new(&binary).output().unwrap();
+        assert!(
+            output.status.success() && output.stderr.is_empty(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(String::from_utf8(output.stdout).unwrap() == expected);
+    }
+
+    /// Compiles the Rust functions of every stored structure of the values
+    /// with overflow checks, and compares them to the products for all 8-bit
+    /// inputs and the extreme 64-bit inputs
+    #[test]
+    fn rust_functions_multiply() {
+        let dir = TempDir::new("cm-rust-test");
+        let mut source = String::new();
+        let mut main = String::from("fn main() {\n");
+        let mut count = 0;
+        for graph in stored_graphs(VALUES) {
+            let value = graph.value();
+            for (input_width, signed) in [(8, false), (8, true), (64, false), (64, true)] {
+                let name = format!("m{count}");
+                count += 1;
+                let options = HdlOptions {
+                    name: Some(name.clone()),
+                    ..HdlOptions::new(input_width, signed)
+                };
+                source.push_str(&graph.to_rust(&options).unwrap());
+                let input = format!("{}{input_width}", if signed { 'i' } else { 'u' });
+                let inputs = if input_width == 8 {
+                    format!("{input}::MIN..={input}::MAX")
+                } else {
+                    format!(
+                        "[{input}::MIN, {input}::MIN + 1, 0, 1, {input}::MAX - 1, {input}::MAX]"
+                    )
+                };
+                writeln!(
+                        main,
+                        "    for x in {inputs} {{ \
+                         assert_eq!(i128::try_from({name}(x)).unwrap(), i128::from(x) * {value}); }}"
+                    )
+                    .unwrap();
+            }
+        }
+        main.push_str("}\n");
+        let path = dir.path().join("mult.rs");
+        std::fs::write(&path, source + &main).unwrap();
+        let binary = dir.path().join("mult");
+        // rustc comes with the toolchain running the tests, so it is never skipped
+        run(Command::new("rustc")
+            .args(["--edition", "2024", "-C", "overflow-checks=on", "-o"])
+            .arg(&binary)
+            .arg(&path));
+        run(&mut Command::new(&binary));
+    }
+
+    #[test]
+    fn c_temporaries_are_widened_before_shifting() {
+        let graph = GraphType::Adder(1, 85).to_adder_graph().unwrap();
+        assert_eq!(
+            graph.to_c(&HdlOptions::new(8, true)).unwrap(),
+            "\
+// Multiplication by 43 using 3 adders
+#include <stdint.h>
+
+int16_t mult_43(int8_t x)
+{
+    int16_t t1 = (int16_t)x + ((int16_t)x * 4);
+    int16_t t2 = t1 + (t1 * 16);
+    int16_t t3_sum = (int16_t)x + t2;
+    int16_t t3 = t3_sum >> 1;
+    return t3;
+}
+"
+        );
+    }
+
+    #[test]
+    fn hls_temporaries_have_exact_widths() {
+        let graph = GraphType::Adder(1, 85).to_adder_graph().unwrap();
+        assert_eq!(
+            graph.to_hls_cpp(&HdlOptions::new(8, true)).unwrap(),
+            "\
+// Multiplication by 43 using 3 adders
+#include <ap_int.h>
+
+ap_int<14> mult_43(ap_int<8> x)
+{
+    ap_int<11> t1 = ap_int<11>(x) + (ap_int<11>(x) << 2);
+    ap_int<15> t2 = ap_int<15>(t1) + (ap_int<15>(t1) << 4);
+    ap_int<15> t3_sum = ap_int<15>(x) + t2;
+    ap_int<14> t3 = t3_sum >> 1;
+    return t3;
+}
+"
+        );
+    }
+}
@@ -0,0 +1,296 @@
+// src/svg.rs
+use crate::dot::edge_label;
+use crate::{AdderGraph, Source};
+use std::fmt::{self, Write};
+
+// Layout in pixels
+// Space between columns, for the edges and their labels
+const COLUMN_GAP: f64 = 96.0;
+const ROW_HEIGHT: f64 = 64.0;
+const MARGIN: f64 = 24.0;
+const NODE_HEIGHT: f64 = 32.0;
+// Bend of the two inputs of a node coming from the same source
+const BEND: f64 = 16.0;
+// Upward bend for each column an edge skips, to pass above the nodes between
+const SKIP_BEND: f64 = 22.0;
+// Position of edge labels along the edges, from 0 at the source to 1 at the target
+const LABEL_POSITION: f64 = 0.7;
+// Space for the captions below drawings in a row
+const CAPTION_HEIGHT: f64 = 20.0;
+
+/// A drawn box, the input, an adder or the output
+struct Shape {
+    label: String,
+    /// Right shift shown below adders
+    note: Option<String>,
+    x: f64,
+    y: f64,
+    width: f64,
+    rounded: bool,
+}
+
+struct Edge {
+    from: usize,
+    to: usize,
+    bend: f64,
+    label: String,
+}
+
+/// Positions of the shapes of a graph, laid out in columns by adder depth
+struct Layout {
+    shapes: Vec<Shape>,
+    edges: Vec<Edge>,
+    width: f64,
+    height: f64,
+}
+
+impl AdderGraph {
+    /// SVG drawing of the adders, laid out in columns by adder depth
+    ///
+    /// Adders are labelled with their fundamentals, and edges with the sign
+    /// and left shift of the input, as in [`AdderGraph::to_dot`]. No external
+    /// tools are needed.
+    pub fn to_svg(&self) -> String {
+        let mut out = String::new();
+        Layout::new(self)
+            .write(&mut out, 0.0, 0.0)
+            .expect("writing to a String cannot fail");
+        out
+    }
+}
+
+/// SVG drawing of several graphs side by side, each with a caption below
+pub fn svg_row(graphs: &[(String, AdderGraph)]) -> String {
+    let layouts: Vec<Layout> = graphs.iter().map(|(_, graph)| Layout::new(graph)).collect();
+    let width = layouts
+        .iter()
+        .map(|layout| layout.width)
+        .fold(0.0, |a, b| a + b);
+    let height = layouts
+        .iter()
+        .map(|layout| layout.height)
+        .fold(0.0, f64::max)
+        + CAPTION_HEIGHT;
+
+    let mut out = String::new();
+    let mut write = || -> fmt::Result {
+        write_header(&mut out, width, height, 0.0, 0.0)?;
+        let mut x = 0.0;
+        for ((caption, _), layout) in graphs.iter().zip(&layouts) {
+            layout.write(&mut out, x, 0.0)?;
+            writeln!(
+                out,
+                "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\">{}</text>",
+                x + layout.width / 2.0,
+                height - CAPTION_HEIGHT / 2.0,
+                escape(caption)
+            )?;
+            x += layout.width;
+        }
+        writeln!(out, "</svg>")
+    };
+    write().expect("writing to a String cannot fail");
+    out
+}
+
+impl Layout {
+    fn new(graph: &AdderGraph) -> Self {
+        // Column of each node, the input in column 0
+        let columns: Vec<usize> = graph
+            .node_depths()
+            .into_iter()
+            .map(|depth| depth as usize)
+            .collect();
+        let output_column = columns.iter().max().map_or(1, |last| last + 1);
+        let mut rows = vec![0usize; output_column + 1];
+        let positions: Vec<(usize, usize)> = std::iter::once(0)
+            .chain(columns.iter().copied())
+            .chain(std::iter::once(output_column))
+            .map(|column| {
+                rows[column] += 1;
+                (column, rows[column] - 1)
+            })
+            .collect();
+        let row_count = rows.iter().copied().max().unwrap_or(1);
+
+        // Labels of the input, the adders and the output
+        let mut labels = vec![("x".to_string(), None, false)];
+        for node in &graph.nodes {
+            let note = (node.right_shift > 0).then(|| format!(">> {}", node.right_shift));
+            labels.push((node.value.to_string(), note, true));
+        }
+        labels.push((format!("{}x", graph.value()), None, false));
+        let output = labels.len() - 1;
+
+        // Columns are as wide as the widest shape, and the ones with fewer
+        // rows are centered vertically
+        let label_width = |label: &str| (label.len() as f64 * 8.0 + 16.0).max(NODE_HEIGHT);
+        let column_width = labels
+            .iter()
+            .map(|(label, _, _)| label_width(label))
+            .fold(0.0, f64::max)
+            + COLUMN_GAP;
+        let mut shapes: Vec<Shape> = labels
+            .into_iter()
+            .zip(&positions)
+            .map(|((label, note, rounded), &(column, row))| {
+                let offset = (row_count - rows[column]) as f64 * ROW_HEIGHT / 2.0;
+                Shape {
+                    width: label_width(&label),
+                    label,
+                    note,
+                    x: MARGIN + column as f64 * column_width + column_width / 2.0,
+                    y: MARGIN + offset + row as f64 * ROW_HEIGHT + ROW_HEIGHT / 2.0,
+                    rounded,
+                }
+            })
+            .collect();
+
+        let shape_index = |source: Source| match source {
+            Source::Input => 0,
+            Source::Node(idx) => idx + 1,
+        };
+        let skip_bend = |from: usize, to: usize| {
+            let span = positions[to].0 - positions[from].0;
+            -((span - 1) as f64) * SKIP_BEND
+        };
+        let mut edges = Vec::with_capacity(2 * graph.nodes.len() + 1);
+        for (idx, node) in graph.nodes.iter().enumerate() {
+            let [a, b] = &node.inputs;
+            let bend = if a.source == b.source { BEND } else { 0.0 };
+            for (operand, bend) in [(a, -bend), (b, bend)] {
+                let from = shape_index(operand.source);
+                edges.push(Edge {
+                    from,
+                    to: idx + 1,
+                    bend: bend + skip_bend(from, idx + 1),
+                    label: edge_label(operand),
+                });
+            }
+        }
+        let from = shape_index(graph.output.source);
+        edges.push(Edge {
+            from,
+            to: output,
+            bend: skip_bend(from, output),
+            label: match graph.output.shift {
+                0 => String::new(),
+                shift => format!("<< {shift}"),
+            },
+        });
+
+        // Move everything down to make room for edges bent above the top row
+        let highest = edges.iter().map(|edge| -edge.bend).fold(0.0, f64::max);
+        let padding = (highest + 16.0 - MARGIN - ROW_HEIGHT / 2.0).max(0.0);
+        for shape in &mut shapes {
+            shape.y += padding;
+        }
+
+        Layout {
+            shapes,
+            edges,
+            width: 2.0 * MARGIN + (output_column + 1) as f64 * column_width,
+            height: 2.0 * MARGIN + padding + row_count as f64 * ROW_HEIGHT,
+        }
+    }
+
+    fn write(&self, out: &mut String, x: f64, y: f64) -> fmt::Result {
+        write_header(out, self.width, self.height, x, y)?;
+        writeln!(
+            out,
+            "<defs><marker id=\"arrow\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" \
+             markerWidth=\"7\" markerHeight=\"7\" orient=\"auto\">\
+             <path d=\"M 0 0 L 10 5 L 0 10 z\"/></marker></defs>"
+        )?;
+        for edge in &self.edges {
+            self.write_edge(out, edge)?;
+        }
+        for shape in &self.shapes {
+            write_shape(out, shape)?;
+        }
+        writeln!(out, "</svg>")
+    }
+
+    /// Arrow from the right side of one shape to the left side of another,
+    /// passing the middle offset by the bend, labelled close to the target
+    fn write_edge(&self, out: &mut String, edge: &Edge) -> fmt::Result {
+        let (from, to) = (&self.shapes[edge.from], &self.shapes[edge.to]);
+        let (x1, y1) = (from.x + from.width / 2.0, from.y);
+        let (x2, y2) = (to.x - to.width / 2.0, to.y);
+        // The curve passes halfway between the chord and the control point
+        let (cx, cy) = ((x1 + x2) / 2.0, (y1 + y2) / 2.0 + 2.0 * edge.bend);
+        writeln!(
+            out,
+            "<path d=\"M {x1} {y1} Q {cx} {cy} {x2} {y2}\" fill=\"none\" stroke=\"#555\" \
+             marker-end=\"url(#arrow)\"/>"
+        )?;
+        if !edge.label.is_empty() {
+            let point = |a: f64, c: f64, b: f64| {
+                let t = LABEL_POSITION;
+                (1.0 - t) * (1.0 - t) * a + 2.0 * t * (1.0 - t) * c + t * t * b
+            };
+            let color = if edge.label.starts_with('-') {
+                "#b22"
+            } else {
+                "#262"
+            };
+            writeln!(
+                out,
+                "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"11\" \
+                 fill=\"{color}\">{}</text>",
+                point(x1, cx, x2),
+                point(y1, cy, y2) - 4.0,
+                escape(&edge.label)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn write_header(out: &mut String, width: f64, height: f64, x: f64, y: f64) -> fmt::Result {
+    writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" x=\"{x}\" y=\"{y}\" width=\"{width}\" \
+         height=\"{height}\" viewBox=\"0 0 {width} {height}\" font-family=\"sans-serif\" \
+         font-size=\"13\">"
+    )
+}
+
+fn write_shape(out: &mut String, shape: &Shape) -> fmt::Result {
+    let radius = if shape.rounded {
+        NODE_HEIGHT / 2.0
+    } else {
+        3.0
+    };
+    writeln!(
+        out,
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{NODE_HEIGHT}\" rx=\"{radius}\" \
+         fill=\"#eef3fb\" stroke=\"#333\"/>",
+        shape.x - shape.width / 2.0,
+        shape.y - NODE_HEIGHT / 2.0,
+        shape.width
+    )?;
+    writeln!(
+        out,
+        "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\">{}</text>",
+        shape.x,
+        shape.y + 4.5,
+        escape(&shape.label)
+    )?;
+    if let Some(note) = &shape.note {
+        writeln!(
+            out,
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"11\">{}</text>",
+            shape.x,
+            shape.y + NODE_HEIGHT / 2.0 + 12.0,
+            escape(note)
+        )?;
+    }
+    Ok(())
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
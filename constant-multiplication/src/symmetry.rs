@@ -0,0 +1,103 @@
+// src/symmetry.rs
+use crate::{AdderGraph, CostTable, Error, GraphType};
+
+/// Whether a mirrored structure computes n with the minimum number of adders in the table
+///
+/// Structures using fundamentals outside the table are left out.
+fn is_equivalent(graph_type: &GraphType, n: u64, table: &CostTable) -> Result<bool, Error> {
+    if graph_type.value() != Some(n) {
+        return Ok(false);
+    }
+    let cost = table.adder_cost(n)?;
+    match AdderGraph::from_graph_type(graph_type, table) {
+        Ok(graph) => Ok(graph.adder_count() == usize::from(cost)),
+        Err(Error::InvalidStructure(_) | Error::OutOfRange(_)) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// All structures reached by repeatedly mirroring, excluding the structure itself
+///
+/// The generator stores only one structure of each such group, see
+/// `GraphType::mirrored`.
+pub(crate) fn equivalents(
+    graph_type: &GraphType,
+    table: &CostTable,
+) -> Result<Vec<GraphType>, Error> {
+    let Some(n) = graph_type.value() else {
+        return Ok(Vec::new());
+    };
+    let mut result = Vec::new();
+    let mut stack = vec![graph_type.clone()];
+    while let Some(current) = stack.pop() {
+        for mirrored in current.mirrored() {
+            if &mirrored != graph_type
+                && !result.contains(&mirrored)
+                && is_equivalent(&mirrored, n, table)?
+            {
+                result.push(mirrored.clone());
+                stack.push(mirrored);
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Stored structures of n followed by the equivalent ones left out by the generator
+pub(crate) fn expand_symmetric(
+    stored: &[GraphType],
+    table: &CostTable,
+) -> Result<Vec<GraphType>, Error> {
+    let mut result = stored.to_vec();
+    for graph_type in stored {
+        for equivalent in equivalents(graph_type, table)? {
+            if !result.contains(&equivalent) {
+                result.push(equivalent);
+            }
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GraphTypeExt, graph_types, table};
+
+    #[test]
+    fn equivalents_compute_the_same_value() {
+        let mut count = 0;
+        for n in (3..1 << 14).step_by(2) {
+            let stored = graph_types(n).unwrap();
+            for graph_type in stored {
+                for equivalent in graph_type.equivalents().unwrap() {
+                    assert_eq!(equivalent.value(), Some(n), "{equivalent:?}");
+                    assert_eq!(equivalent.to_adder_graph().unwrap().value(), n);
+                    assert!(equivalent.tag() <= 10, "{equivalent:?}");
+                    count += 1;
+                }
+            }
+            let expanded = expand_symmetric(stored, table()).unwrap();
+            assert_eq!(&expanded[..stored.len()], stored);
+            // Only structures that are not stored yet are added
+            for (i, graph_type) in expanded.iter().enumerate().skip(stored.len()) {
+                assert!(!expanded[..i].contains(graph_type), "{graph_type:?}");
+            }
+        }
+        assert!(count > 0);
+    }
+
+    #[test]
+    fn long_leapfrogs_have_no_equivalents() {
+        let leapfrogs: Vec<&GraphType> = (1 << 18..1 << 19)
+            .step_by(2)
+            .flat_map(|n| graph_types(n).unwrap())
+            .filter(|graph_type| graph_type.tag() >= 11)
+            .take(100)
+            .collect();
+        assert!(!leapfrogs.is_empty());
+        for leapfrog in leapfrogs {
+            assert!(leapfrog.equivalents().unwrap().is_empty(), "{leapfrog:?}");
+        }
+    }
+}
@@ -0,0 +1,277 @@
+// src/table.rs
+use crate::{Error, GraphType, symmetry};
+use constant_multiplication_format::{
+    AdderCostFile, DepthCostFile, FormatError, GraphTypesFile, UNKNOWN_COST,
+};
+use memmap2::Mmap;
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Name of the adder cost table in a table directory, as written by the generator
+pub const ADDER_COST_FILE_NAME: &str = "adder_cost.bin";
+
+/// Name of the depth-bounded adder cost table in a table directory, as written by the generator
+pub const DEPTH_COST_BOUND_FILE_NAME: &str = "depth_cost_bound.bin";
+
+/// Name of the graph types table in a table directory, as written by the generator
+pub const GRAPH_TYPES_FILE_NAME: &str = "graph_types.bin";
+
+/// Bytes of a table, either embedded in the library or memory-mapped from a file
+enum TableData {
+    Embedded(&'static [u8]),
+    Mapped(Mmap),
+}
+
+impl TableData {
+    fn map(path: &Path) -> Result<Self, Error> {
+        // Keep the path in the error, as there are several files to choose from
+        let with_path = |e: io::Error| io::Error::new(e.kind(), format!("{}: {e}", path.display()));
+        let file = File::open(path).map_err(with_path)?;
+        // SAFETY: The file must not be modified while mapped, as documented on `CostTable::open`
+        let mmap = unsafe { Mmap::map(&file).map_err(with_path)? };
+        Ok(TableData::Mapped(mmap))
+    }
+
+    fn bytes(&self) -> &[u8] {
+        match self {
+            TableData::Embedded(data) => data,
+            TableData::Mapped(mmap) => mmap,
+        }
+    }
+}
+
+/// Adder cost, depth-bounded adder cost and graph types tables generated together
+///
+/// Graph types are decoded one chunk at a time on first access, checking the
+/// checksum of that chunk, and kept for the lifetime of the table.
+pub struct CostTable {
+    path: Option<PathBuf>,
+    adder_costs: TableData,
+    depth_costs: TableData,
+    graph_types: TableData,
+    // Set once the checksums are verified, failures are repeated on the next access
+    adder_costs_verified: OnceLock<()>,
+    depth_costs_verified: OnceLock<()>,
+    chunks: Vec<OnceLock<Vec<Vec<GraphType>>>>,
+}
+
+impl CostTable {
+    /// Create a table from the embedded data, checksums are verified on first access
+    pub(crate) fn from_embedded(
+        adder_costs: &'static [u8],
+        depth_costs: &'static [u8],
+        graph_types: &'static [u8],
+    ) -> Self {
+        Self::new(
+            None,
+            TableData::Embedded(adder_costs),
+            TableData::Embedded(depth_costs),
+            TableData::Embedded(graph_types),
+        )
+        .expect("embedded tables are validated at compile time")
+    }
+
+    /// Memory-map the tables in a directory written by the generator
+    ///
+    /// The directory must contain `adder_cost.bin`, `depth_cost_bound.bin` and
+    /// `graph_types.bin` from the same generator run. Headers and checksums of all files are
+    /// validated before returning. The files must not be modified while the
+    /// table is open.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let table = Self::new(
+            Some(path.to_path_buf()),
+            TableData::map(&path.join(ADDER_COST_FILE_NAME))?,
+            TableData::map(&path.join(DEPTH_COST_BOUND_FILE_NAME))?,
+            TableData::map(&path.join(GRAPH_TYPES_FILE_NAME))?,
+        )?;
+        table.verify_adder_costs()?;
+        table.verify_depth_costs()?;
+        table.graph_types_file().verify_checksum()?;
+        Ok(table)
+    }
+
+    fn new(
+        path: Option<PathBuf>,
+        adder_costs: TableData,
+        depth_costs: TableData,
+        graph_types: TableData,
+    ) -> Result<Self, Error> {
+        let adder_cost_header = AdderCostFile::parse(adder_costs.bytes())?.header();
+        let depth_cost_header = DepthCostFile::parse(depth_costs.bytes())?.header();
+        let graph_types_file = GraphTypesFile::parse(graph_types.bytes())?;
+        let graph_types_header = graph_types_file.header();
+        if [adder_cost_header, depth_cost_header].iter().any(|header| {
+            header.max_bits != graph_types_header.max_bits
+                || header.max_extra_bits != graph_types_header.max_extra_bits
+        }) {
+            return Err(Error::MismatchedTables);
+        }
+
+        let chunks = (0..graph_types_file.chunk_count())
+            .map(|_| OnceLock::new())
+            .collect();
+        Ok(CostTable {
+            path,
+            adder_costs,
+            depth_costs,
+            graph_types,
+            adder_costs_verified: OnceLock::new(),
+            depth_costs_verified: OnceLock::new(),
+            chunks,
+        })
+    }
+
+    // All files are parsed successfully when the table is created
+    fn adder_cost_file(&self) -> AdderCostFile<'_> {
+        AdderCostFile::parse(self.adder_costs.bytes()).expect("validated on creation")
+    }
+
+    fn depth_cost_file(&self) -> DepthCostFile<'_> {
+        DepthCostFile::parse(self.depth_costs.bytes()).expect("validated on creation")
+    }
+
+    fn graph_types_file(&self) -> GraphTypesFile<'_> {
+        GraphTypesFile::parse(self.graph_types.bytes()).expect("validated on creation")
+    }
+
+    fn verify_adder_costs(&self) -> Result<(), Error> {
+        check(&self.adder_costs_verified, || {
+            self.adder_cost_file().verify_checksum()
+        })
+    }
+
+    fn verify_depth_costs(&self) -> Result<(), Error> {
+        check(&self.depth_costs_verified, || {
+            self.depth_cost_file().verify_checksum()
+        })
+    }
+
+    /// Minimum number of adders and subtractors to multiply by n
+    pub fn adder_cost(&self, n: u64) -> Result<u8, Error> {
+        self.verify_adder_costs()?;
+
+        let file = self.adder_cost_file();
+        let position = position(n, file.count())?;
+        file.get(position).ok_or(Error::OutOfRange(n))
+    }
+
+    /// Minimum number of adders to multiply by n with at most max_depth adders
+    /// on any path, or `None` if no structure that shallow is known
+    ///
+    /// Depths of six or more give [`CostTable::adder_cost`], as no structure
+    /// has more adders.
+    pub fn adder_cost_within_depth(&self, n: u64, max_depth: u32) -> Result<Option<u8>, Error> {
+        self.verify_depth_costs()?;
+
+        let file = self.depth_cost_file();
+        let position = position(n, file.count())?;
+        if position == 0 {
+            return Ok(Some(0));
+        }
+        // No structure has more adders than the largest bound
+        let depth = (max_depth as usize).min(file.max_depth());
+        Ok(file
+            .get(depth, position)
+            .filter(|&cost| cost != UNKNOWN_COST))
+    }
+
+    /// All stored structures multiplying by n with the minimum number of adders
+    ///
+    /// There are no structures for 1, or any power of two, as no adders are needed.
+    pub fn graph_types(&self, n: u64) -> Result<&[GraphType], Error> {
+        let file = self.graph_types_file();
+        let position = position(n, file.count())?;
+
+        // Only the chunk containing the position is decoded
+        let chunk = self.chunk(position / file.chunk_size())?;
+        Ok(&chunk[position % file.chunk_size()])
+    }
+
+    /// All structures multiplying by n with the minimum number of adders, including
+    /// the ones left out of the table by symmetry
+    ///
+    /// The stored structures come first, followed by their equivalents in this table.
+    pub fn expand_symmetric(&self, n: u64) -> Result<Vec<GraphType>, Error> {
+        symmetry::expand_symmetric(self.graph_types(n)?, self)
+    }
+
+    /// Structures of all odd integers in the table, the ones of n at position `n / 2`
+    pub fn all_graph_types(&self) -> Result<Vec<&[GraphType]>, Error> {
+        let mut result = Vec::with_capacity(self.graph_types_file().count());
+        for chunk_idx in 0..self.chunks.len() {
+            result.extend(self.chunk(chunk_idx)?.iter().map(Vec::as_slice));
+        }
+        Ok(result)
+    }
+
+    /// Largest odd integer in the table
+    pub fn max_coefficient(&self) -> u64 {
+        (2 * self.adder_cost_file().count() - 1) as u64
+    }
+
+    /// Description of the table data
+    pub fn info(&self) -> String {
+        let adder_costs = self.adder_cost_file();
+        let graph_types = self.graph_types_file();
+        let header = graph_types.header();
+        let source = match &self.path {
+            Some(path) => format!("Data in {}", path.display()),
+            None => "Embedded data".to_string(),
+        };
+        format!(
+            "{source}: {} elements, {} bytes packed, graph types: {} bytes compressed in {} chunks, \
+             generated for {} bits ({} extra), format version {}, variant set version {}, \
+             checksums {:#010x} and {:#010x}",
+            adder_costs.count() * 2,
+            adder_costs.packed_size(),
+            graph_types.compressed_size(),
+            graph_types.chunk_count(),
+            header.max_bits,
+            header.max_extra_bits,
+            header.format_version,
+            header.variant_set_version,
+            adder_costs.header().checksum,
+            header.checksum
+        )
+    }
+
+    /// Get a chunk of decoded graph types, decompressing and deserializing it on first use
+    fn chunk(&self, chunk_idx: usize) -> Result<&[Vec<GraphType>], Error> {
+        let cache = &self.chunks[chunk_idx];
+        if let Some(types) = cache.get() {
+            return Ok(types);
+        }
+
+        let types = self.graph_types_file().decode_chunk(chunk_idx)?;
+
+        // Another thread may have finished first, in which case its chunk is kept
+        Ok(cache.get_or_init(|| types))
+    }
+}
+
+/// Verify a checksum the first time it is needed
+fn check(
+    verified: &OnceLock<()>,
+    verify: impl FnOnce() -> Result<(), FormatError>,
+) -> Result<(), Error> {
+    if verified.get().is_none() {
+        verify()?;
+        let _ = verified.set(());
+    }
+    Ok(())
+}
+
+/// Position of the odd part of n in a table with count odd integers
+///
+/// index 1 -> position 0, index 3 -> position 1, index 5 -> position 2, etc.
+fn position(n: u64, count: usize) -> Result<usize, Error> {
+    // Right-shift even indices until odd
+    let odd = if n > 0 { n >> n.trailing_zeros() } else { n };
+    usize::try_from(odd / 2)
+        .ok()
+        .filter(|&position| position < count)
+        .ok_or(Error::OutOfRange(n))
+}
@@ -0,0 +1,106 @@
+// src/test_support.rs
+use crate::{AdderGraph, GraphTypeExt, HdlOptions, graph_types};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Values whose stored structures the generated designs are checked with
+pub(crate) const VALUES: [u64; 10] = [3, 7, 43, 45, 105, 255, 683, 1100, 11467, 65521];
+
+/// Adder graphs of every stored structure of the values
+pub(crate) fn stored_graphs(
+    values: impl IntoIterator<Item = u64>,
+) -> impl Iterator<Item = AdderGraph> {
+    values
+        .into_iter()
+        .flat_map(|n| graph_types(n).unwrap())
+        .map(|graph_type| graph_type.to_adder_graph().unwrap())
+}
+
+/// All values of an input of the width and signedness of the options
+pub(crate) fn inputs(options: &HdlOptions) -> Range<i128> {
+    let half = 1i128 << (options.input_width - 1);
+    if options.signed {
+        -half..half
+    } else {
+        0..2 * half
+    }
+}
+
+/// Compares the design of a graph to the products for all inputs
+///
+/// `run` returns the output of the design for an input.
+pub(crate) fn assert_multiplies(
+    graph: &AdderGraph,
+    options: &HdlOptions,
+    mut run: impl FnMut(i128) -> i128,
+) {
+    let value = i128::from(graph.value());
+    for x in inputs(options) {
+        assert_eq!(run(x), x * value, "{graph}\n{options:?}, x = {x}");
+    }
+}
+
+/// Compares the designs of every stored structure of [`VALUES`] to the
+/// products for all inputs, for each input width and pipeline, signed and
+/// unsigned
+///
+/// `design` builds the design of a graph with the options and returns a
+/// function computing its output for an input.
+pub(crate) fn assert_designs_multiply<R: FnMut(i128) -> i128>(
+    input_widths: &[u32],
+    pipelines: &[Option<u32>],
+    mut design: impl FnMut(&AdderGraph, &HdlOptions) -> R,
+) {
+    for graph in stored_graphs(VALUES) {
+        for &input_width in input_widths {
+            for signed in [false, true] {
+                for &pipeline in pipelines {
+                    let options = HdlOptions {
+                        pipeline,
+                        ..HdlOptions::new(input_width, signed)
+                    };
+                    assert_multiplies(&graph, &options, design(&graph, &options));
+                }
+            }
+        }
+    }
+}
+
+/// Directory in the system temporary directory, removed when dropped, also
+/// when an assertion fails
+pub(crate) struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(prefix: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("{prefix}-{}", std::process::id()));
+        std::fs::create_dir_all(&path).unwrap();
+        TempDir(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Run a command, failing when the program is missing or exits with an error
+///
+/// Returns the standard output.
+pub(crate) fn run(command: &mut Command) -> String {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let output = command
+        .output()
+        .unwrap_or_else(|e| panic!("{program} is needed for this test: {e}"));
+    assert!(
+        output.status.success(),
+        "{program} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
@@ -0,0 +1,225 @@
+// src/verilog.rs
+use crate::hdl::{HdlGraph, HdlOptions};
+use crate::{AdderGraph, Error};
+use std::fmt::{self, Write};
+
+impl AdderGraph {
+    /// Synthesizable Verilog module computing `y = value * x`
+    ///
+    /// Every node gets a wire wide enough for any input, so no intermediate
+    /// result overflows. Signed inputs are sign-extended by the expression
+    /// width rules, as all signals are then declared signed. Pipelined
+    /// modules get a `clk` input and register the output.
+    pub fn to_verilog(&self, options: &HdlOptions) -> Result<String, Error> {
+        options.check()?;
+        let mut out = String::new();
+        write_module(&mut out, &HdlGraph::new(self, options))
+            .expect("writing to a String cannot fail");
+        Ok(out)
+    }
+}
+
+fn write_module(out: &mut String, graph: &HdlGraph) -> fmt::Result {
+    let options = graph.options;
+    let signed = options.signed;
+    writeln!(
+        out,
+        "// Multiplication by {} using {} adders",
+        graph.value,
+        graph.nodes.len()
+    )?;
+    if let Some(summary) = graph.pipeline_summary() {
+        writeln!(out, "// {summary}")?;
+    }
+    writeln!(out, "module {} (", options.name(graph.value))?;
+    if graph.pipelined() {
+        writeln!(out, "    input  wire clk,")?;
+    }
+    writeln!(
+        out,
+        "    {},",
+        declaration("input  wire", signed, options.input_width, "x")
+    )?;
+    writeln!(
+        out,
+        "    {}",
+        declaration("output wire", signed, graph.output_width(), "y")
+    )?;
+    writeln!(out, ");")?;
+
+    for node in &graph.nodes {
+        if let Some(sum_width) = node.sum_width {
+            let sum_name = format!("{}_sum", node.name);
+            writeln!(
+                out,
+                "    {};",
+                declaration("wire", signed, sum_width, &sum_name)
+            )?;
+        }
+        writeln!(
+            out,
+            "    {};",
+            declaration("wire", signed, node.width, &node.name)
+        )?;
+    }
+    let registers = graph.registers();
+    for (name, _, width) in &registers {
+        writeln!(out, "    {};", declaration("reg ", signed, *width, name))?;
+    }
+    if !graph.nodes.is_empty() {
+        writeln!(out)?;
+    }
+
+    for node in &graph.nodes {
+        let [a, b] = &node.inputs;
+        let expr = format!(
+            "{}{} {} {}",
+            if a.negative { "-" } else { "" },
+            shifted(&graph.signal(a.source, node.stage), a.shift),
+            if b.negative { "-" } else { "+" },
+            shifted(&graph.signal(b.source, node.stage), b.shift)
+        );
+        if node.sum_width.is_some() {
+            // Shift the trailing zeros out of the full-width sum
+            let shift = if signed { ">>>" } else { ">>" };
+            writeln!(out, "    assign {}_sum = {expr};", node.name)?;
+            writeln!(
+                out,
+                "    assign {0} = {0}_sum {shift} {1};",
+                node.name, node.right_shift
+            )?;
+        } else {
+            writeln!(out, "    assign {} = {expr};", node.name)?;
+        }
+    }
+    writeln!(
+        out,
+        "    assign y = {};",
+        shifted(&graph.output_signal(), graph.output.shift)
+    )?;
+
+    if !registers.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "    always @(posedge clk) begin")?;
+        for (name, input, _) in &registers {
+            writeln!(out, "        {name} <= {input};")?;
+        }
+        writeln!(out, "    end")?;
+    }
+    writeln!(out, "endmodule")
+}
+
+/// Vector declaration such as `wire signed [7:0] x`
+fn declaration(kind: &str, signed: bool, width: u32, name: &str) -> String {
+    let signed = if signed { " signed" } else { "" };
+    format!("{kind}{signed} [{}:0] {name}", width - 1)
+}
+
+fn shifted(name: &str, shift: u32) -> String {
+    match shift {
+        0 => name.to_string(),
+        shift => format!("({name} << {shift})"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{GraphType, GraphTypeExt, HdlOptions};
+
+    #[test]
+    fn right_shifts_are_arithmetic_for_signed_inputs() {
+        let graph = GraphType::Adder(1, 85).to_adder_graph().unwrap();
+        assert_eq!(
+            graph.to_verilog(&HdlOptions::new(8, true)).unwrap(),
+            "\
+// Multiplication by 43 using 3 adders
+module mult_43 (
+    input  wire signed [7:0] x,
+    output wire signed [13:0] y
+);
+    wire signed [10:0] t1;
+    wire signed [14:0] t2;
+    wire signed [14:0] t3_sum;
+    wire signed [13:0] t3;
+
+    assign t1 = x + (x << 2);
+    assign t2 = t1 + (t1 << 4);
+    assign t3_sum = x + t2;
+    assign t3 = t3_sum >>> 1;
+    assign y = t3;
+endmodule
+"
+        );
+        let unsigned = graph.to_verilog(&HdlOptions::new(8, false)).unwrap();
+        assert!(unsigned.contains("    assign t3 = t3_sum >> 1;\n"));
+    }
+
+    #[test]
+    fn pipelines_register_every_level() {
+        let graph = GraphType::Cascade(3, 15).to_adder_graph().unwrap();
+        let options = HdlOptions {
+            pipeline: Some(1),
+            ..HdlOptions::new(8, false)
+        };
+        assert_eq!(
+            graph.to_verilog(&options).unwrap(),
+            "\
+// Multiplication by 45 using 2 adders
+// Registers after every adder level, latency 2 cycles, 24 register bits
+module mult_45 (
+    input  wire clk,
+    input  wire [7:0] x,
+    output wire [13:0] y
+);
+    wire [9:0] t1;
+    wire [13:0] t2;
+    reg  [9:0] t1_d1;
+    reg  [13:0] t2_d1;
+
+    assign t1 = x + (x << 1);
+    assign t2 = (t1_d1 << 4) - t1_d1;
+    assign y = t2_d1;
+
+    always @(posedge clk) begin
+        t1_d1 <= t1;
+        t2_d1 <= t2;
+    end
+endmodule
+"
+        );
+    }
+
+    #[test]
+    fn powers_of_two_are_wired() {
+        let options = HdlOptions {
+            name: Some("times_eight".to_string()),
+            ..HdlOptions::new(4, false)
+        };
+        assert_eq!(
+            crate::to_verilog(8, &options).unwrap(),
+            "\
+// Multiplication by 8 using 0 adders
+module times_eight (
+    input  wire [3:0] x,
+    output wire [6:0] y
+);
+    assign y = (x << 3);
+endmodule
+"
+        );
+    }
+
+    #[test]
+    fn invalid_options_are_rejected() {
+        for options in [
+            HdlOptions::new(0, false),
+            HdlOptions::new(65, true),
+            HdlOptions {
+                pipeline: Some(0),
+                ..HdlOptions::new(8, false)
+            },
+        ] {
+            assert!(crate::to_verilog(45, &options).is_err());
+        }
+    }
+}
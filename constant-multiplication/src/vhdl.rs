@@ -0,0 +1,359 @@
+// src/vhdl.rs
+use crate::hdl::{HdlGraph, HdlOptions};
+use crate::{AdderGraph, Error, Operand};
+use std::fmt::{self, Write};
+
+impl AdderGraph {
+    /// VHDL-2008 entity and architecture computing `y = value * x`
+    ///
+    /// Ports and signals are `numeric_std` vectors, `signed` or `unsigned`
+    /// depending on the options. Operands are resized to the width of the
+    /// sum before shifting and adding, so no intermediate result overflows.
+    /// Pipelined entities get a `clk` input and register the output.
+    pub fn to_vhdl(&self, options: &HdlOptions) -> Result<String, Error> {
+        options.check()?;
+        let mut out = String::new();
+        write_design(&mut out, &HdlGraph::new(self, options))
+            .expect("writing to a String cannot fail");
+        Ok(out)
+    }
+}
+
+fn write_design(out: &mut String, graph: &HdlGraph) -> fmt::Result {
+    let options = graph.options;
+    let value = graph.value;
+    let name = options.name(value);
+    let kind = if options.signed { "signed" } else { "unsigned" };
+    let output_width = graph.output_width();
+    let nodes = &graph.nodes;
+
+    writeln!(
+        out,
+        "-- Multiplication by {value} using {} adders",
+        nodes.len()
+    )?;
+    if let Some(summary) = graph.pipeline_summary() {
+        writeln!(out, "-- {summary}")?;
+    }
+    writeln!(out, "library ieee;")?;
+    writeln!(out, "use ieee.std_logic_1164.all;")?;
+    writeln!(out, "use ieee.numeric_std.all;")?;
+    writeln!(out)?;
+    writeln!(out, "entity {name} is")?;
+    writeln!(out, "    port (")?;
+    if graph.pipelined() {
+        writeln!(out, "        clk : in  std_logic;")?;
+    }
+    writeln!(
+        out,
+        "        x : in  {};",
+        vector(kind, options.input_width)
+    )?;
+    writeln!(out, "        y : out {}", vector(kind, output_width))?;
+    writeln!(out, "    );")?;
+    writeln!(out, "end entity {name};")?;
+    writeln!(out)?;
+    writeln!(out, "architecture rtl of {name} is")?;
+    // Sums are computed at least as wide as their operands, as resizing a
+    // signed operand to fewer bits would drop its high bits
+    let sum_widths: Vec<u32> = nodes
+        .iter()
+        .map(|node| {
+            let [a, b] = &node.inputs;
+            node.sum_width
+                .unwrap_or(node.width)
+                .max(graph.width(a.source))
+                .max(graph.width(b.source))
+        })
+        .collect();
+    for (node, &sum_width) in nodes.iter().zip(&sum_widths) {
+        if node.sum_width.is_some() {
+            writeln!(
+                out,
+                "    signal {}_sum : {};",
+                node.name,
+                vector(kind, sum_width)
+            )?;
+        }
+        writeln!(
+            out,
+            "    signal {} : {};",
+            node.name,
+            vector(kind, node.width)
+        )?;
+    }
+    let registers = graph.registers();
+    for (name, _, width) in &registers {
+        writeln!(out, "    signal {name} : {};", vector(kind, *width))?;
+    }
+    writeln!(out, "begin")?;
+
+    for (node, &sum_width) in nodes.iter().zip(&sum_widths) {
+        let operand = |operand: &Operand| {
+            shifted(
+                &resized(
+                    &graph.signal(operand.source, node.stage),
+                    graph.width(operand.source),
+                    sum_width,
+                ),
+                operand.shift,
+            )
+        };
+        // numeric_std has no unary minus for unsigned, so subtract from the positive input
+        let [a, b] = &node.inputs;
+        let (a, b) = if a.negative { (b, a) } else { (a, b) };
+        let expr = format!(
+            "{} {} {}",
+            operand(a),
+            if b.negative { "-" } else { "+" },
+            operand(b)
+        );
+        if node.sum_width.is_some() {
+            writeln!(out, "    {}_sum <= {expr};", node.name)?;
+            let shifted_sum = format!("shift_right({}_sum, {})", node.name, node.right_shift);
+            writeln!(
+                out,
+                "    {} <= {};",
+                node.name,
+                resized(&shifted_sum, sum_width, node.width)
+            )?;
+        } else {
+            writeln!(
+                out,
+                "    {} <= {};",
+                node.name,
+                resized(&expr, sum_width, node.width)
+            )?;
+        }
+    }
+    let output = resized(
+        &graph.output_signal(),
+        graph.width(graph.output.source),
+        output_width,
+    );
+    writeln!(out, "    y <= {};", shifted(&output, graph.output.shift))?;
+
+    if !registers.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "    process (clk)")?;
+        writeln!(out, "    begin")?;
+        writeln!(out, "        if rising_edge(clk) then")?;
+        for (name, input, _) in &registers {
+            writeln!(out, "            {name} <= {input};")?;
+        }
+        writeln!(out, "        end if;")?;
+        writeln!(out, "    end process;")?;
+    }
+    writeln!(out, "end architecture rtl;")
+}
+
+/// Vector type such as `signed(7 downto 0)`
+fn vector(kind: &str, width: u32) -> String {
+    format!("{kind}({} downto 0)", width - 1)
+}
+
+fn resized(expr: &str, width: u32, target_width: u32) -> String {
+    if width == target_width {
+        expr.to_string()
+    } else {
+        format!("resize({expr}, {target_width})")
+    }
+}
+
+fn shifted(expr: &str, shift: u32) -> String {
+    match shift {
+        0 => expr.to_string(),
+        shift => format!("shift_left({expr}, {shift})"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::assert_designs_multiply;
+    use crate::{GraphType, GraphTypeExt, HdlOptions};
+    use std::collections::HashMap;
+
+    /// A `numeric_std` value and its width
+    #[derive(Clone, Copy)]
+    struct Vector {
+        value: i128,
+        width: u32,
+    }
+
+    /// Interprets the concurrent assignments of a generated design
+    ///
+    /// Registers are treated as wires, which gives the settled output of a
+    /// pipeline. Assignments must have the width of their target, as VHDL
+    /// requires.
+    struct Design {
+        signed: bool,
+        widths: HashMap<String, u32>,
+        // Targets and the reversed tokens of their expressions
+        assignments: Vec<(String, Vec<String>)>,
+    }
+
+    impl Design {
+        fn parse(vhdl: &str, signed: bool) -> Self {
+            let mut widths = HashMap::new();
+            let mut assignments = Vec::new();
+            for line in vhdl.lines().map(str::trim) {
+                if let Some((name, vector)) = line.split_once(" : ")
+                    && let Some((_, high)) = vector.split_once('(')
+                    && let Some((high, _)) = high.split_once(" downto 0)")
+                {
+                    let name = name.trim_start_matches("signal ").to_string();
+                    widths.insert(name, high.parse::<u32>().unwrap() + 1);
+                } else if let Some((target, expr)) = line.split_once(" <= ") {
+                    let mut tokens = tokenize(expr.strip_suffix(';').unwrap());
+                    tokens.reverse();
+                    assignments.push((target.to_string(), tokens));
+                }
+            }
+            Design {
+                signed,
+                widths,
+                assignments,
+            }
+        }
+
+        fn wrap(&self, value: i128, width: u32) -> Vector {
+            let wrapped = value.rem_euclid(1 << width);
+            let value = if self.signed && wrapped >= 1 << (width - 1) {
+                wrapped - (1 << width)
+            } else {
+                wrapped
+            };
+            Vector { value, width }
+        }
+
+        /// Output for the input x
+        fn run(&self, x: i128) -> i128 {
+            let mut signals = HashMap::from([("x".to_string(), self.wrap(x, self.widths["x"]))]);
+            // Every pass settles at least one more pipeline stage
+            while signals.len() <= self.assignments.len() {
+                let known = signals.len();
+                for (target, expr) in &self.assignments {
+                    let mut tokens = expr.clone();
+                    if let Some(result) = self.expr(&mut tokens, &signals) {
+                        assert!(tokens.is_empty(), "{target}");
+                        assert_eq!(result.width, self.widths[target], "{target}");
+                        signals.insert(target.clone(), result);
+                    }
+                }
+                assert!(signals.len() > known, "unassigned signals");
+            }
+            signals["y"].value
+        }
+
+        /// Sum or difference of terms, `None` if a signal is not known yet
+        fn expr(
+            &self,
+            tokens: &mut Vec<String>,
+            signals: &HashMap<String, Vector>,
+        ) -> Option<Vector> {
+            let mut result = self.term(tokens, signals);
+            while let Some(op) = tokens.last().filter(|op| *op == "+" || *op == "-").cloned() {
+                tokens.pop();
+                let b = self.term(tokens, signals);
+                result = match (result, b) {
+                    (Some(a), Some(b)) => {
+                        let sum = if op == "+" {
+                            a.value + b.value
+                        } else {
+                            a.value - b.value
+                        };
+                        Some(self.wrap(sum, a.width.max(b.width)))
+                    }
+                    _ => None,
+                };
+            }
+            result
+        }
+
+        fn term(
+            &self,
+            tokens: &mut Vec<String>,
+            signals: &HashMap<String, Vector>,
+        ) -> Option<Vector> {
+            let name = tokens.pop().unwrap();
+            if tokens.last().is_none_or(|token| token != "(") {
+                return signals.get(&name).copied();
+            }
+            tokens.pop();
+            let arg = self.expr(tokens, signals);
+            assert_eq!(tokens.pop().as_deref(), Some(","));
+            let n: u32 = tokens.pop().unwrap().parse().unwrap();
+            assert_eq!(tokens.pop().as_deref(), Some(")"));
+            let arg = arg?;
+            Some(match name.as_str() {
+                // Shrinking a signed vector keeps its sign bit
+                "resize" if self.signed && n < arg.width => {
+                    let low = arg.value.rem_euclid(1 << (n - 1));
+                    Vector {
+                        value: if arg.value < 0 {
+                            low - (1 << (n - 1))
+                        } else {
+                            low
+                        },
+                        width: n,
+                    }
+                }
+                "resize" => self.wrap(arg.value, n),
+                "shift_left" => self.wrap(arg.value << n, arg.width),
+                "shift_right" => self.wrap(arg.value >> n, arg.width),
+                _ => panic!("unknown function {name}"),
+            })
+        }
+    }
+
+    fn tokenize(expr: &str) -> Vec<String> {
+        expr.replace('(', " ( ")
+            .replace(')', " ) ")
+            .replace(',', " , ")
+            .split_whitespace()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn designs_multiply() {
+        assert_designs_multiply(&[8], &[None, Some(2)], |graph, options| {
+            let design = Design::parse(&graph.to_vhdl(options).unwrap(), options.signed);
+            move |x| design.run(x)
+        });
+    }
+
+    #[test]
+    fn right_shifts_resize_the_sum() {
+        let graph = GraphType::Adder(1, 85).to_adder_graph().unwrap();
+        assert_eq!(
+            graph.to_vhdl(&HdlOptions::new(8, true)).unwrap(),
+            "\
+-- Multiplication by 43 using 3 adders
+library ieee;
+use ieee.std_logic_1164.all;
+use ieee.numeric_std.all;
+
+entity mult_43 is
+    port (
+        x : in  signed(7 downto 0);
+        y : out signed(13 downto 0)
+    );
+end entity mult_43;
+
+architecture rtl of mult_43 is
+    signal t1 : signed(10 downto 0);
+    signal t2 : signed(14 downto 0);
+    signal t3_sum : signed(14 downto 0);
+    signal t3 : signed(13 downto 0);
+begin
+    t1 <= resize(x, 11) + shift_left(resize(x, 11), 2);
+    t2 <= resize(t1, 15) + shift_left(resize(t1, 15), 4);
+    t3_sum <= resize(x, 15) + t2;
+    t3 <= resize(shift_right(t3_sum, 1), 14);
+    y <= t3;
+end architecture rtl;
+"
+        );
+    }
+}
@@ -0,0 +1,1409 @@
+//! Binary table formats shared by the generator and the library
+//!
+//! Three files are produced by the generator and embedded in the library:
+//!
+//! - `adder_cost.bin`: the minimum number of adders for each odd integer,
+//!   packed as 3-bit values
+//! - `depth_cost_bound.bin`: the minimum number of adders for each odd integer
+//!   when the adder depth is bounded, one packed table per bound
+//! - `graph_types.bin`: the optimal adder structures for each odd integer,
+//!   varint-encoded and stored as independently LZ4-compressed chunks
+//!
+//! All start with a [`TableHeader`] identifying the file, the table it holds
+//! and a checksum of the rest of the file.
+use std::fmt;
+use unsigned_varint::decode as varint_decode;
+use unsigned_varint::encode as varint_encode;
+
+/// Number of odd coefficients per independently compressed chunk of graph types
+pub const GRAPH_TYPES_CHUNK_SIZE: usize = 1024;
+
+/// Version of the layout of the files, increased on incompatible changes
+///
+/// Version 2 adds a checksum of every chunk of graph types to the index.
+pub const FORMAT_VERSION: u16 = 2;
+
+/// Version of the set of graph type variants and their tags
+///
+/// Version 1 has the tags 0 (`Adder`) to 34 (`Leapfrog8_16`).
+pub const VARIANT_SET_VERSION: u16 = 1;
+
+/// Adder cost of integers without a known structure, the largest packed 3-bit value
+pub const UNKNOWN_COST: u8 = 7;
+
+/// Magic number at the start of `adder_cost.bin`
+pub const ADDER_COST_MAGIC: [u8; 4] = *b"CMAC";
+
+/// Magic number at the start of `depth_cost_bound.bin`
+pub const DEPTH_COST_MAGIC: [u8; 4] = *b"CMDC";
+
+/// Magic number at the start of `graph_types.bin`
+pub const GRAPH_TYPES_MAGIC: [u8; 4] = *b"CMGT";
+
+/// Size of the [`TableHeader`] at the start of all files
+pub const HEADER_SIZE: usize = 16;
+
+/// Offset of the packed values in `adder_cost.bin`, after the header and count
+pub const ADDER_COST_HEADER_SIZE: usize = HEADER_SIZE + 8;
+
+/// Offset of the packed tables in `depth_cost_bound.bin`, after the header, count and number of bounds
+pub const DEPTH_COST_HEADER_SIZE: usize = HEADER_SIZE + 16;
+
+/// Offset of the chunk index in `graph_types.bin`, after the header, count and chunk size
+pub const GRAPH_TYPES_INDEX_OFFSET: usize = HEADER_SIZE + 16;
+
+/// Errors when decoding a table
+#[derive(Debug)]
+pub enum FormatError {
+    /// The data ended in the middle of a value
+    UnexpectedEnd,
+    /// A varint could not be decoded
+    Varint(varint_decode::Error),
+    /// A graph type has a tag not assigned to any variant
+    UnknownVariantTag(u8),
+    /// A header or chunk index does not match the size of the data
+    InvalidIndex(&'static str),
+    /// A chunk could not be decompressed
+    Decompress(lz4_flex::block::DecompressError),
+    /// The file does not start with the expected magic number
+    BadMagic,
+    /// The file was written with an unsupported format version
+    UnsupportedFormatVersion(u16),
+    /// The file uses graph type variants unknown to this version
+    UnsupportedVariantSet(u16),
+    /// The checksum in the header does not match the data
+    ChecksumMismatch { expected: u32, actual: u32 },
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatError::UnexpectedEnd => write!(f, "Unexpected end of data"),
+            FormatError::Varint(e) => write!(f, "Failed to decode varint: {e}"),
+            FormatError::UnknownVariantTag(tag) => write!(f, "Unknown variant tag: {tag}"),
+            FormatError::InvalidIndex(reason) => write!(f, "Invalid index: {reason}"),
+            FormatError::Decompress(e) => write!(f, "Failed to decompress: {e}"),
+            FormatError::BadMagic => write!(f, "Not a table file, wrong magic number"),
+            FormatError::UnsupportedFormatVersion(version) => {
+                write!(f, "Unsupported format version: {version}")
+            }
+            FormatError::UnsupportedVariantSet(version) => {
+                write!(f, "Unsupported variant set version: {version}")
+            }
+            FormatError::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "Checksum mismatch: expected {expected:#010x}, got {actual:#010x}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+/// Adder structure realizing an odd integer
+///
+/// The parameters are the (possibly shifted) constants combined by the
+/// structure, see [`GraphType::evaluate`] for the expression of each variant.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GraphType {
+    Adder(usize, usize),
+    Subtractor(usize, usize),
+    Cascade(usize, usize),
+    Leapfrog4_1(usize, usize, usize, usize),
+    Leapfrog4_2(usize, usize, usize, usize),
+    Leapfrog4_3(usize, usize, usize, usize),
+    Leapfrog4_4(usize, usize, usize, usize),
+    Leapfrog5_1(usize, usize, usize, usize, usize),
+    Leapfrog5_2(usize, usize, usize, usize, usize),
+    Leapfrog5_3(usize, usize, usize, usize, usize),
+    Leapfrog5_4(usize, usize, usize, usize, usize),
+    Leapfrog7_1(usize, usize, usize, usize, usize, usize, usize),
+    Leapfrog7_2(usize, usize, usize, usize, usize, usize, usize),
+    Leapfrog7_3(usize, usize, usize, usize, usize, usize, usize),
+    Leapfrog7_4(usize, usize, usize, usize, usize, usize, usize),
+    Leapfrog7_5(usize, usize, usize, usize, usize, usize, usize),
+    Leapfrog7_6(usize, usize, usize, usize, usize, usize, usize),
+    Leapfrog7_7(usize, usize, usize, usize, usize, usize, usize),
+    Leapfrog7_8(usize, usize, usize, usize, usize, usize, usize),
+    Leapfrog8_1(usize, usize, usize, usize, usize, usize, usize, usize),
+    Leapfrog8_2(usize, usize, usize, usize, usize, usize, usize, usize),
+    Leapfrog8_3(usize, usize, usize, usize, usize, usize, usize, usize),
+    Leapfrog8_4(usize, usize, usize, usize, usize, usize, usize, usize),
+    Leapfrog8_5(usize, usize, usize, usize, usize, usize, usize, usize),
+    Leapfrog8_6(usize, usize, usize, usize, usize, usize, usize, usize),
+    Leapfrog8_7(usize, usize, usize, usize, usize, usize, usize, usize),
+    Leapfrog8_8(usize, usize, usize, usize, usize, usize, usize, usize),
+    Leapfrog8_9(usize, usize, usize, usize, usize, usize, usize, usize),
+    Leapfrog8_10(usize, usize, usize, usize, usize, usize, usize, usize),
+    Leapfrog8_11(usize, usize, usize, usize, usize, usize, usize, usize),
+    Leapfrog8_12(usize, usize, usize, usize, usize, usize, usize, usize),
+    Leapfrog8_13(usize, usize, usize, usize, usize, usize, usize, usize),
+    Leapfrog8_14(usize, usize, usize, usize, usize, usize, usize, usize),
+    Leapfrog8_15(usize, usize, usize, usize, usize, usize, usize, usize),
+    Leapfrog8_16(usize, usize, usize, usize, usize, usize, usize, usize),
+}
+
+impl GraphType {
+    /// Tag identifying the variant in `graph_types.bin`
+    pub fn tag(&self) -> u8 {
+        match self {
+            GraphType::Adder(..) => 0,
+            GraphType::Subtractor(..) => 1,
+            GraphType::Cascade(..) => 2,
+            GraphType::Leapfrog4_1(..) => 3,
+            GraphType::Leapfrog4_2(..) => 4,
+            GraphType::Leapfrog4_3(..) => 5,
+            GraphType::Leapfrog4_4(..) => 6,
+            GraphType::Leapfrog5_1(..) => 7,
+            GraphType::Leapfrog5_2(..) => 8,
+            GraphType::Leapfrog5_3(..) => 9,
+            GraphType::Leapfrog5_4(..) => 10,
+            GraphType::Leapfrog7_1(..) => 11,
+            GraphType::Leapfrog7_2(..) => 12,
+            GraphType::Leapfrog7_3(..) => 13,
+            GraphType::Leapfrog7_4(..) => 14,
+            GraphType::Leapfrog7_5(..) => 15,
+            GraphType::Leapfrog7_6(..) => 16,
+            GraphType::Leapfrog7_7(..) => 17,
+            GraphType::Leapfrog7_8(..) => 18,
+            GraphType::Leapfrog8_1(..) => 19,
+            GraphType::Leapfrog8_2(..) => 20,
+            GraphType::Leapfrog8_3(..) => 21,
+            GraphType::Leapfrog8_4(..) => 22,
+            GraphType::Leapfrog8_5(..) => 23,
+            GraphType::Leapfrog8_6(..) => 24,
+            GraphType::Leapfrog8_7(..) => 25,
+            GraphType::Leapfrog8_8(..) => 26,
+            GraphType::Leapfrog8_9(..) => 27,
+            GraphType::Leapfrog8_10(..) => 28,
+            GraphType::Leapfrog8_11(..) => 29,
+            GraphType::Leapfrog8_12(..) => 30,
+            GraphType::Leapfrog8_13(..) => 31,
+            GraphType::Leapfrog8_14(..) => 32,
+            GraphType::Leapfrog8_15(..) => 33,
+            GraphType::Leapfrog8_16(..) => 34,
+        }
+    }
+
+    /// Name of the variant
+    pub fn name(&self) -> &'static str {
+        match self {
+            GraphType::Adder(..) => "Adder",
+            GraphType::Subtractor(..) => "Subtractor",
+            GraphType::Cascade(..) => "Cascade",
+            GraphType::Leapfrog4_1(..) => "Leapfrog4_1",
+            GraphType::Leapfrog4_2(..) => "Leapfrog4_2",
+            GraphType::Leapfrog4_3(..) => "Leapfrog4_3",
+            GraphType::Leapfrog4_4(..) => "Leapfrog4_4",
+            GraphType::Leapfrog5_1(..) => "Leapfrog5_1",
+            GraphType::Leapfrog5_2(..) => "Leapfrog5_2",
+            GraphType::Leapfrog5_3(..) => "Leapfrog5_3",
+            GraphType::Leapfrog5_4(..) => "Leapfrog5_4",
+            GraphType::Leapfrog7_1(..) => "Leapfrog7_1",
+            GraphType::Leapfrog7_2(..) => "Leapfrog7_2",
+            GraphType::Leapfrog7_3(..) => "Leapfrog7_3",
+            GraphType::Leapfrog7_4(..) => "Leapfrog7_4",
+            GraphType::Leapfrog7_5(..) => "Leapfrog7_5",
+            GraphType::Leapfrog7_6(..) => "Leapfrog7_6",
+            GraphType::Leapfrog7_7(..) => "Leapfrog7_7",
+            GraphType::Leapfrog7_8(..) => "Leapfrog7_8",
+            GraphType::Leapfrog8_1(..) => "Leapfrog8_1",
+            GraphType::Leapfrog8_2(..) => "Leapfrog8_2",
+            GraphType::Leapfrog8_3(..) => "Leapfrog8_3",
+            GraphType::Leapfrog8_4(..) => "Leapfrog8_4",
+            GraphType::Leapfrog8_5(..) => "Leapfrog8_5",
+            GraphType::Leapfrog8_6(..) => "Leapfrog8_6",
+            GraphType::Leapfrog8_7(..) => "Leapfrog8_7",
+            GraphType::Leapfrog8_8(..) => "Leapfrog8_8",
+            GraphType::Leapfrog8_9(..) => "Leapfrog8_9",
+            GraphType::Leapfrog8_10(..) => "Leapfrog8_10",
+            GraphType::Leapfrog8_11(..) => "Leapfrog8_11",
+            GraphType::Leapfrog8_12(..) => "Leapfrog8_12",
+            GraphType::Leapfrog8_13(..) => "Leapfrog8_13",
+            GraphType::Leapfrog8_14(..) => "Leapfrog8_14",
+            GraphType::Leapfrog8_15(..) => "Leapfrog8_15",
+            GraphType::Leapfrog8_16(..) => "Leapfrog8_16",
+        }
+    }
+
+    /// Parameters of the variant, in order
+    pub fn params(&self) -> Vec<usize> {
+        match self {
+            GraphType::Adder(a, b) => vec![*a, *b],
+            GraphType::Subtractor(a, b) => vec![*a, *b],
+            GraphType::Cascade(a, b) => vec![*a, *b],
+            GraphType::Leapfrog4_1(a, b, c, d) => vec![*a, *b, *c, *d],
+            GraphType::Leapfrog4_2(a, b, c, d) => vec![*a, *b, *c, *d],
+            GraphType::Leapfrog4_3(a, b, c, d) => vec![*a, *b, *c, *d],
+            GraphType::Leapfrog4_4(a, b, c, d) => vec![*a, *b, *c, *d],
+            GraphType::Leapfrog5_1(a, b, c, d, e) => vec![*a, *b, *c, *d, *e],
+            GraphType::Leapfrog5_2(a, b, c, d, e) => vec![*a, *b, *c, *d, *e],
+            GraphType::Leapfrog5_3(a, b, c, d, e) => vec![*a, *b, *c, *d, *e],
+            GraphType::Leapfrog5_4(a, b, c, d, e) => vec![*a, *b, *c, *d, *e],
+            GraphType::Leapfrog7_1(a, b, c, d, e, f, g) => vec![*a, *b, *c, *d, *e, *f, *g],
+            GraphType::Leapfrog7_2(a, b, c, d, e, f, g) => vec![*a, *b, *c, *d, *e, *f, *g],
+            GraphType::Leapfrog7_3(a, b, c, d, e, f, g) => vec![*a, *b, *c, *d, *e, *f, *g],
+            GraphType::Leapfrog7_4(a, b, c, d, e, f, g) => vec![*a, *b, *c, *d, *e, *f, *g],
+            GraphType::Leapfrog7_5(a, b, c, d, e, f, g) => vec![*a, *b, *c, *d, *e, *f, *g],
+            GraphType::Leapfrog7_6(a, b, c, d, e, f, g) => vec![*a, *b, *c, *d, *e, *f, *g],
+            GraphType::Leapfrog7_7(a, b, c, d, e, f, g) => vec![*a, *b, *c, *d, *e, *f, *g],
+            GraphType::Leapfrog7_8(a, b, c, d, e, f, g) => vec![*a, *b, *c, *d, *e, *f, *g],
+            GraphType::Leapfrog8_1(a, b, c, d, e, f, g, h) => vec![*a, *b, *c, *d, *e, *f, *g, *h],
+            GraphType::Leapfrog8_2(a, b, c, d, e, f, g, h) => vec![*a, *b, *c, *d, *e, *f, *g, *h],
+            GraphType::Leapfrog8_3(a, b, c, d, e, f, g, h) => vec![*a, *b, *c, *d, *e, *f, *g, *h],
+            GraphType::Leapfrog8_4(a, b, c, d, e, f, g, h) => vec![*a, *b, *c, *d, *e, *f, *g, *h],
+            GraphType::Leapfrog8_5(a, b, c, d, e, f, g, h) => vec![*a, *b, *c, *d, *e, *f, *g, *h],
+            GraphType::Leapfrog8_6(a, b, c, d, e, f, g, h) => vec![*a, *b, *c, *d, *e, *f, *g, *h],
+            GraphType::Leapfrog8_7(a, b, c, d, e, f, g, h) => vec![*a, *b, *c, *d, *e, *f, *g, *h],
+            GraphType::Leapfrog8_8(a, b, c, d, e, f, g, h) => vec![*a, *b, *c, *d, *e, *f, *g, *h],
+            GraphType::Leapfrog8_9(a, b, c, d, e, f, g, h) => vec![*a, *b, *c, *d, *e, *f, *g, *h],
+            GraphType::Leapfrog8_10(a, b, c, d, e, f, g, h) => vec![*a, *b, *c, *d, *e, *f, *g, *h],
+            GraphType::Leapfrog8_11(a, b, c, d, e, f, g, h) => vec![*a, *b, *c, *d, *e, *f, *g, *h],
+            GraphType::Leapfrog8_12(a, b, c, d, e, f, g, h) => vec![*a, *b, *c, *d, *e, *f, *g, *h],
+            GraphType::Leapfrog8_13(a, b, c, d, e, f, g, h) => vec![*a, *b, *c, *d, *e, *f, *g, *h],
+            GraphType::Leapfrog8_14(a, b, c, d, e, f, g, h) => vec![*a, *b, *c, *d, *e, *f, *g, *h],
+            GraphType::Leapfrog8_15(a, b, c, d, e, f, g, h) => vec![*a, *b, *c, *d, *e, *f, *g, *h],
+            GraphType::Leapfrog8_16(a, b, c, d, e, f, g, h) => vec![*a, *b, *c, *d, *e, *f, *g, *h],
+        }
+    }
+
+    /// Number of parameters of the variant with the given tag
+    pub fn param_count(tag: u8) -> Option<usize> {
+        match tag {
+            0..=2 => Some(2),
+            3..=6 => Some(4),
+            7..=10 => Some(5),
+            11..=18 => Some(7),
+            19..=34 => Some(8),
+            _ => None,
+        }
+    }
+
+    /// Build the variant with the given tag from its parameters
+    ///
+    /// Returns `None` for unknown tags or a wrong number of parameters.
+    pub fn from_params(tag: u8, params: &[usize]) -> Option<GraphType> {
+        match (tag, params) {
+            (0, &[a, b]) => Some(GraphType::Adder(a, b)),
+            (1, &[a, b]) => Some(GraphType::Subtractor(a, b)),
+            (2, &[a, b]) => Some(GraphType::Cascade(a, b)),
+            (3, &[a, b, c, d]) => Some(GraphType::Leapfrog4_1(a, b, c, d)),
+            (4, &[a, b, c, d]) => Some(GraphType::Leapfrog4_2(a, b, c, d)),
+            (5, &[a, b, c, d]) => Some(GraphType::Leapfrog4_3(a, b, c, d)),
+            (6, &[a, b, c, d]) => Some(GraphType::Leapfrog4_4(a, b, c, d)),
+            (7, &[a, b, c, d, e]) => Some(GraphType::Leapfrog5_1(a, b, c, d, e)),
+            (8, &[a, b, c, d, e]) => Some(GraphType::Leapfrog5_2(a, b, c, d, e)),
+            (9, &[a, b, c, d, e]) => Some(GraphType::Leapfrog5_3(a, b, c, d, e)),
+            (10, &[a, b, c, d, e]) => Some(GraphType::Leapfrog5_4(a, b, c, d, e)),
+            (11, &[a, b, c, d, e, f, g]) => Some(GraphType::Leapfrog7_1(a, b, c, d, e, f, g)),
+            (12, &[a, b, c, d, e, f, g]) => Some(GraphType::Leapfrog7_2(a, b, c, d, e, f, g)),
+            (13, &[a, b, c, d, e, f, g]) => Some(GraphType::Leapfrog7_3(a, b, c, d, e, f, g)),
+            (14, &[a, b, c, d, e, f, g]) => Some(GraphType::Leapfrog7_4(a, b, c, d, e, f, g)),
+            (15, &[a, b, c, d, e, f, g]) => Some(GraphType::Leapfrog7_5(a, b, c, d, e, f, g)),
+            (16, &[a, b, c, d, e, f, g]) => Some(GraphType::Leapfrog7_6(a, b, c, d, e, f, g)),
+            (17, &[a, b, c, d, e, f, g]) => Some(GraphType::Leapfrog7_7(a, b, c, d, e, f, g)),
+            (18, &[a, b, c, d, e, f, g]) => Some(GraphType::Leapfrog7_8(a, b, c, d, e, f, g)),
+            (19, &[a, b, c, d, e, f, g, h]) => Some(GraphType::Leapfrog8_1(a, b, c, d, e, f, g, h)),
+            (20, &[a, b, c, d, e, f, g, h]) => Some(GraphType::Leapfrog8_2(a, b, c, d, e, f, g, h)),
+            (21, &[a, b, c, d, e, f, g, h]) => Some(GraphType::Leapfrog8_3(a, b, c, d, e, f, g, h)),
+            (22, &[a, b, c, d, e, f, g, h]) => Some(GraphType::Leapfrog8_4(a, b, c, d, e, f, g, h)),
+            (23, &[a, b, c, d, e, f, g, h]) => Some(GraphType::Leapfrog8_5(a, b, c, d, e, f, g, h)),
+            (24, &[a, b, c, d, e, f, g, h]) => Some(GraphType::Leapfrog8_6(a, b, c, d, e, f, g, h)),
+            (25, &[a, b, c, d, e, f, g, h]) => Some(GraphType::Leapfrog8_7(a, b, c, d, e, f, g, h)),
+            (26, &[a, b, c, d, e, f, g, h]) => Some(GraphType::Leapfrog8_8(a, b, c, d, e, f, g, h)),
+            (27, &[a, b, c, d, e, f, g, h]) => Some(GraphType::Leapfrog8_9(a, b, c, d, e, f, g, h)),
+            (28, &[a, b, c, d, e, f, g, h]) => {
+                Some(GraphType::Leapfrog8_10(a, b, c, d, e, f, g, h))
+            }
+            (29, &[a, b, c, d, e, f, g, h]) => {
+                Some(GraphType::Leapfrog8_11(a, b, c, d, e, f, g, h))
+            }
+            (30, &[a, b, c, d, e, f, g, h]) => {
+                Some(GraphType::Leapfrog8_12(a, b, c, d, e, f, g, h))
+            }
+            (31, &[a, b, c, d, e, f, g, h]) => {
+                Some(GraphType::Leapfrog8_13(a, b, c, d, e, f, g, h))
+            }
+            (32, &[a, b, c, d, e, f, g, h]) => {
+                Some(GraphType::Leapfrog8_14(a, b, c, d, e, f, g, h))
+            }
+            (33, &[a, b, c, d, e, f, g, h]) => {
+                Some(GraphType::Leapfrog8_15(a, b, c, d, e, f, g, h))
+            }
+            (34, &[a, b, c, d, e, f, g, h]) => {
+                Some(GraphType::Leapfrog8_16(a, b, c, d, e, f, g, h))
+            }
+            _ => None,
+        }
+    }
+
+    /// Mirrored structures, which may compute the same value with the same number of adders
+    ///
+    /// The leapfrog structures are symmetric in the roles of t1 and t5, and of
+    /// t2 and t4, for example `t5 * (t1 + t2) + t1 * t4 == t1 * (t5 + t4) + t5 * t2`.
+    /// Absolute differences may move between the two adders when mirrored, so
+    /// all variants of the family with reversed parameters are returned.
+    /// Cascades can be built in either order.
+    ///
+    /// The seven- and eight-term leapfrogs have no such symmetry, as each adder
+    /// uses the result of the one before it, so nothing is returned for them.
+    /// The generator stores only one structure of each group related this way,
+    /// see `GraphTypeExt::equivalents` in the library.
+    pub fn mirrored(&self) -> Vec<GraphType> {
+        let params = self.params();
+        let (tags, mirrored) = match self.tag() {
+            2 => (2..=2, vec![params[1], params[0]]),
+            3..=6 => (3..=6, params.iter().rev().copied().collect()),
+            7..=10 => (7..=10, params.iter().rev().copied().collect()),
+            _ => return Vec::new(),
+        };
+        tags.filter_map(|tag| GraphType::from_params(tag, &mirrored))
+            .filter(|mirrored| mirrored != self)
+            .collect()
+    }
+
+    /// Odd integer computed by the structure
+    ///
+    /// Returns `None` if an intermediate value overflows.
+    pub fn value(&self) -> Option<u64> {
+        let value = self.evaluate(&mut ValueEvaluator).ok()?;
+        if value == 0 {
+            return Some(0);
+        }
+        u64::try_from(value >> value.trailing_zeros()).ok()
+    }
+
+    /// Evaluate the expression of the structure with the given operations
+    ///
+    /// The first parameter is evaluated once and shared by all terms using it,
+    /// as in the adder structure.
+    pub fn evaluate<E: Evaluator>(&self, e: &mut E) -> Result<E::Value, E::Error> {
+        let t = self.params();
+        match self.tag() {
+            0 => {
+                let (a, b) = (e.constant(t[0])?, e.constant(t[1])?);
+                e.add(&a, &b)
+            }
+            1 => {
+                let (a, b) = (e.constant(t[0])?, e.constant(t[1])?);
+                e.abs_diff(&a, &b)
+            }
+            2 => {
+                let a = e.constant(t[0])?;
+                e.multiply(&a, t[1])
+            }
+            // t5 * (t1 ± t2) ± t1 * t4
+            tag @ 3..=6 => {
+                let k = tag - 3;
+                let t1 = e.constant(t[0])?;
+                let t2 = e.constant(t[1])?;
+                let a = combine(e, &t1, &t2, k >= 2)?;
+                leapfrog_tail(e, &t1, &a, t[2], t[3], k % 2 == 1)
+            }
+            // t5 * (t1 * t3 ± t2) ± t1 * t4
+            tag @ 7..=10 => {
+                let k = tag - 7;
+                let t1 = e.constant(t[0])?;
+                let t13 = e.multiply(&t1, t[2])?;
+                let t2 = e.constant(t[1])?;
+                let a = combine(e, &t13, &t2, k >= 2)?;
+                leapfrog_tail(e, &t1, &a, t[3], t[4], k % 2 == 1)
+            }
+            // t7 * (t5 * (t1 * t3 ± t2) ± t1 * t4) ± t6 * (t1 * t3 ± t2)
+            tag @ 11..=18 => {
+                let t1 = e.constant(t[0])?;
+                leapfrog7(e, &t1, &t, tag - 11)
+            }
+            // Leapfrog7 ± t1 * t8
+            tag => {
+                let t1 = e.constant(t[0])?;
+                let r = leapfrog7(e, &t1, &t, (tag - 19) % 8)?;
+                let t18 = e.multiply(&t1, t[7])?;
+                combine(e, &r, &t18, tag >= 27)
+            }
+        }
+    }
+}
+
+/// Operations to evaluate the expression of a structure, see [`GraphType::evaluate`]
+///
+/// Values are multiples of the input, and parameters shifted constants.
+pub trait Evaluator {
+    type Value;
+    type Error;
+
+    /// The input multiplied by the constant t
+    fn constant(&mut self, t: usize) -> Result<Self::Value, Self::Error>;
+
+    /// A value multiplied by the constant t
+    fn multiply(&mut self, value: &Self::Value, t: usize) -> Result<Self::Value, Self::Error>;
+
+    /// The sum of two values
+    fn add(&mut self, a: &Self::Value, b: &Self::Value) -> Result<Self::Value, Self::Error>;
+
+    /// The absolute difference of two values
+    fn abs_diff(&mut self, a: &Self::Value, b: &Self::Value) -> Result<Self::Value, Self::Error>;
+}
+
+/// Evaluates the expression of a structure on integers
+struct ValueEvaluator;
+
+impl Evaluator for ValueEvaluator {
+    type Value = u128;
+    type Error = ();
+
+    fn constant(&mut self, t: usize) -> Result<u128, ()> {
+        Ok(t as u128)
+    }
+
+    fn multiply(&mut self, value: &u128, t: usize) -> Result<u128, ()> {
+        value.checked_mul(t as u128).ok_or(())
+    }
+
+    fn add(&mut self, a: &u128, b: &u128) -> Result<u128, ()> {
+        a.checked_add(*b).ok_or(())
+    }
+
+    fn abs_diff(&mut self, a: &u128, b: &u128) -> Result<u128, ()> {
+        Ok(a.abs_diff(*b))
+    }
+}
+
+/// Sum or absolute difference of two values
+fn combine<E: Evaluator>(
+    e: &mut E,
+    a: &E::Value,
+    b: &E::Value,
+    diff: bool,
+) -> Result<E::Value, E::Error> {
+    if diff { e.abs_diff(a, b) } else { e.add(a, b) }
+}
+
+/// `t5 * a ± t1 * t4`, the final adder of the four- and five-adder leapfrogs
+fn leapfrog_tail<E: Evaluator>(
+    e: &mut E,
+    t1: &E::Value,
+    a: &E::Value,
+    t4: usize,
+    t5: usize,
+    diff: bool,
+) -> Result<E::Value, E::Error> {
+    let a5 = e.multiply(a, t5)?;
+    let t14 = e.multiply(t1, t4)?;
+    combine(e, &a5, &t14, diff)
+}
+
+/// The seven-parameter leapfrog with variant index k (0 for Leapfrog7_1)
+fn leapfrog7<E: Evaluator>(
+    e: &mut E,
+    t1: &E::Value,
+    t: &[usize],
+    k: u8,
+) -> Result<E::Value, E::Error> {
+    // Which of the three adders are absolute differences, in variant order
+    const DIFFS: [(bool, bool, bool); 8] = [
+        (false, false, false),
+        (true, false, false),
+        (false, true, false),
+        (false, false, true),
+        (true, true, false),
+        (true, false, true),
+        (false, true, true),
+        (true, true, true),
+    ];
+    let (a_diff, b_diff, c_diff) = DIFFS[k as usize];
+
+    let t13 = e.multiply(t1, t[2])?;
+    let t2 = e.constant(t[1])?;
+    let a = combine(e, &t13, &t2, a_diff)?;
+    let b = leapfrog_tail(e, t1, &a, t[3], t[4], b_diff)?;
+    let b7 = e.multiply(&b, t[6])?;
+    let a6 = e.multiply(&a, t[5])?;
+    combine(e, &b7, &a6, c_diff)
+}
+
+/// Read a little-endian u16
+const fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+/// Read a little-endian u32
+const fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ])
+}
+
+/// Read a little-endian u64
+pub const fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+        data[offset + 4],
+        data[offset + 5],
+        data[offset + 6],
+        data[offset + 7],
+    ])
+}
+
+/// Read a little-endian u64 counting or locating data, which must fit in usize
+const fn read_usize(data: &[u8], offset: usize) -> Result<usize, FormatError> {
+    let value = read_u64(data, offset);
+    if value > usize::MAX as u64 {
+        return Err(FormatError::InvalidIndex("value does not fit in usize"));
+    }
+    Ok(value as usize)
+}
+
+/// Header at the start of all table files
+///
+/// Layout (integers little-endian):
+/// - 4 bytes magic number, [`ADDER_COST_MAGIC`], [`DEPTH_COST_MAGIC`] or [`GRAPH_TYPES_MAGIC`]
+/// - u16 format version
+/// - u8 number of bits of the largest integer in the table
+/// - u8 number of extra bits allowed for intermediate values when generating
+/// - u16 variant set version
+/// - 2 reserved bytes, zero
+/// - u32 CRC-32 of the rest of the file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TableHeader {
+    pub format_version: u16,
+    pub max_bits: u8,
+    pub max_extra_bits: u8,
+    pub variant_set_version: u16,
+    pub checksum: u32,
+}
+
+impl TableHeader {
+    /// Header for a table generated with the given number of bits, using the current versions
+    ///
+    /// The checksum is filled in when encoding.
+    pub const fn new(max_bits: u8, max_extra_bits: u8) -> Self {
+        TableHeader {
+            format_version: FORMAT_VERSION,
+            max_bits,
+            max_extra_bits,
+            variant_set_version: VARIANT_SET_VERSION,
+            checksum: 0,
+        }
+    }
+
+    /// Number of odd integers in a table with this many bits
+    pub const fn count(&self) -> usize {
+        if self.max_bits == 0 {
+            0
+        } else {
+            1 << (self.max_bits - 1)
+        }
+    }
+
+    fn encode(&self, magic: [u8; 4], payload: &[u8]) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(HEADER_SIZE + payload.len());
+        buf.extend_from_slice(&magic);
+        buf.extend_from_slice(&self.format_version.to_le_bytes());
+        buf.push(self.max_bits);
+        buf.push(self.max_extra_bits);
+        buf.extend_from_slice(&self.variant_set_version.to_le_bytes());
+        buf.extend_from_slice(&[0; 2]);
+        buf.extend_from_slice(&crc32fast::hash(payload).to_le_bytes());
+        buf.extend_from_slice(payload);
+        buf
+    }
+
+    /// Parse and check the header, except for the checksum which needs the whole file
+    const fn parse(data: &[u8], magic: [u8; 4]) -> Result<Self, FormatError> {
+        if data.len() < HEADER_SIZE {
+            return Err(FormatError::UnexpectedEnd);
+        }
+        let mut i = 0;
+        while i < magic.len() {
+            if data[i] != magic[i] {
+                return Err(FormatError::BadMagic);
+            }
+            i += 1;
+        }
+        let header = TableHeader {
+            format_version: read_u16(data, 4),
+            max_bits: data[6],
+            max_extra_bits: data[7],
+            variant_set_version: read_u16(data, 8),
+            checksum: read_u32(data, 12),
+        };
+        if header.format_version != FORMAT_VERSION {
+            return Err(FormatError::UnsupportedFormatVersion(header.format_version));
+        }
+        if header.variant_set_version > VARIANT_SET_VERSION {
+            return Err(FormatError::UnsupportedVariantSet(
+                header.variant_set_version,
+            ));
+        }
+        if header.max_bits as u32 >= usize::BITS {
+            return Err(FormatError::InvalidIndex("too many bits"));
+        }
+        Ok(header)
+    }
+
+    /// Check the checksum against the data following the header
+    fn verify_checksum(&self, data: &[u8]) -> Result<(), FormatError> {
+        let actual = crc32fast::hash(&data[HEADER_SIZE..]);
+        if actual != self.checksum {
+            return Err(FormatError::ChecksumMismatch {
+                expected: self.checksum,
+                actual,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Pack the adder costs of the odd indices into 3-bit values
+///
+/// Returns the packed data and the number of odd values.
+pub fn pack_adder_costs(adder_count: &[u8]) -> (Vec<u8>, usize) {
+    let odd_count = adder_count.len() / 2;
+    let mut packed = Vec::new();
+    let mut bit_buffer = 0u32;
+    let mut bits_in_buffer = 0;
+
+    // Extract and pack values at odd indices
+    for i in (1..adder_count.len()).step_by(2) {
+        let val = adder_count[i] & 0b111; // Ensure only 3 bits
+        bit_buffer |= (val as u32) << bits_in_buffer;
+        bits_in_buffer += 3;
+
+        // Flush complete bytes
+        while bits_in_buffer >= 8 {
+            packed.push(bit_buffer as u8);
+            bit_buffer >>= 8;
+            bits_in_buffer -= 8;
+        }
+    }
+
+    // Flush remaining bits
+    if bits_in_buffer > 0 {
+        packed.push(bit_buffer as u8);
+    }
+
+    (packed, odd_count)
+}
+
+/// Encode `adder_cost.bin` from the adder cost of every integer
+pub fn encode_adder_costs(adder_count: &[u8], header: TableHeader) -> Vec<u8> {
+    let (packed, count) = pack_adder_costs(adder_count);
+    let mut buf = Vec::with_capacity(8 + packed.len());
+    // Write count as u64 little-endian
+    buf.extend_from_slice(&(count as u64).to_le_bytes());
+    // Write packed data
+    buf.extend_from_slice(&packed);
+    header.encode(ADDER_COST_MAGIC, &buf)
+}
+
+/// View of an encoded `adder_cost.bin`
+#[derive(Debug, Clone, Copy)]
+pub struct AdderCostFile<'a> {
+    data: &'a [u8],
+    header: TableHeader,
+    packed: &'a [u8],
+    count: usize,
+}
+
+impl<'a> AdderCostFile<'a> {
+    /// Parse the header and check that the packed data holds all values
+    ///
+    /// The checksum is not verified, see [`AdderCostFile::verify_checksum`].
+    pub const fn parse(data: &'a [u8]) -> Result<Self, FormatError> {
+        let header = match TableHeader::parse(data, ADDER_COST_MAGIC) {
+            Ok(header) => header,
+            Err(e) => return Err(e),
+        };
+        if data.len() < ADDER_COST_HEADER_SIZE {
+            return Err(FormatError::UnexpectedEnd);
+        }
+        let count = match read_usize(data, HEADER_SIZE) {
+            Ok(count) => count,
+            Err(e) => return Err(e),
+        };
+        if count != header.count() {
+            return Err(FormatError::InvalidIndex(
+                "count does not match the bit width",
+            ));
+        }
+        let (_, packed) = data.split_at(ADDER_COST_HEADER_SIZE);
+        if packed.len() < (count * 3).div_ceil(8) {
+            return Err(FormatError::InvalidIndex("too few packed adder costs"));
+        }
+        Ok(AdderCostFile {
+            data,
+            header,
+            packed,
+            count,
+        })
+    }
+
+    /// Header of the file
+    pub const fn header(&self) -> TableHeader {
+        self.header
+    }
+
+    /// Check the checksum in the header against the data
+    pub fn verify_checksum(&self) -> Result<(), FormatError> {
+        self.header.verify_checksum(self.data)
+    }
+
+    /// Number of odd values
+    pub const fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Size of the packed values in bytes
+    pub const fn packed_size(&self) -> usize {
+        self.packed.len()
+    }
+
+    /// Adder cost of the odd value at position, i.e., of the integer `2 * position + 1`
+    pub const fn get(&self, position: usize) -> Option<u8> {
+        if position >= self.count {
+            return None;
+        }
+        Some(unpack_adder_cost(self.packed, position))
+    }
+}
+
+/// 3-bit value at position in adder costs packed by [`pack_adder_costs`]
+const fn unpack_adder_cost(packed: &[u8], position: usize) -> u8 {
+    let bit_offset = position * 3;
+    let byte_offset = bit_offset / 8;
+    let bit_in_byte = bit_offset % 8;
+
+    let mut val = (packed[byte_offset] >> bit_in_byte) & 0b111;
+
+    // Handle values that span two bytes
+    if bit_in_byte > 5 && byte_offset + 1 < packed.len() {
+        let bits_from_next = 3 - (8 - bit_in_byte);
+        val |= (packed[byte_offset + 1] & ((1 << bits_from_next) - 1)) << (8 - bit_in_byte);
+    }
+
+    val & 0b111
+}
+
+/// Encode `depth_cost_bound.bin` from the adder cost of every integer for each depth bound
+///
+/// `costs[d - 1]` holds the costs when the depth is at most d, with
+/// [`UNKNOWN_COST`] where no structure is known.
+///
+/// Layout after the [`TableHeader`]:
+/// - u64 little-endian number of odd values
+/// - u64 little-endian number of depth bounds
+/// - for each bound from 1, the costs packed by [`pack_adder_costs`]
+pub fn encode_depth_costs(costs: &[Vec<u8>], header: TableHeader) -> Vec<u8> {
+    let count = header.count();
+    let mut buf = Vec::new();
+    buf.extend_from_slice(&(count as u64).to_le_bytes());
+    buf.extend_from_slice(&(costs.len() as u64).to_le_bytes());
+    for bound_costs in costs {
+        let (packed, bound_count) = pack_adder_costs(bound_costs);
+        assert_eq!(bound_count, count, "costs must match the bit width");
+        buf.extend_from_slice(&packed);
+    }
+    header.encode(DEPTH_COST_MAGIC, &buf)
+}
+
+/// View of an encoded `depth_cost_bound.bin`
+#[derive(Debug, Clone, Copy)]
+pub struct DepthCostFile<'a> {
+    data: &'a [u8],
+    header: TableHeader,
+    count: usize,
+    max_depth: usize,
+}
+
+impl<'a> DepthCostFile<'a> {
+    /// Parse the header and check that the packed data holds all tables
+    ///
+    /// The checksum is not verified, see [`DepthCostFile::verify_checksum`].
+    pub const fn parse(data: &'a [u8]) -> Result<Self, FormatError> {
+        let header = match TableHeader::parse(data, DEPTH_COST_MAGIC) {
+            Ok(header) => header,
+            Err(e) => return Err(e),
+        };
+        if data.len() < DEPTH_COST_HEADER_SIZE {
+            return Err(FormatError::UnexpectedEnd);
+        }
+        let count = match read_usize(data, HEADER_SIZE) {
+            Ok(count) => count,
+            Err(e) => return Err(e),
+        };
+        let max_depth = match read_usize(data, HEADER_SIZE + 8) {
+            Ok(max_depth) => max_depth,
+            Err(e) => return Err(e),
+        };
+        if count != header.count() {
+            return Err(FormatError::InvalidIndex(
+                "count does not match the bit width",
+            ));
+        }
+        let table_size = (count * 3).div_ceil(8);
+        match max_depth.checked_mul(table_size) {
+            Some(size) if size == data.len() - DEPTH_COST_HEADER_SIZE => {}
+            _ => {
+                return Err(FormatError::InvalidIndex(
+                    "size does not match the number of depth bounds",
+                ));
+            }
+        }
+        Ok(DepthCostFile {
+            data,
+            header,
+            count,
+            max_depth,
+        })
+    }
+
+    /// Header of the file
+    pub const fn header(&self) -> TableHeader {
+        self.header
+    }
+
+    /// Check the checksum in the header against the data
+    pub fn verify_checksum(&self) -> Result<(), FormatError> {
+        self.header.verify_checksum(self.data)
+    }
+
+    /// Number of odd values
+    pub const fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Largest depth bound with a table
+    pub const fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Adder cost of the odd value at position with depth at most `depth`, from 1
+    /// to [`DepthCostFile::max_depth`]
+    pub const fn get(&self, depth: usize, position: usize) -> Option<u8> {
+        if position >= self.count || depth == 0 || depth > self.max_depth {
+            return None;
+        }
+        let table_size = (self.count * 3).div_ceil(8);
+        let (_, packed) = self
+            .data
+            .split_at(DEPTH_COST_HEADER_SIZE + (depth - 1) * table_size);
+        Some(unpack_adder_cost(packed, position))
+    }
+}
+
+fn encode_usize(buf: &mut Vec<u8>, value: usize) {
+    let mut value_buf = varint_encode::usize_buffer();
+    buf.extend_from_slice(varint_encode::usize(value, &mut value_buf));
+}
+
+fn decode_usize(remaining: &mut &[u8]) -> Result<usize, FormatError> {
+    let (value, rest) = varint_decode::usize(remaining).map_err(FormatError::Varint)?;
+    *remaining = rest;
+    Ok(value)
+}
+
+/// Serialize graph types with varint encoding
+///
+/// The number of entries is followed by, for each entry, the number of graph
+/// types and then the tag and parameters of each graph type.
+pub fn serialize_graph_types(types: &[Vec<GraphType>]) -> Vec<u8> {
+    let mut buf = Vec::new();
+
+    // Write number of entries (varint)
+    encode_usize(&mut buf, types.len());
+
+    for type_vec in types {
+        // Write length of this Vec (varint)
+        encode_usize(&mut buf, type_vec.len());
+
+        for gt in type_vec {
+            buf.push(gt.tag());
+            for param in gt.params() {
+                encode_usize(&mut buf, param);
+            }
+        }
+    }
+
+    buf
+}
+
+/// Deserialize graph types written by [`serialize_graph_types`]
+pub fn deserialize_graph_types(data: &[u8]) -> Result<Vec<Vec<GraphType>>, FormatError> {
+    let mut remaining = data;
+
+    // Read number of entries
+    let count = decode_usize(&mut remaining)?;
+    let mut result = Vec::with_capacity(count);
+
+    for _ in 0..count {
+        // Read length of this Vec
+        let vec_len = decode_usize(&mut remaining)?;
+        let mut type_vec = Vec::with_capacity(vec_len);
+
+        for _ in 0..vec_len {
+            let (&variant_tag, rest) = remaining.split_first().ok_or(FormatError::UnexpectedEnd)?;
+            remaining = rest;
+
+            let param_count = GraphType::param_count(variant_tag)
+                .ok_or(FormatError::UnknownVariantTag(variant_tag))?;
+            let mut params = [0; 8];
+            for param in &mut params[..param_count] {
+                *param = decode_usize(&mut remaining)?;
+            }
+            let graph_type = GraphType::from_params(variant_tag, &params[..param_count])
+                .ok_or(FormatError::UnknownVariantTag(variant_tag))?;
+
+            type_vec.push(graph_type);
+        }
+
+        result.push(type_vec);
+    }
+
+    Ok(result)
+}
+
+/// Encode `graph_types.bin` as independently LZ4-compressed chunks with an offset index
+///
+/// Layout after the [`TableHeader`] (integers little-endian):
+/// - u64 number of odd coefficients
+/// - u64 number of odd coefficients per chunk
+/// - `chunk_count + 1` u64 byte offsets of the chunks, relative to the end of the index
+/// - `chunk_count` u32 CRC-32 of the compressed chunks
+/// - the compressed chunks, each a size-prepended LZ4 block of [`serialize_graph_types`]
+pub fn encode_graph_types(
+    types: &[Vec<GraphType>],
+    chunk_size: usize,
+    header: TableHeader,
+) -> Vec<u8> {
+    assert!(chunk_size > 0, "chunk size must be positive");
+    let mut offsets: Vec<u64> = vec![0];
+    let mut checksums = Vec::new();
+    let mut chunks = Vec::new();
+
+    for chunk in types.chunks(chunk_size) {
+        // Serialize with varint encoding and compress with lz4
+        let compressed = lz4_flex::compress_prepend_size(&serialize_graph_types(chunk));
+        checksums.push(crc32fast::hash(&compressed));
+        chunks.extend_from_slice(&compressed);
+        offsets.push(chunks.len() as u64);
+    }
+
+    let mut buf = Vec::with_capacity(16 + 8 * offsets.len() + 4 * checksums.len() + chunks.len());
+    buf.extend_from_slice(&(types.len() as u64).to_le_bytes());
+    buf.extend_from_slice(&(chunk_size as u64).to_le_bytes());
+    for offset in offsets {
+        buf.extend_from_slice(&offset.to_le_bytes());
+    }
+    for checksum in checksums {
+        buf.extend_from_slice(&checksum.to_le_bytes());
+    }
+    buf.extend_from_slice(&chunks);
+    header.encode(GRAPH_TYPES_MAGIC, &buf)
+}
+
+/// View of an encoded `graph_types.bin`
+#[derive(Debug, Clone, Copy)]
+pub struct GraphTypesFile<'a> {
+    data: &'a [u8],
+    header: TableHeader,
+    count: usize,
+    chunk_size: usize,
+    chunk_count: usize,
+}
+
+impl<'a> GraphTypesFile<'a> {
+    /// Parse the header and check that the chunk index matches the data
+    ///
+    /// The checksum of the file is not verified, see
+    /// [`GraphTypesFile::verify_checksum`]. Chunks are verified when decoded.
+    pub const fn parse(data: &'a [u8]) -> Result<Self, FormatError> {
+        let header = match TableHeader::parse(data, GRAPH_TYPES_MAGIC) {
+            Ok(header) => header,
+            Err(e) => return Err(e),
+        };
+        if data.len() < GRAPH_TYPES_INDEX_OFFSET {
+            return Err(FormatError::UnexpectedEnd);
+        }
+        let count = match read_usize(data, HEADER_SIZE) {
+            Ok(count) => count,
+            Err(e) => return Err(e),
+        };
+        let chunk_size = match read_usize(data, HEADER_SIZE + 8) {
+            Ok(chunk_size) => chunk_size,
+            Err(e) => return Err(e),
+        };
+        if count != header.count() {
+            return Err(FormatError::InvalidIndex(
+                "count does not match the bit width",
+            ));
+        }
+        if chunk_size == 0 {
+            return Err(FormatError::InvalidIndex("chunk size is zero"));
+        }
+        let chunk_count = count.div_ceil(chunk_size);
+        let file = GraphTypesFile {
+            data,
+            header,
+            count,
+            chunk_size,
+            chunk_count,
+        };
+        let data_offset = file.data_offset();
+        if data.len() < data_offset {
+            return Err(FormatError::UnexpectedEnd);
+        }
+        let data_size = match read_usize(data, file.checksum_offset() - 8) {
+            Ok(data_size) => data_size,
+            Err(e) => return Err(e),
+        };
+        match data_offset.checked_add(data_size) {
+            Some(size) if size == data.len() => {}
+            _ => {
+                return Err(FormatError::InvalidIndex(
+                    "size does not match the chunk index",
+                ));
+            }
+        }
+        Ok(file)
+    }
+
+    /// Number of odd values
+    pub const fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Header of the file
+    pub const fn header(&self) -> TableHeader {
+        self.header
+    }
+
+    /// Check the checksum in the header against the data
+    pub fn verify_checksum(&self) -> Result<(), FormatError> {
+        self.header.verify_checksum(self.data)
+    }
+
+    /// Number of odd values per chunk
+    pub const fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Number of chunks
+    pub const fn chunk_count(&self) -> usize {
+        self.chunk_count
+    }
+
+    /// Size of the compressed chunks in bytes
+    pub const fn compressed_size(&self) -> usize {
+        self.data.len() - self.data_offset()
+    }
+
+    const fn checksum_offset(&self) -> usize {
+        GRAPH_TYPES_INDEX_OFFSET + 8 * (self.chunk_count + 1)
+    }
+
+    const fn data_offset(&self) -> usize {
+        self.checksum_offset() + 4 * self.chunk_count
+    }
+
+    /// Check the checksum of one chunk, then decompress and deserialize it
+    pub fn decode_chunk(&self, chunk_idx: usize) -> Result<Vec<Vec<GraphType>>, FormatError> {
+        if chunk_idx >= self.chunk_count {
+            return Err(FormatError::InvalidIndex("chunk out of range"));
+        }
+
+        // Look up the compressed chunk in the index
+        let start = read_usize(self.data, GRAPH_TYPES_INDEX_OFFSET + 8 * chunk_idx)?;
+        let end = read_usize(self.data, GRAPH_TYPES_INDEX_OFFSET + 8 * (chunk_idx + 1))?;
+        let data_offset = self.data_offset();
+        let compressed = data_offset
+            .checked_add(start)
+            .zip(data_offset.checked_add(end))
+            .and_then(|(start, end)| self.data.get(start..end))
+            .ok_or(FormatError::InvalidIndex("chunk outside of data"))?;
+
+        // Only this chunk is checked, so a lookup does not read the whole file
+        let expected = read_u32(self.data, self.checksum_offset() + 4 * chunk_idx);
+        let actual = crc32fast::hash(compressed);
+        if actual != expected {
+            return Err(FormatError::ChecksumMismatch { expected, actual });
+        }
+
+        // Decompress the LZ4 data
+        let decompressed =
+            lz4_flex::decompress_size_prepended(compressed).map_err(FormatError::Decompress)?;
+
+        // Deserialize with varint decoding
+        let types = deserialize_graph_types(&decompressed)?;
+        let expected = self
+            .chunk_size
+            .min(self.count - chunk_idx * self.chunk_size);
+        if types.len() != expected {
+            return Err(FormatError::InvalidIndex(
+                "chunk has the wrong number of entries",
+            ));
+        }
+        Ok(types)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One graph type of every variant, with parameters spanning several varint bytes
+    fn all_variants() -> Vec<GraphType> {
+        (0..=u8::MAX)
+            .filter_map(|tag| {
+                let count = GraphType::param_count(tag)?;
+                let params: Vec<usize> = (0..count).map(|i| (i + 1) << (7 * i)).collect();
+                GraphType::from_params(tag, &params)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn every_tag_round_trips() {
+        let variants = all_variants();
+        assert_eq!(variants.len(), 35);
+        for (tag, gt) in variants.iter().enumerate() {
+            assert_eq!(gt.tag() as usize, tag);
+            assert_eq!(
+                GraphType::from_params(gt.tag(), &gt.params()).as_ref(),
+                Some(gt)
+            );
+        }
+        assert!(GraphType::param_count(35).is_none());
+        assert!(GraphType::from_params(0, &[1, 2, 3]).is_none());
+    }
+
+    #[test]
+    fn values_follow_the_generator_expressions() {
+        let (t1, t2, t3, t4, t5, t6, t7, t8) = (3u64, 1, 5, 2, 7, 9, 11, 4);
+        let odd = |v: u64| v >> v.trailing_zeros();
+        let cases = [
+            (GraphType::Adder(3, 8), 11),
+            (GraphType::Subtractor(524288, 1), 524287),
+            (GraphType::Cascade(3, 15), 45),
+            (GraphType::Leapfrog4_1(3, 8, 64, 1025), 11467),
+            (
+                GraphType::Leapfrog4_2(t1 as usize, 8, t4 as usize, t5 as usize),
+                odd((t5 * (t1 + 8)).abs_diff(t1 * t4)),
+            ),
+            (
+                GraphType::Leapfrog5_4(
+                    t1 as usize,
+                    t2 as usize,
+                    t3 as usize,
+                    t4 as usize,
+                    t5 as usize,
+                ),
+                odd((t5 * (t1 * t3).abs_diff(t2)).abs_diff(t1 * t4)),
+            ),
+            (
+                GraphType::Leapfrog7_8(3, 1, 5, 2, 7, 9, 11),
+                odd((t7 * (t5 * ((t1 * t3).abs_diff(t2))).abs_diff(t1 * t4))
+                    .abs_diff(t6 * ((t1 * t3).abs_diff(t2)))),
+            ),
+            (
+                GraphType::Leapfrog8_4(3, 1, 5, 2, 7, 9, 11, 4),
+                odd((t7 * (t5 * (t1 * t3 + t2) + t1 * t4)).abs_diff(t6 * (t1 * t3 + t2)) + t1 * t8),
+            ),
+            (
+                GraphType::Leapfrog8_11(3, 1, 5, 2, 7, 9, 11, 4),
+                odd(
+                    (t7 * ((t5 * (t1 * t3 + t2)).abs_diff(t1 * t4)) + t6 * (t1 * t3 + t2))
+                        .abs_diff(t1 * t8),
+                ),
+            ),
+        ];
+        for (gt, value) in cases {
+            assert_eq!(gt.value(), Some(value), "{gt:?}");
+        }
+        assert_eq!(GraphType::Cascade(usize::MAX, usize::MAX).value(), None);
+    }
+
+    #[test]
+    fn mirrored_leapfrogs_compute_the_same_value() {
+        let leapfrog = GraphType::Leapfrog4_1(3, 8, 64, 1025);
+        let mirrored = leapfrog.mirrored();
+        assert_eq!(mirrored.len(), 4);
+        assert!(mirrored.contains(&GraphType::Leapfrog4_1(1025, 64, 8, 3)));
+        assert_eq!(
+            GraphType::Leapfrog4_1(1025, 64, 8, 3).value(),
+            leapfrog.value()
+        );
+        assert_eq!(
+            GraphType::Cascade(3, 15).mirrored(),
+            [GraphType::Cascade(15, 3)]
+        );
+        assert!(GraphType::Cascade(3, 3).mirrored().is_empty());
+        assert!(GraphType::Adder(1, 2).mirrored().is_empty());
+        for variant in all_variants() {
+            if variant.tag() >= 11 {
+                assert!(variant.mirrored().is_empty(), "{variant:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn graph_types_serialization_round_trips() {
+        let types = vec![all_variants(), vec![], vec![GraphType::Adder(1, 2)]];
+        let serialized = serialize_graph_types(&types);
+        assert_eq!(deserialize_graph_types(&serialized).unwrap(), types);
+    }
+
+    #[test]
+    fn unknown_tag_is_rejected() {
+        // One entry with one graph type with tag 35
+        assert!(matches!(
+            deserialize_graph_types(&[1, 1, 35, 1, 1]),
+            Err(FormatError::UnknownVariantTag(35))
+        ));
+        assert!(matches!(
+            deserialize_graph_types(&[1, 1]),
+            Err(FormatError::UnexpectedEnd)
+        ));
+    }
+
+    #[test]
+    fn graph_types_file_round_trips() {
+        let variants = all_variants();
+        let types: Vec<Vec<GraphType>> = (0..16)
+            .map(|i| variants[i % variants.len()..].to_vec())
+            .collect();
+        let encoded = encode_graph_types(&types, 5, TableHeader::new(5, 2));
+        let file = GraphTypesFile::parse(&encoded).unwrap();
+        file.verify_checksum().unwrap();
+        assert_eq!(file.header().max_bits, 5);
+        assert_eq!(file.header().max_extra_bits, 2);
+        assert_eq!(file.header().variant_set_version, VARIANT_SET_VERSION);
+        assert_eq!(file.count(), 16);
+        assert_eq!(file.chunk_size(), 5);
+        assert_eq!(file.chunk_count(), 4);
+
+        let decoded: Vec<Vec<GraphType>> = (0..file.chunk_count())
+            .flat_map(|chunk_idx| file.decode_chunk(chunk_idx).unwrap())
+            .collect();
+        assert_eq!(decoded, types);
+        assert!(file.decode_chunk(4).is_err());
+
+        // A corrupt chunk fails to decode, while the others still decode
+        let mut corrupt = encoded.clone();
+        *corrupt.last_mut().unwrap() ^= 1;
+        let corrupt_file = GraphTypesFile::parse(&corrupt).unwrap();
+        assert!(matches!(
+            corrupt_file.decode_chunk(3),
+            Err(FormatError::ChecksumMismatch { .. })
+        ));
+        assert_eq!(corrupt_file.decode_chunk(0).unwrap(), types[..5]);
+
+        assert!(GraphTypesFile::parse(&encoded[..encoded.len() - 1]).is_err());
+        // Offsets too large to add to the position of the data are rejected
+        let mut corrupt = encoded.clone();
+        let end = GRAPH_TYPES_INDEX_OFFSET + 8 * file.chunk_count();
+        corrupt[end..end + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(matches!(
+            GraphTypesFile::parse(&corrupt),
+            Err(FormatError::InvalidIndex(_))
+        ));
+        let mut corrupt = encoded.clone();
+        corrupt[end - 8..end].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(matches!(
+            GraphTypesFile::parse(&corrupt).unwrap().decode_chunk(3),
+            Err(FormatError::InvalidIndex(_))
+        ));
+        // The bit width must match the number of entries
+        let encoded = encode_graph_types(&types, 5, TableHeader::new(6, 2));
+        assert!(GraphTypesFile::parse(&encoded).is_err());
+    }
+
+    #[test]
+    fn adder_costs_round_trip() {
+        let adder_count: Vec<u8> = (0..1025).map(|i| (i * 7 % 8) as u8).collect();
+        let encoded = encode_adder_costs(&adder_count, TableHeader::new(10, 2));
+        let file = AdderCostFile::parse(&encoded).unwrap();
+        file.verify_checksum().unwrap();
+        assert_eq!(file.header(), {
+            let mut header = TableHeader::new(10, 2);
+            header.checksum = file.header().checksum;
+            header
+        });
+        assert_eq!(file.count(), 512);
+        for position in 0..file.count() {
+            assert_eq!(file.get(position), Some(adder_count[2 * position + 1]));
+        }
+        assert_eq!(file.get(512), None);
+
+        assert!(AdderCostFile::parse(&encoded[..encoded.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn depth_costs_round_trip() {
+        let costs: Vec<Vec<u8>> = (1..=3)
+            .map(|depth| (0..1025).map(|i| ((i + depth) * 5 % 8) as u8).collect())
+            .collect();
+        let encoded = encode_depth_costs(&costs, TableHeader::new(10, 2));
+        let file = DepthCostFile::parse(&encoded).unwrap();
+        file.verify_checksum().unwrap();
+        assert_eq!(file.count(), 512);
+        assert_eq!(file.max_depth(), 3);
+        for depth in 1..=3 {
+            for position in 0..file.count() {
+                assert_eq!(
+                    file.get(depth, position),
+                    Some(costs[depth - 1][2 * position + 1])
+                );
+            }
+        }
+        assert_eq!(file.get(0, 0), None);
+        assert_eq!(file.get(4, 0), None);
+        assert_eq!(file.get(1, 512), None);
+
+        assert!(DepthCostFile::parse(&encoded[..encoded.len() - 1]).is_err());
+        assert!(matches!(
+            AdderCostFile::parse(&encoded),
+            Err(FormatError::BadMagic)
+        ));
+    }
+
+    #[test]
+    fn invalid_headers_are_rejected() {
+        let adder_count = vec![1; 1025];
+        let encoded = encode_adder_costs(&adder_count, TableHeader::new(10, 2));
+
+        // Graph types and adder costs cannot be mixed up
+        assert!(matches!(
+            GraphTypesFile::parse(&encoded),
+            Err(FormatError::BadMagic)
+        ));
+
+        let mut newer = encoded.clone();
+        newer[4] = FORMAT_VERSION as u8 + 1;
+        assert!(matches!(
+            AdderCostFile::parse(&newer),
+            Err(FormatError::UnsupportedFormatVersion(_))
+        ));
+
+        let mut newer = encoded.clone();
+        newer[8] = VARIANT_SET_VERSION as u8 + 1;
+        assert!(matches!(
+            AdderCostFile::parse(&newer),
+            Err(FormatError::UnsupportedVariantSet(_))
+        ));
+
+        let mut corrupt = encoded.clone();
+        *corrupt.last_mut().unwrap() ^= 1;
+        let file = AdderCostFile::parse(&corrupt).unwrap();
+        assert!(matches!(
+            file.verify_checksum(),
+            Err(FormatError::ChecksumMismatch { .. })
+        ));
+    }
+}
@@ -1,12 +1,21 @@
+use constant_multiplication_format::{
+    Evaluator, GRAPH_TYPES_CHUNK_SIZE, GraphType, TableHeader, UNKNOWN_COST, encode_adder_costs,
+    encode_depth_costs, encode_graph_types, pack_adder_costs,
+};
+use std::collections::HashSet;
+use std::io;
+use std::path::Path;
 use std::{iter::zip, ops::Shr};
 use tracing::{Level, debug, info, warn};
-use unsigned_varint::encode as varint_encode;
 
 type AdderStructures = [Option<Vec<GraphType>>];
 
 const SAVE_GRAPH_TYPES: bool = true;
 
-fn main() -> Result<()> {
+/// Largest depth bound in `depth_cost_bound.bin`, no structure has more adders
+const MAX_DEPTH: usize = 6;
+
+fn main() -> io::Result<()> {
     // Initialize tracing subscriber
     tracing_subscriber::fmt()
         .with_max_level(Level::DEBUG)
@@ -16,156 +25,138 @@ fn main() -> Result<()> {
 
     let max_bits: usize = 19;
     let max_extra_bits: usize = 2;
-    let table_max: usize = 1 << max_bits;
-    let max_value: usize = 1 << (max_bits + max_extra_bits);
 
     let print_structures = false;
     let print_missing = true;
 
+    let generated = generate(max_bits, max_extra_bits);
+    write_tables(Path::new("constant-multiplication"), &generated)?;
+
+    debug!("Generating final results");
+    // Print results
+    let mut result_count = 0;
+    let mut missing_count = 0;
+    for c in zip(
+        generated.tables.adder_count.iter(),
+        generated.tables.adder_structures.iter(),
+    )
+    .enumerate()
+    {
+        if let Some(structures) = c.1.1 {
+            result_count += 1;
+            if print_structures {
+                println!(
+                    "Value: {}, Cost: {}, Structures: {:?}",
+                    c.0, c.1.0, structures
+                );
+            }
+        }
+    }
+
+    for c in generated.tables.adder_structures.iter().enumerate() {
+        if c.1.is_none() && c.0 % 2 == 1 && c.0 != 1 {
+            missing_count += 1;
+            if print_missing {
+                println!("Missing: {}", c.0);
+            }
+        }
+    }
+
+    info!(result_count, missing_count, "Computation complete");
+    Ok(())
+}
+
+/// Tables of all integers up to 2^max_bits, with their header
+struct Generated {
+    header: TableHeader,
+    tables: Tables,
+}
+
+/// Minimum adder costs, structures and depths found so far
+struct Tables {
+    adder_count: Vec<u8>,
+    adder_structures: Vec<Option<Vec<GraphType>>>,
+    depths: DepthTable,
+    // Set once the costs and structures are final, when repeating the passes
+    depths_only: bool,
+}
+
+/// Run all combination passes for integers up to 2^max_bits, with intermediate
+/// values of up to max_extra_bits more bits
+fn generate(max_bits: usize, max_extra_bits: usize) -> Generated {
+    let table_max: usize = 1 << max_bits;
+    let max_value: usize = 1 << (max_bits + max_extra_bits);
+
     info!(
         max_bits,
         max_extra_bits, table_max, max_value, "Configuration initialized"
     );
 
-    let mut adder_count: Vec<u8> = vec![7; table_max + 1];
-    let mut adder_structures: Vec<Option<Vec<GraphType>>> = vec![None; table_max + 1];
+    let mut adder_count: Vec<u8> = vec![UNKNOWN_COST; table_max + 1];
+    adder_count[1] = 0; // Cost 0 for constant 1
+    let mut tables = Tables {
+        adder_count,
+        adder_structures: vec![None; table_max + 1],
+        depths: DepthTable::new(table_max),
+        depths_only: false,
+    };
+    run_passes(&mut tables, max_bits, max_value);
+
+    // Terms may get shallower structures with more adders in later passes, so
+    // the passes are repeated until no depth improves
+    tables.depths_only = true;
+    loop {
+        tables.depths.changed = false;
+        run_passes(&mut tables, max_bits, max_value);
+        if !tables.depths.changed {
+            break;
+        }
+        debug!("Depths improved, repeating the passes");
+    }
+
+    Generated {
+        // Recorded in the header of all files
+        header: TableHeader::new(max_bits as u8, max_extra_bits as u8),
+        tables,
+    }
+}
+
+/// Combine the integers of each cost into the ones of the next cost
+fn run_passes(tables: &mut Tables, max_bits: usize, max_value: usize) {
+    let table_max = tables.adder_count.len() - 1;
     let cost0: Vec<usize> = vec![1];
     let cost0_shifted = create_shifted_variants(&cost0, max_value);
-    adder_count[1] = 0; // Cost 0 for constant 1
     // Cost 1 combinations
     debug!("Processing cost 1 combinations");
-    addsub_combinations(
-        &mut adder_count,
-        &mut adder_structures,
-        &cost0,
-        &cost0_shifted,
-        1,
-        table_max,
-    );
-    let cost1 = extract_cost_values(&adder_count, 1);
+    addsub_combinations(tables, &cost0, &cost0_shifted, 1, table_max);
+    let cost1 = extract_cost_values(&tables.adder_count, 1);
     debug!(cost1_count = cost1.len(), "Cost 1 values found");
     let cost1_shifted = create_shifted_variants(&cost1, max_value);
     debug!("Processing cost 2 combinations");
-    addsub_combinations(
-        &mut adder_count,
-        &mut adder_structures,
-        &cost1,
-        &cost0_shifted,
-        2,
-        table_max,
-    );
-    addsub_combinations(
-        &mut adder_count,
-        &mut adder_structures,
-        &cost0,
-        &cost1_shifted,
-        2,
-        table_max,
-    );
-    cascade_combinations(
-        &mut adder_count,
-        &mut adder_structures,
-        &cost1,
-        &cost1,
-        2,
-        table_max,
-        true,
-    );
-    let cost2 = extract_cost_values(&adder_count, 2);
+    addsub_combinations(tables, &cost1, &cost0_shifted, 2, table_max);
+    addsub_combinations(tables, &cost0, &cost1_shifted, 2, table_max);
+    cascade_combinations(tables, &cost1, &cost1, 2, table_max, true);
+    let cost2 = extract_cost_values(&tables.adder_count, 2);
     debug!(cost2_count = cost2.len(), "Cost 2 values found");
     let cost2_shifted = create_shifted_variants(&cost2, max_value);
     debug!("Processing cost 3 combinations");
-    addsub_combinations(
-        &mut adder_count,
-        &mut adder_structures,
-        &cost2,
-        &cost0_shifted,
-        3,
-        table_max,
-    );
-    addsub_combinations(
-        &mut adder_count,
-        &mut adder_structures,
-        &cost1,
-        &cost1_shifted,
-        3,
-        table_max,
-    );
-    addsub_combinations(
-        &mut adder_count,
-        &mut adder_structures,
-        &cost0,
-        &cost2_shifted,
-        3,
-        table_max,
-    );
-    cascade_combinations(
-        &mut adder_count,
-        &mut adder_structures,
-        &cost1,
-        &cost2,
-        3,
-        table_max,
-        false,
-    );
+    addsub_combinations(tables, &cost2, &cost0_shifted, 3, table_max);
+    addsub_combinations(tables, &cost1, &cost1_shifted, 3, table_max);
+    addsub_combinations(tables, &cost0, &cost2_shifted, 3, table_max);
+    cascade_combinations(tables, &cost1, &cost2, 3, table_max, false);
 
-    let cost3 = extract_cost_values(&adder_count, 3);
+    let cost3 = extract_cost_values(&tables.adder_count, 3);
     debug!(cost3_count = cost3.len(), "Cost 3 values found");
     let cost3_shifted = create_shifted_variants(&cost3, max_value);
     debug!("Processing cost 4 combinations");
-    addsub_combinations(
-        &mut adder_count,
-        &mut adder_structures,
-        &cost3,
-        &cost0_shifted,
-        4,
-        table_max,
-    );
-    addsub_combinations(
-        &mut adder_count,
-        &mut adder_structures,
-        &cost2,
-        &cost1_shifted,
-        4,
-        table_max,
-    );
-    addsub_combinations(
-        &mut adder_count,
-        &mut adder_structures,
-        &cost1,
-        &cost2_shifted,
-        4,
-        table_max,
-    );
-    addsub_combinations(
-        &mut adder_count,
-        &mut adder_structures,
-        &cost0,
-        &cost3_shifted,
-        4,
-        table_max,
-    );
-    cascade_combinations(
-        &mut adder_count,
-        &mut adder_structures,
-        &cost1,
-        &cost3,
-        4,
-        table_max,
-        false,
-    );
-    cascade_combinations(
-        &mut adder_count,
-        &mut adder_structures,
-        &cost2,
-        &cost2,
-        4,
-        table_max,
-        true,
-    );
+    addsub_combinations(tables, &cost3, &cost0_shifted, 4, table_max);
+    addsub_combinations(tables, &cost2, &cost1_shifted, 4, table_max);
+    addsub_combinations(tables, &cost1, &cost2_shifted, 4, table_max);
+    addsub_combinations(tables, &cost0, &cost3_shifted, 4, table_max);
+    cascade_combinations(tables, &cost1, &cost3, 4, table_max, false);
+    cascade_combinations(tables, &cost2, &cost2, 4, table_max, true);
     leapfrog4_combinations(
-        &mut adder_count,
-        &mut adder_structures,
+        tables,
         &cost1_shifted,
         &cost0_shifted,
         &cost0_shifted,
@@ -174,72 +165,20 @@ fn main() -> Result<()> {
         table_max,
     );
 
-    let cost4 = extract_cost_values(&adder_count, 4);
+    let cost4 = extract_cost_values(&tables.adder_count, 4);
     debug!(cost4_count = cost4.len(), "Cost 4 values found");
     let cost4_shifted = create_shifted_variants(&cost4, max_value);
     if max_bits > 12 {
         debug!("Processing cost 5 combinations");
-        addsub_combinations(
-            &mut adder_count,
-            &mut adder_structures,
-            &cost4,
-            &cost0_shifted,
-            5,
-            table_max,
-        );
-        addsub_combinations(
-            &mut adder_count,
-            &mut adder_structures,
-            &cost3,
-            &cost1_shifted,
-            5,
-            table_max,
-        );
-        addsub_combinations(
-            &mut adder_count,
-            &mut adder_structures,
-            &cost2,
-            &cost2_shifted,
-            5,
-            table_max,
-        );
-        addsub_combinations(
-            &mut adder_count,
-            &mut adder_structures,
-            &cost1,
-            &cost3_shifted,
-            5,
-            table_max,
-        );
-        addsub_combinations(
-            &mut adder_count,
-            &mut adder_structures,
-            &cost0,
-            &cost4_shifted,
-            5,
-            table_max,
-        );
-        cascade_combinations(
-            &mut adder_count,
-            &mut adder_structures,
-            &cost1,
-            &cost4,
-            5,
-            table_max,
-            false,
-        );
-        cascade_combinations(
-            &mut adder_count,
-            &mut adder_structures,
-            &cost2,
-            &cost3,
-            5,
-            table_max,
-            false,
-        );
+        addsub_combinations(tables, &cost4, &cost0_shifted, 5, table_max);
+        addsub_combinations(tables, &cost3, &cost1_shifted, 5, table_max);
+        addsub_combinations(tables, &cost2, &cost2_shifted, 5, table_max);
+        addsub_combinations(tables, &cost1, &cost3_shifted, 5, table_max);
+        addsub_combinations(tables, &cost0, &cost4_shifted, 5, table_max);
+        cascade_combinations(tables, &cost1, &cost4, 5, table_max, false);
+        cascade_combinations(tables, &cost2, &cost3, 5, table_max, false);
         leapfrog5_combinations(
-            &mut adder_count,
-            &mut adder_structures,
+            tables,
             &cost1_shifted,
             &cost0_shifted,
             &cost1,
@@ -250,8 +189,7 @@ fn main() -> Result<()> {
         );
 
         leapfrog4_combinations(
-            &mut adder_count,
-            &mut adder_structures,
+            tables,
             &cost2_shifted,
             &cost0_shifted,
             &cost0_shifted,
@@ -261,8 +199,7 @@ fn main() -> Result<()> {
         );
 
         leapfrog4_combinations(
-            &mut adder_count,
-            &mut adder_structures,
+            tables,
             &cost1_shifted,
             &cost1_shifted,
             &cost0_shifted,
@@ -272,8 +209,7 @@ fn main() -> Result<()> {
         );
 
         leapfrog4_combinations(
-            &mut adder_count,
-            &mut adder_structures,
+            tables,
             &cost1_shifted,
             &cost0_shifted,
             &cost0_shifted,
@@ -282,8 +218,7 @@ fn main() -> Result<()> {
             table_max,
         );
         leapfrog7_combinations(
-            &mut adder_count,
-            &mut adder_structures,
+            tables,
             &cost1_shifted,
             &cost0_shifted,
             &cost0_shifted,
@@ -295,90 +230,23 @@ fn main() -> Result<()> {
             table_max,
         );
     }
-    let cost5 = extract_cost_values(&adder_count, 5);
+    let cost5 = extract_cost_values(&tables.adder_count, 5);
     debug!(cost5_count = cost5.len(), "Cost 5 values found");
     if max_bits > 19 {
         let cost5_shifted = create_shifted_variants(&cost5, max_value);
         debug!("Processing cost 6 combinations");
-        addsub_combinations(
-            &mut adder_count,
-            &mut adder_structures,
-            &cost5,
-            &cost0_shifted,
-            6,
-            table_max,
-        );
-        addsub_combinations(
-            &mut adder_count,
-            &mut adder_structures,
-            &cost4,
-            &cost1_shifted,
-            6,
-            table_max,
-        );
-        addsub_combinations(
-            &mut adder_count,
-            &mut adder_structures,
-            &cost3,
-            &cost2_shifted,
-            6,
-            table_max,
-        );
-        addsub_combinations(
-            &mut adder_count,
-            &mut adder_structures,
-            &cost2,
-            &cost3_shifted,
-            6,
-            table_max,
-        );
-        addsub_combinations(
-            &mut adder_count,
-            &mut adder_structures,
-            &cost1,
-            &cost4_shifted,
-            6,
-            table_max,
-        );
-        addsub_combinations(
-            &mut adder_count,
-            &mut adder_structures,
-            &cost0,
-            &cost5_shifted,
-            6,
-            table_max,
-        );
-        cascade_combinations(
-            &mut adder_count,
-            &mut adder_structures,
-            &cost1,
-            &cost5,
-            6,
-            table_max,
-            false,
-        );
-        cascade_combinations(
-            &mut adder_count,
-            &mut adder_structures,
-            &cost2,
-            &cost4,
-            6,
-            table_max,
-            false,
-        );
-        cascade_combinations(
-            &mut adder_count,
-            &mut adder_structures,
-            &cost3,
-            &cost3,
-            6,
-            table_max,
-            true,
-        );
+        addsub_combinations(tables, &cost5, &cost0_shifted, 6, table_max);
+        addsub_combinations(tables, &cost4, &cost1_shifted, 6, table_max);
+        addsub_combinations(tables, &cost3, &cost2_shifted, 6, table_max);
+        addsub_combinations(tables, &cost2, &cost3_shifted, 6, table_max);
+        addsub_combinations(tables, &cost1, &cost4_shifted, 6, table_max);
+        addsub_combinations(tables, &cost0, &cost5_shifted, 6, table_max);
+        cascade_combinations(tables, &cost1, &cost5, 6, table_max, false);
+        cascade_combinations(tables, &cost2, &cost4, 6, table_max, false);
+        cascade_combinations(tables, &cost3, &cost3, 6, table_max, true);
 
         leapfrog4_combinations(
-            &mut adder_count,
-            &mut adder_structures,
+            tables,
             &cost3_shifted,
             &cost0_shifted,
             &cost0_shifted,
@@ -388,8 +256,7 @@ fn main() -> Result<()> {
         );
 
         leapfrog4_combinations(
-            &mut adder_count,
-            &mut adder_structures,
+            tables,
             &cost2_shifted,
             &cost0_shifted,
             &cost0_shifted,
@@ -399,8 +266,7 @@ fn main() -> Result<()> {
         );
 
         leapfrog4_combinations(
-            &mut adder_count,
-            &mut adder_structures,
+            tables,
             &cost1_shifted,
             &cost1_shifted,
             &cost0_shifted,
@@ -410,8 +276,7 @@ fn main() -> Result<()> {
         );
 
         leapfrog4_combinations(
-            &mut adder_count,
-            &mut adder_structures,
+            tables,
             &cost1_shifted,
             &cost1_shifted,
             &cost1_shifted,
@@ -421,8 +286,7 @@ fn main() -> Result<()> {
         );
 
         leapfrog4_combinations(
-            &mut adder_count,
-            &mut adder_structures,
+            tables,
             &cost2_shifted,
             &cost1_shifted,
             &cost0_shifted,
@@ -432,8 +296,7 @@ fn main() -> Result<()> {
         );
 
         leapfrog5_combinations(
-            &mut adder_count,
-            &mut adder_structures,
+            tables,
             &cost1_shifted,
             &cost0_shifted,
             &cost2,
@@ -444,8 +307,7 @@ fn main() -> Result<()> {
         );
 
         leapfrog5_combinations(
-            &mut adder_count,
-            &mut adder_structures,
+            tables,
             &cost2_shifted,
             &cost0_shifted,
             &cost1,
@@ -455,8 +317,7 @@ fn main() -> Result<()> {
             table_max,
         );
         leapfrog5_combinations(
-            &mut adder_count,
-            &mut adder_structures,
+            tables,
             &cost1_shifted,
             &cost1_shifted,
             &cost1,
@@ -466,8 +327,7 @@ fn main() -> Result<()> {
             table_max,
         );
         /* leapfrog5_combinations(
-            &mut adder_count,
-            &mut adder_structures,
+            tables,
             &cost1_shifted,
             &cost0_shifted,
             &cost1_shifted,
@@ -477,8 +337,7 @@ fn main() -> Result<()> {
             table_max,
         ); */
         leapfrog7_combinations(
-            &mut adder_count,
-            &mut adder_structures,
+            tables,
             &cost2_shifted,
             &cost0_shifted,
             &cost0_shifted,
@@ -490,8 +349,7 @@ fn main() -> Result<()> {
             table_max,
         );
         leapfrog7_combinations(
-            &mut adder_count,
-            &mut adder_structures,
+            tables,
             &cost1_shifted,
             &cost1_shifted,
             &cost0_shifted,
@@ -503,8 +361,7 @@ fn main() -> Result<()> {
             table_max,
         );
         leapfrog7_combinations(
-            &mut adder_count,
-            &mut adder_structures,
+            tables,
             &cost1_shifted,
             &cost0_shifted,
             &cost1_shifted,
@@ -516,8 +373,7 @@ fn main() -> Result<()> {
             table_max,
         );
         leapfrog7_combinations(
-            &mut adder_count,
-            &mut adder_structures,
+            tables,
             &cost1_shifted,
             &cost0_shifted,
             &cost0_shifted,
@@ -529,8 +385,7 @@ fn main() -> Result<()> {
             table_max,
         );
         leapfrog7_combinations(
-            &mut adder_count,
-            &mut adder_structures,
+            tables,
             &cost1_shifted,
             &cost0_shifted,
             &cost0_shifted,
@@ -542,8 +397,7 @@ fn main() -> Result<()> {
             table_max,
         );
         leapfrog8_combinations(
-            &mut adder_count,
-            &mut adder_structures,
+            tables,
             &cost1_shifted,
             &cost0_shifted,
             &cost0_shifted,
@@ -556,12 +410,35 @@ fn main() -> Result<()> {
             table_max,
         );
     }
-    let cost6 = extract_cost_values(&adder_count, 6);
+    let cost6 = extract_cost_values(&tables.adder_count, 6);
     debug!(cost5_count = cost6.len(), "Cost 6 values found");
+}
+
+/// Write the adder cost, depth-bounded adder cost and graph types tables to dir
+///
+/// Only one structure of each group of mirrored ones is saved, see `prune_symmetric`.
+fn write_tables(dir: &Path, generated: &Generated) -> io::Result<()> {
+    let Generated { header, tables } = generated;
+    let Tables {
+        adder_count,
+        adder_structures,
+        depths,
+        ..
+    } = tables;
 
     info!("Packing and saving data");
-    let (packed, count) = pack_sparse_vector(&adder_count);
-    save_packed_data("constant-multiplication/adder_cost.bin", &packed, count)?;
+    let (packed, count) = pack_adder_costs(adder_count);
+    std::fs::write(
+        dir.join("adder_cost.bin"),
+        encode_adder_costs(adder_count, *header),
+    )?;
+    let depth_costs: Vec<Vec<u8>> = (1..=MAX_DEPTH as u8)
+        .map(|max_depth| depths.costs(max_depth))
+        .collect();
+    std::fs::write(
+        dir.join("depth_cost_bound.bin"),
+        encode_depth_costs(&depth_costs, *header),
+    )?;
 
     info!("Packed {} odd values into {} bytes", count, packed.len());
     info!("Original size: {} bytes", adder_count.len());
@@ -570,111 +447,79 @@ fn main() -> Result<()> {
         (packed.len() as f64 / adder_count.len() as f64) * 100.0
     );
 
-    info!("Saving graph types");
-    let mut graph_types: Vec<Vec<GraphType>> = Vec::new();
-    for (i, structure) in adder_structures.iter().enumerate() {
-        if i % 2 == 1 && i <= table_max {
-            if let Some(types) = structure {
-                graph_types.push(types.clone());
-            } else {
-                graph_types.push(vec![]);
-                warn!("No graph types found for value {}", i);
-            }
-        }
-    }
-
     if !SAVE_GRAPH_TYPES {
         return Ok(());
     }
 
-    // Serialize with varint encoding
-    let serialized = serialize_graph_types(&graph_types);
-
-    info!("Graph types serialization:");
-    info!("  Varint-encoded: {} bytes", serialized.len());
+    info!("Saving graph types");
+    let graph_types = collect_graph_types(adder_structures, true);
 
-    // Compress with lz4
-    let compressed = lz4_flex::compress_prepend_size(&serialized);
+    // Serialize with varint encoding and compress with lz4, chunk by chunk
+    let encoded = encode_graph_types(&graph_types, GRAPH_TYPES_CHUNK_SIZE, *header);
 
-    info!("  Compressed (LZ4): {} bytes", compressed.len());
+    info!("Graph types serialization:");
     info!(
-        "  Compression ratio: {:.2}%",
-        (compressed.len() as f64 / serialized.len() as f64) * 100.0
+        "  Compressed (LZ4): {} bytes in {} chunks",
+        encoded.len(),
+        graph_types.len().div_ceil(GRAPH_TYPES_CHUNK_SIZE)
     );
 
-    std::fs::write("constant-multiplication/graph_types.bin", &compressed)?;
+    std::fs::write(dir.join("graph_types.bin"), &encoded)
+}
 
-    debug!("Generating final results");
-    // Print results
-    let mut result_count = 0;
-    let mut missing_count = 0;
-    for c in zip(adder_count.iter(), adder_structures.iter()).enumerate() {
-        if let Some(structures) = c.1.1 {
-            result_count += 1;
-            if print_structures {
-                println!(
-                    "Value: {}, Cost: {}, Structures: {:?}",
-                    c.0, c.1.0, structures
-                );
+/// Structures of each odd integer, without duplicates
+///
+/// With prune, only the first structure of each group of mirrored ones is kept.
+fn collect_graph_types(adder_structures: &AdderStructures, prune: bool) -> Vec<Vec<GraphType>> {
+    let mut graph_types: Vec<Vec<GraphType>> = Vec::new();
+    for (i, structure) in adder_structures.iter().enumerate() {
+        if i % 2 == 1 {
+            if let Some(types) = structure {
+                graph_types.push(if prune {
+                    prune_symmetric(types)
+                } else {
+                    let mut seen = HashSet::new();
+                    types.iter().filter(|t| seen.insert(*t)).cloned().collect()
+                });
+            } else {
+                graph_types.push(vec![]);
+                warn!("No graph types found for value {}", i);
             }
         }
     }
+    graph_types
+}
 
-    for c in adder_structures.iter().enumerate() {
-        if c.1.is_none() && c.0 % 2 == 1 && c.0 != 1 && c.0 <= table_max {
-            missing_count += 1;
-            if print_missing {
-                println!("Missing: {}", c.0);
+/// Keep the first structure of each group of mirrored ones
+///
+/// The others are restored from it by the library, see `GraphType::mirrored`.
+fn prune_symmetric(types: &[GraphType]) -> Vec<GraphType> {
+    let present: HashSet<&GraphType> = types.iter().collect();
+    let mut covered: HashSet<&GraphType> = HashSet::new();
+    let mut result = Vec::new();
+    for graph_type in types {
+        if !covered.insert(graph_type) {
+            continue;
+        }
+        result.push(graph_type.clone());
+
+        // Mark the rest of the group as covered
+        let mut stack = vec![graph_type];
+        while let Some(current) = stack.pop() {
+            for mirrored in current.mirrored() {
+                if let Some(&other) = present.get(&mirrored)
+                    && covered.insert(other)
+                {
+                    stack.push(other);
+                }
             }
         }
     }
-
-    info!(result_count, missing_count, "Computation complete");
-    Ok(())
-}
-
-#[derive(Debug, Clone)]
-enum GraphType {
-    Adder(usize, usize),
-    Subtractor(usize, usize),
-    Cascade(usize, usize),
-    Leapfrog4_1(usize, usize, usize, usize),
-    Leapfrog4_2(usize, usize, usize, usize),
-    Leapfrog4_3(usize, usize, usize, usize),
-    Leapfrog4_4(usize, usize, usize, usize),
-    Leapfrog5_1(usize, usize, usize, usize, usize),
-    Leapfrog5_2(usize, usize, usize, usize, usize),
-    Leapfrog5_3(usize, usize, usize, usize, usize),
-    Leapfrog5_4(usize, usize, usize, usize, usize),
-    Leapfrog7_1(usize, usize, usize, usize, usize, usize, usize),
-    Leapfrog7_2(usize, usize, usize, usize, usize, usize, usize),
-    Leapfrog7_3(usize, usize, usize, usize, usize, usize, usize),
-    Leapfrog7_4(usize, usize, usize, usize, usize, usize, usize),
-    Leapfrog7_5(usize, usize, usize, usize, usize, usize, usize),
-    Leapfrog7_6(usize, usize, usize, usize, usize, usize, usize),
-    Leapfrog7_7(usize, usize, usize, usize, usize, usize, usize),
-    Leapfrog7_8(usize, usize, usize, usize, usize, usize, usize),
-    Leapfrog8_1(usize, usize, usize, usize, usize, usize, usize, usize),
-    Leapfrog8_2(usize, usize, usize, usize, usize, usize, usize, usize),
-    Leapfrog8_3(usize, usize, usize, usize, usize, usize, usize, usize),
-    Leapfrog8_4(usize, usize, usize, usize, usize, usize, usize, usize),
-    Leapfrog8_5(usize, usize, usize, usize, usize, usize, usize, usize),
-    Leapfrog8_6(usize, usize, usize, usize, usize, usize, usize, usize),
-    Leapfrog8_7(usize, usize, usize, usize, usize, usize, usize, usize),
-    Leapfrog8_8(usize, usize, usize, usize, usize, usize, usize, usize),
-    Leapfrog8_9(usize, usize, usize, usize, usize, usize, usize, usize),
-    Leapfrog8_10(usize, usize, usize, usize, usize, usize, usize, usize),
-    Leapfrog8_11(usize, usize, usize, usize, usize, usize, usize, usize),
-    Leapfrog8_12(usize, usize, usize, usize, usize, usize, usize, usize),
-    Leapfrog8_13(usize, usize, usize, usize, usize, usize, usize, usize),
-    Leapfrog8_14(usize, usize, usize, usize, usize, usize, usize, usize),
-    Leapfrog8_15(usize, usize, usize, usize, usize, usize, usize, usize),
-    Leapfrog8_16(usize, usize, usize, usize, usize, usize, usize, usize),
+    result
 }
 
 fn addsub_combinations(
-    adder_count: &mut [u8],
-    adder_structures: &mut AdderStructures,
+    tables: &mut Tables,
     terms: &[usize],
     terms_shifted: &[usize],
     adder_cost: u8,
@@ -689,31 +534,32 @@ fn addsub_combinations(
     for &term1 in terms.iter() {
         for &term2 in terms_shifted.iter() {
             let sum = findodd(term1 + term2);
-            if sum <= max_value && adder_count[sum] >= adder_cost {
-                adder_count[sum] = adder_cost;
-                if term1 <= term2 {
-                    add_graph_type(adder_structures, sum, GraphType::Adder(term1, term2));
-                } else {
-                    add_graph_type(adder_structures, sum, GraphType::Adder(term2, term1));
-                }
+            if sum <= max_value {
+                let (a, b) = (term1.min(term2), term1.max(term2));
+                add_structure(tables, sum, adder_cost, GraphType::Adder(a, b));
             }
             let diff = findodd(term1.abs_diff(term2));
 
-            if diff <= max_value && diff != 0 && adder_count[diff] > adder_cost {
-                adder_count[diff] = adder_cost;
-                if term1 >= term2 {
-                    add_graph_type(adder_structures, diff, GraphType::Subtractor(term1, term2));
-                } else {
-                    add_graph_type(adder_structures, diff, GraphType::Subtractor(term2, term1));
+            if diff <= max_value && diff != 0 {
+                let (a, b) = (term1.max(term2), term1.min(term2));
+                tables.record(diff, adder_cost, &GraphType::Subtractor(a, b));
+                // Unlike sums, differences are only kept when they lower the cost
+                if !tables.depths_only && tables.adder_count[diff] > adder_cost {
+                    tables.adder_count[diff] = adder_cost;
+                    add_graph_type(
+                        &mut tables.adder_structures,
+                        diff,
+                        GraphType::Subtractor(a, b),
+                    );
                 }
             }
         }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn cascade_combinations(
-    adder_count: &mut [u8],
-    adder_structures: &mut AdderStructures,
+    tables: &mut Tables,
     terms1: &[usize],
     terms2: &[usize],
     adder_cost: u8,
@@ -732,21 +578,17 @@ fn cascade_combinations(
                 continue;
             }
             let cascade = term1 * term2;
-            if cascade <= max_value && adder_count[cascade] >= adder_cost {
-                adder_count[cascade] = adder_cost;
-                if term1 <= term2 {
-                    add_graph_type(adder_structures, cascade, GraphType::Cascade(term1, term2));
-                } else {
-                    add_graph_type(adder_structures, cascade, GraphType::Cascade(term2, term1));
-                }
+            if cascade <= max_value {
+                let (a, b) = (term1.min(term2), term1.max(term2));
+                add_symmetric_structure(tables, cascade, adder_cost, GraphType::Cascade(a, b));
             }
         }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn leapfrog5_combinations(
-    adder_count: &mut [u8],
-    adder_structures: &mut AdderStructures,
+    tables: &mut Tables,
     terms1: &[usize],
     terms2: &[usize],
     terms3: &[usize],
@@ -768,7 +610,6 @@ fn leapfrog5_combinations(
 
     for &term1 in terms1.iter() {
         let t1 = term1 as u128;
-        let t1_odd = findodd_u128(t1);
         for &term2 in terms2.iter() {
             if term1.is_multiple_of(2) && term2.is_multiple_of(2) {
                 continue;
@@ -797,84 +638,54 @@ fn leapfrog5_combinations(
                         }
 
                         let t5 = term5 as u128;
-                        let t5_odd = findodd_u128(t5);
                         let leapfrog = findodd_u128(t5 * (t1 * t3 + t2) + t1 * t4);
-                        if leapfrog != 0
-                            && leapfrog <= max_value_u128
-                            && adder_count[leapfrog as usize] >= adder_cost
-                        {
-                            // Symmetric case when t2 = t4
-                            // (t5 * (t1 * t3 + t2) + t1 * t4 == (t1 * (t5 * t3 + t4) + t5 * t2
-                            if t2 != t4 {
-                                adder_count[leapfrog as usize] = adder_cost;
-                                add_graph_type(
-                                    adder_structures,
-                                    leapfrog as usize,
-                                    GraphType::Leapfrog5_1(term1, term2, term3, term4, term5),
-                                );
-                            }
+                        // Symmetric case when t2 = t4
+                        // (t5 * (t1 * t3 + t2) + t1 * t4 == (t1 * (t5 * t3 + t4) + t5 * t2
+                        if leapfrog != 0 && leapfrog <= max_value_u128 {
+                            add_symmetric_structure(
+                                tables,
+                                leapfrog as usize,
+                                adder_cost,
+                                GraphType::Leapfrog5_1(term1, term2, term3, term4, term5),
+                            );
                         }
 
                         let leapfrog = findodd_u128((t5 * (t1 * t3 + t2)).abs_diff(t1 * t4));
-                        if leapfrog != 0
-                            && leapfrog <= max_value_u128
-                            && adder_count[leapfrog as usize] >= adder_cost
-                        {
-                            // Symmetric case with 5_3 or 5_4 when t1 and t5 are odd
-                            if !(t1 == t1_odd && t5 == t5_odd && t1 >= t5) {
-                                adder_count[leapfrog as usize] = adder_cost;
-                                add_graph_type(
-                                    adder_structures,
-                                    leapfrog as usize,
-                                    GraphType::Leapfrog5_2(term1, term2, term3, term4, term5),
-                                );
-                            }
+                        // Symmetric case with 5_3 or 5_4 when t1 and t5 are odd
+                        if leapfrog != 0 && leapfrog <= max_value_u128 {
+                            add_symmetric_structure(
+                                tables,
+                                leapfrog as usize,
+                                adder_cost,
+                                GraphType::Leapfrog5_2(term1, term2, term3, term4, term5),
+                            );
                         }
 
                         let leapfrog = findodd_u128(t5 * ((t1 * t3).abs_diff(t2)) + t1 * t4);
-                        if leapfrog != 0
-                            && leapfrog <= max_value_u128
-                            && adder_count[leapfrog as usize] >= adder_cost
-                        {
-                            if !(t2 == 1 && t4 == 1)
-                                && !(t2 == t4 && t4 > t3 * t5 && t1 >= t5)
-                                && !(t1 == t1_odd && t5 == t5_odd && t1 >= t5)
-                            {
-                                // Symmetric case (with 5_2)
-                                // t5 * ((t1 * t3 - 1)) + t1 * 1 = (t1 * (t5 * t3 + 1)) - t5 * 1)
-                                // Symmetric case when t2 = t4 > t3 * t5
-                                // t5 * ((t2 - t1 * t3)) + t1 * t4 = t1 * (t4 - t5 * t3) - t1 * t2
-                                // Symmetric case with 5_2 when t1 and t5 are odd
-                                adder_count[leapfrog as usize] = adder_cost;
-                                add_graph_type(
-                                    adder_structures,
-                                    leapfrog as usize,
-                                    GraphType::Leapfrog5_3(term1, term2, term3, term4, term5),
-                                );
-                            }
+                        // Symmetric case (with 5_2)
+                        // t5 * ((t1 * t3 - 1)) + t1 * 1 = (t1 * (t5 * t3 + 1)) - t5 * 1)
+                        // Symmetric case when t2 = t4 > t3 * t5
+                        // t5 * ((t2 - t1 * t3)) + t1 * t4 = t1 * (t4 - t5 * t3) - t1 * t2
+                        // Symmetric case with 5_2 when t1 and t5 are odd
+                        if leapfrog != 0 && leapfrog <= max_value_u128 {
+                            add_symmetric_structure(
+                                tables,
+                                leapfrog as usize,
+                                adder_cost,
+                                GraphType::Leapfrog5_3(term1, term2, term3, term4, term5),
+                            );
                         }
 
                         let leapfrog =
                             findodd_u128((t5 * (t1 * t3).abs_diff(t2)).abs_diff(t1 * t4));
-                        if leapfrog != 0
-                            && leapfrog <= max_value_u128
-                            && adder_count[leapfrog as usize] >= adder_cost
-                        {
-                            // Symmetric case with 5_2 when t1 and t5 are odd
-                            if !(t1 == t1_odd && t5 == t5_odd && t1 >= t5)
-                                && !(term1 >= term5
-                                    && term2 == 1
-                                    && term4 == 1
-                                    && term1.is_multiple_of(2)
-                                    && term5.is_multiple_of(2))
-                            {
-                                adder_count[leapfrog as usize] = adder_cost;
-                                add_graph_type(
-                                    adder_structures,
-                                    leapfrog as usize,
-                                    GraphType::Leapfrog5_4(term1, term2, term3, term4, term5),
-                                );
-                            }
+                        // Symmetric case with 5_2 when t1 and t5 are odd
+                        if leapfrog != 0 && leapfrog <= max_value_u128 {
+                            add_symmetric_structure(
+                                tables,
+                                leapfrog as usize,
+                                adder_cost,
+                                GraphType::Leapfrog5_4(term1, term2, term3, term4, term5),
+                            );
                         }
                     }
                 }
@@ -883,9 +694,9 @@ fn leapfrog5_combinations(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn leapfrog4_combinations(
-    adder_count: &mut [u8],
-    adder_structures: &mut AdderStructures,
+    tables: &mut Tables,
     terms1: &[usize],
     terms2: &[usize],
     terms4: &[usize],
@@ -924,80 +735,51 @@ fn leapfrog4_combinations(
                     let t5 = term5 as u128;
 
                     let leapfrog = findodd_u128(t5 * (t1 + t2) + t1 * t4);
-                    if leapfrog != 0
-                        && leapfrog <= max_value_u128
-                        && adder_count[leapfrog as usize] >= adder_cost
-                    {
-                        // Symmetric case
-                        // t5 * (t1 + t2) + t1 * t4 == t1 * (t5 + t4) + t5 * t2
-                        // Symmetric case when t2 == t4
-                        // t5 * (t1 + 1) + t1 * 1 == t1 * (t5 + 1) + t5 * 1
-                        if !(t1 == findodd_u128(t1) && t5 == findodd_u128(t5) && t1 >= t5)
-                            && !(t2 == t4 && t1 >= t5)
-                        {
-                            adder_count[leapfrog as usize] = adder_cost;
-                            add_graph_type(
-                                adder_structures,
-                                leapfrog as usize,
-                                GraphType::Leapfrog4_1(term1, term2, term4, term5),
-                            );
-                        }
+                    // Symmetric case
+                    // t5 * (t1 + t2) + t1 * t4 == t1 * (t5 + t4) + t5 * t2
+                    // Symmetric case when t2 == t4
+                    // t5 * (t1 + 1) + t1 * 1 == t1 * (t5 + 1) + t5 * 1
+                    if leapfrog != 0 && leapfrog <= max_value_u128 {
+                        add_symmetric_structure(
+                            tables,
+                            leapfrog as usize,
+                            adder_cost,
+                            GraphType::Leapfrog4_1(term1, term2, term4, term5),
+                        );
                     }
 
                     let leapfrog = findodd_u128((t5 * (t1 + t2)).abs_diff(t1 * t4));
-                    if leapfrog != 0
-                        && leapfrog <= max_value_u128
-                        && adder_count[leapfrog as usize] >= adder_cost
-                    {
-                        // Symmetric case (with 4_3)
-                        // t5 * (t1 - t2) + t1 * t4 == t1 * (t5 + t4) - t5 * t2
-                        if !(t1 == findodd_u128(t1) && t5 == findodd_u128(t5) && t1 >= t5) {
-                            adder_count[leapfrog as usize] = adder_cost;
-                            add_graph_type(
-                                adder_structures,
-                                leapfrog as usize,
-                                GraphType::Leapfrog4_2(term1, term2, term4, term5),
-                            );
-                        }
+                    // Symmetric case (with 4_3)
+                    // t5 * (t1 - t2) + t1 * t4 == t1 * (t5 + t4) - t5 * t2
+                    if leapfrog != 0 && leapfrog <= max_value_u128 {
+                        add_symmetric_structure(
+                            tables,
+                            leapfrog as usize,
+                            adder_cost,
+                            GraphType::Leapfrog4_2(term1, term2, term4, term5),
+                        );
                     }
 
                     let leapfrog = findodd_u128(t5 * (t1.abs_diff(t2)) + t1 * t4);
-                    if leapfrog != 0
-                        && leapfrog <= max_value_u128
-                        && adder_count[leapfrog as usize] >= adder_cost
-                    {
-                        // Symmetric case (with 4_2)
-                        // t5 * (t1 - t2) + t1 * t4 == t1 * (t5 + t4) - t5 * t2
-                        if !(t1 == findodd_u128(t1) && t5 == findodd_u128(t5) && t1 >= t5)
-                            && !(term2 == 1
-                                && term4 == 1
-                                && term1.is_multiple_of(2)
-                                && term5.is_multiple_of(2))
-                        {
-                            adder_count[leapfrog as usize] = adder_cost;
-                            add_graph_type(
-                                adder_structures,
-                                leapfrog as usize,
-                                GraphType::Leapfrog4_3(term1, term2, term4, term5),
-                            );
-                        }
+                    // Symmetric case (with 4_2)
+                    // t5 * (t1 - t2) + t1 * t4 == t1 * (t5 + t4) - t5 * t2
+                    if leapfrog != 0 && leapfrog <= max_value_u128 {
+                        add_symmetric_structure(
+                            tables,
+                            leapfrog as usize,
+                            adder_cost,
+                            GraphType::Leapfrog4_3(term1, term2, term4, term5),
+                        );
                     }
 
                     let leapfrog = findodd_u128((t5 * (t1.abs_diff(t2))).abs_diff(t1 * t4));
-                    if leapfrog != 0
-                        && leapfrog <= max_value_u128
-                        && adder_count[leapfrog as usize] >= adder_cost
-                    {
-                        if !(t1 == findodd_u128(t1) && t5 == findodd_u128(t5) && t1 >= t5)
-                            && !(term1 >= term5 && term2 == 1 && term4 == 1)
-                        {
-                            adder_count[leapfrog as usize] = adder_cost;
-                            add_graph_type(
-                                adder_structures,
-                                leapfrog as usize,
-                                GraphType::Leapfrog4_4(term1, term2, term4, term5),
-                            );
-                        }
+                    if leapfrog != 0 && leapfrog <= max_value_u128 {
+                        add_symmetric_structure(
+                            tables,
+                            leapfrog as usize,
+                            adder_cost,
+                            GraphType::Leapfrog4_4(term1, term2, term4, term5),
+                        );
                     }
                 }
             }
@@ -1005,9 +787,9 @@ fn leapfrog4_combinations(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn leapfrog7_combinations(
-    adder_count: &mut [u8],
-    adder_structures: &mut AdderStructures,
+    tables: &mut Tables,
     terms1: &[usize],
     terms2: &[usize],
     terms3: &[usize],
@@ -1033,7 +815,6 @@ fn leapfrog7_combinations(
 
     for &term1 in terms1.iter() {
         let t1 = term1 as u128;
-        let t1_odd = findodd_u128(t1);
         for &term2 in terms2.iter() {
             if term1.is_multiple_of(2) && term2.is_multiple_of(2) {
                 continue;
@@ -1051,7 +832,6 @@ fn leapfrog7_combinations(
                             continue;
                         }
                         let t5 = term5 as u128;
-                        let t5_odd = findodd_u128(t5);
 
                         for &term6 in terms6.iter() {
                             if (term4.is_multiple_of(2) || term5.is_multiple_of(2))
@@ -1060,26 +840,21 @@ fn leapfrog7_combinations(
                                 continue;
                             }
                             let t6 = term6 as u128;
-                            let t6_odd = findodd_u128(t6);
 
                             for &term7 in terms7.iter() {
                                 if term6.is_multiple_of(2) && term7.is_multiple_of(2) {
                                     continue;
                                 }
                                 let t7 = term7 as u128;
-                                let t7_odd = findodd_u128(t7);
 
                                 let leapfrog = findodd_u128(
                                     (t7 * (t5 * (t1 * t3 + t2) + t1 * t4)) + t6 * (t1 * t3 + t2),
                                 );
-                                if leapfrog != 0
-                                    && leapfrog <= max_value_u128
-                                    && adder_count[leapfrog as usize] >= adder_cost
-                                {
-                                    adder_count[leapfrog as usize] = adder_cost;
-                                    add_graph_type(
-                                        adder_structures,
+                                if leapfrog != 0 && leapfrog <= max_value_u128 {
+                                    add_structure(
+                                        tables,
                                         leapfrog as usize,
+                                        adder_cost,
                                         GraphType::Leapfrog7_1(
                                             term1, term2, term3, term4, term5, term6, term7,
                                         ),
@@ -1090,14 +865,11 @@ fn leapfrog7_combinations(
                                     (t7 * (t5 * ((t1 * t3).abs_diff(t2)) + t1 * t4))
                                         + t6 * ((t1 * t3).abs_diff(t2)),
                                 );
-                                if leapfrog != 0
-                                    && leapfrog <= max_value_u128
-                                    && adder_count[leapfrog as usize] >= adder_cost
-                                {
-                                    adder_count[leapfrog as usize] = adder_cost;
-                                    add_graph_type(
-                                        adder_structures,
+                                if leapfrog != 0 && leapfrog <= max_value_u128 {
+                                    add_structure(
+                                        tables,
                                         leapfrog as usize,
+                                        adder_cost,
                                         GraphType::Leapfrog7_2(
                                             term1, term2, term3, term4, term5, term6, term7,
                                         ),
@@ -1107,14 +879,11 @@ fn leapfrog7_combinations(
                                     t7 * ((t5 * (t1 * t3 + t2)).abs_diff(t1 * t4))
                                         + t6 * (t1 * t3 + t2),
                                 );
-                                if leapfrog != 0
-                                    && leapfrog <= max_value_u128
-                                    && adder_count[leapfrog as usize] >= adder_cost
-                                {
-                                    adder_count[leapfrog as usize] = adder_cost;
-                                    add_graph_type(
-                                        adder_structures,
+                                if leapfrog != 0 && leapfrog <= max_value_u128 {
+                                    add_structure(
+                                        tables,
                                         leapfrog as usize,
+                                        adder_cost,
                                         GraphType::Leapfrog7_3(
                                             term1, term2, term3, term4, term5, term6, term7,
                                         ),
@@ -1124,14 +893,11 @@ fn leapfrog7_combinations(
                                     (t7 * (t5 * (t1 * t3 + t2) + t1 * t4))
                                         .abs_diff(t6 * (t1 * t3 + t2)),
                                 );
-                                if leapfrog != 0
-                                    && leapfrog <= max_value_u128
-                                    && adder_count[leapfrog as usize] >= adder_cost
-                                {
-                                    adder_count[leapfrog as usize] = adder_cost;
-                                    add_graph_type(
-                                        adder_structures,
+                                if leapfrog != 0 && leapfrog <= max_value_u128 {
+                                    add_structure(
+                                        tables,
                                         leapfrog as usize,
+                                        adder_cost,
                                         GraphType::Leapfrog7_4(
                                             term1, term2, term3, term4, term5, term6, term7,
                                         ),
@@ -1141,14 +907,11 @@ fn leapfrog7_combinations(
                                     t7 * ((t5 * ((t1 * t3).abs_diff(t2))).abs_diff(t1 * t4))
                                         + t6 * ((t1 * t3).abs_diff(t2)),
                                 );
-                                if leapfrog != 0
-                                    && leapfrog <= max_value_u128
-                                    && adder_count[leapfrog as usize] >= adder_cost
-                                {
-                                    adder_count[leapfrog as usize] = adder_cost;
-                                    add_graph_type(
-                                        adder_structures,
+                                if leapfrog != 0 && leapfrog <= max_value_u128 {
+                                    add_structure(
+                                        tables,
                                         leapfrog as usize,
+                                        adder_cost,
                                         GraphType::Leapfrog7_5(
                                             term1, term2, term3, term4, term5, term6, term7,
                                         ),
@@ -1158,14 +921,11 @@ fn leapfrog7_combinations(
                                     (t7 * (t5 * ((t1 * t3).abs_diff(t2)) + t1 * t4))
                                         .abs_diff(t6 * ((t1 * t3).abs_diff(t2))),
                                 );
-                                if leapfrog != 0
-                                    && leapfrog <= max_value_u128
-                                    && adder_count[leapfrog as usize] >= adder_cost
-                                {
-                                    adder_count[leapfrog as usize] = adder_cost;
-                                    add_graph_type(
-                                        adder_structures,
+                                if leapfrog != 0 && leapfrog <= max_value_u128 {
+                                    add_structure(
+                                        tables,
                                         leapfrog as usize,
+                                        adder_cost,
                                         GraphType::Leapfrog7_6(
                                             term1, term2, term3, term4, term5, term6, term7,
                                         ),
@@ -1175,14 +935,11 @@ fn leapfrog7_combinations(
                                     (t7 * ((t5 * (t1 * t3 + t2)).abs_diff(t1 * t4)))
                                         .abs_diff(t6 * (t1 * t3 + t2)),
                                 );
-                                if leapfrog != 0
-                                    && leapfrog <= max_value_u128
-                                    && adder_count[leapfrog as usize] >= adder_cost
-                                {
-                                    adder_count[leapfrog as usize] = adder_cost;
-                                    add_graph_type(
-                                        adder_structures,
+                                if leapfrog != 0 && leapfrog <= max_value_u128 {
+                                    add_structure(
+                                        tables,
                                         leapfrog as usize,
+                                        adder_cost,
                                         GraphType::Leapfrog7_7(
                                             term1, term2, term3, term4, term5, term6, term7,
                                         ),
@@ -1192,14 +949,11 @@ fn leapfrog7_combinations(
                                     (t7 * (t5 * ((t1 * t3).abs_diff(t2))).abs_diff(t1 * t4))
                                         .abs_diff(t6 * ((t1 * t3).abs_diff(t2))),
                                 );
-                                if leapfrog != 0
-                                    && leapfrog <= max_value_u128
-                                    && adder_count[leapfrog as usize] >= adder_cost
-                                {
-                                    adder_count[leapfrog as usize] = adder_cost;
-                                    add_graph_type(
-                                        adder_structures,
+                                if leapfrog != 0 && leapfrog <= max_value_u128 {
+                                    add_structure(
+                                        tables,
                                         leapfrog as usize,
+                                        adder_cost,
                                         GraphType::Leapfrog7_8(
                                             term1, term2, term3, term4, term5, term6, term7,
                                         ),
@@ -1214,9 +968,9 @@ fn leapfrog7_combinations(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn leapfrog8_combinations(
-    adder_count: &mut [u8],
-    adder_structures: &mut AdderStructures,
+    tables: &mut Tables,
     terms1: &[usize],
     terms2: &[usize],
     terms3: &[usize],
@@ -1244,7 +998,6 @@ fn leapfrog8_combinations(
 
     for &term1 in terms1.iter() {
         let t1 = term1 as u128;
-        let t1_odd = findodd_u128(t1);
         for &term2 in terms2.iter() {
             if term1.is_multiple_of(2) && term2.is_multiple_of(2) {
                 continue;
@@ -1262,15 +1015,12 @@ fn leapfrog8_combinations(
                             continue;
                         }
                         let t5 = term5 as u128;
-                        let t5_odd = findodd_u128(t5);
 
                         for &term6 in terms6.iter() {
                             let t6 = term6 as u128;
-                            let t6_odd = findodd_u128(t6);
 
                             for &term7 in terms7.iter() {
                                 let t7 = term7 as u128;
-                                let t7_odd = findodd_u128(t7);
 
                                 for &term8 in terms8.iter() {
                                     if term6.is_multiple_of(2)
@@ -1280,20 +1030,16 @@ fn leapfrog8_combinations(
                                         continue;
                                     }
                                     let t8 = term8 as u128;
-                                    let t8_odd = findodd_u128(t8);
                                     let leapfrog = findodd_u128(
                                         ((t7 * (t5 * (t1 * t3 + t2) + t1 * t4))
                                             + t6 * (t1 * t3 + t2))
                                             .abs_diff(t1 * t8),
                                     );
-                                    if leapfrog != 0
-                                        && leapfrog <= max_value_u128
-                                        && adder_count[leapfrog as usize] >= adder_cost
-                                    {
-                                        adder_count[leapfrog as usize] = adder_cost;
-                                        add_graph_type(
-                                            adder_structures,
+                                    if leapfrog != 0 && leapfrog <= max_value_u128 {
+                                        add_structure(
+                                            tables,
                                             leapfrog as usize,
+                                            adder_cost,
                                             GraphType::Leapfrog8_9(
                                                 term1, term2, term3, term4, term5, term6, term7,
                                                 term8,
@@ -1306,14 +1052,11 @@ fn leapfrog8_combinations(
                                             + t6 * ((t1 * t3).abs_diff(t2)))
                                         .abs_diff(t1 * t8),
                                     );
-                                    if leapfrog != 0
-                                        && leapfrog <= max_value_u128
-                                        && adder_count[leapfrog as usize] >= adder_cost
-                                    {
-                                        adder_count[leapfrog as usize] = adder_cost;
-                                        add_graph_type(
-                                            adder_structures,
+                                    if leapfrog != 0 && leapfrog <= max_value_u128 {
+                                        add_structure(
+                                            tables,
                                             leapfrog as usize,
+                                            adder_cost,
                                             GraphType::Leapfrog8_10(
                                                 term1, term2, term3, term4, term5, term6, term7,
                                                 term8,
@@ -1325,14 +1068,11 @@ fn leapfrog8_combinations(
                                             + t6 * (t1 * t3 + t2))
                                             .abs_diff(t1 * t8),
                                     );
-                                    if leapfrog != 0
-                                        && leapfrog <= max_value_u128
-                                        && adder_count[leapfrog as usize] >= adder_cost
-                                    {
-                                        adder_count[leapfrog as usize] = adder_cost;
-                                        add_graph_type(
-                                            adder_structures,
+                                    if leapfrog != 0 && leapfrog <= max_value_u128 {
+                                        add_structure(
+                                            tables,
                                             leapfrog as usize,
+                                            adder_cost,
                                             GraphType::Leapfrog8_11(
                                                 term1, term2, term3, term4, term5, term6, term7,
                                                 term8,
@@ -1344,14 +1084,11 @@ fn leapfrog8_combinations(
                                             .abs_diff(t6 * (t1 * t3 + t2)))
                                         .abs_diff(t1 * t8),
                                     );
-                                    if leapfrog != 0
-                                        && leapfrog <= max_value_u128
-                                        && adder_count[leapfrog as usize] >= adder_cost
-                                    {
-                                        adder_count[leapfrog as usize] = adder_cost;
-                                        add_graph_type(
-                                            adder_structures,
+                                    if leapfrog != 0 && leapfrog <= max_value_u128 {
+                                        add_structure(
+                                            tables,
                                             leapfrog as usize,
+                                            adder_cost,
                                             GraphType::Leapfrog8_12(
                                                 term1, term2, term3, term4, term5, term6, term7,
                                                 term8,
@@ -1363,14 +1100,11 @@ fn leapfrog8_combinations(
                                             + t6 * ((t1 * t3).abs_diff(t2)))
                                         .abs_diff(t1 * t8),
                                     );
-                                    if leapfrog != 0
-                                        && leapfrog <= max_value_u128
-                                        && adder_count[leapfrog as usize] >= adder_cost
-                                    {
-                                        adder_count[leapfrog as usize] = adder_cost;
-                                        add_graph_type(
-                                            adder_structures,
+                                    if leapfrog != 0 && leapfrog <= max_value_u128 {
+                                        add_structure(
+                                            tables,
                                             leapfrog as usize,
+                                            adder_cost,
                                             GraphType::Leapfrog8_13(
                                                 term1, term2, term3, term4, term5, term6, term7,
                                                 term8,
@@ -1382,14 +1116,11 @@ fn leapfrog8_combinations(
                                             .abs_diff(t6 * ((t1 * t3).abs_diff(t2))))
                                         .abs_diff(t1 * t8),
                                     );
-                                    if leapfrog != 0
-                                        && leapfrog <= max_value_u128
-                                        && adder_count[leapfrog as usize] >= adder_cost
-                                    {
-                                        adder_count[leapfrog as usize] = adder_cost;
-                                        add_graph_type(
-                                            adder_structures,
+                                    if leapfrog != 0 && leapfrog <= max_value_u128 {
+                                        add_structure(
+                                            tables,
                                             leapfrog as usize,
+                                            adder_cost,
                                             GraphType::Leapfrog8_14(
                                                 term1, term2, term3, term4, term5, term6, term7,
                                                 term8,
@@ -1401,14 +1132,11 @@ fn leapfrog8_combinations(
                                             .abs_diff(t6 * (t1 * t3 + t2)))
                                         .abs_diff(t1 * t8),
                                     );
-                                    if leapfrog != 0
-                                        && leapfrog <= max_value_u128
-                                        && adder_count[leapfrog as usize] >= adder_cost
-                                    {
-                                        adder_count[leapfrog as usize] = adder_cost;
-                                        add_graph_type(
-                                            adder_structures,
+                                    if leapfrog != 0 && leapfrog <= max_value_u128 {
+                                        add_structure(
+                                            tables,
                                             leapfrog as usize,
+                                            adder_cost,
                                             GraphType::Leapfrog8_15(
                                                 term1, term2, term3, term4, term5, term6, term7,
                                                 term8,
@@ -1420,14 +1148,11 @@ fn leapfrog8_combinations(
                                             .abs_diff(t6 * ((t1 * t3).abs_diff(t2))))
                                         .abs_diff(t1 * t8),
                                     );
-                                    if leapfrog != 0
-                                        && leapfrog <= max_value_u128
-                                        && adder_count[leapfrog as usize] >= adder_cost
-                                    {
-                                        adder_count[leapfrog as usize] = adder_cost;
-                                        add_graph_type(
-                                            adder_structures,
+                                    if leapfrog != 0 && leapfrog <= max_value_u128 {
+                                        add_structure(
+                                            tables,
                                             leapfrog as usize,
+                                            adder_cost,
                                             GraphType::Leapfrog8_16(
                                                 term1, term2, term3, term4, term5, term6, term7,
                                                 term8,
@@ -1440,14 +1165,11 @@ fn leapfrog8_combinations(
                                             + t6 * (t1 * t3 + t2)
                                             + t1 * t8,
                                     );
-                                    if leapfrog != 0
-                                        && leapfrog <= max_value_u128
-                                        && adder_count[leapfrog as usize] >= adder_cost
-                                    {
-                                        adder_count[leapfrog as usize] = adder_cost;
-                                        add_graph_type(
-                                            adder_structures,
+                                    if leapfrog != 0 && leapfrog <= max_value_u128 {
+                                        add_structure(
+                                            tables,
                                             leapfrog as usize,
+                                            adder_cost,
                                             GraphType::Leapfrog8_1(
                                                 term1, term2, term3, term4, term5, term6, term7,
                                                 term8,
@@ -1460,14 +1182,11 @@ fn leapfrog8_combinations(
                                             + t6 * ((t1 * t3).abs_diff(t2))
                                             + t1 * t8,
                                     );
-                                    if leapfrog != 0
-                                        && leapfrog <= max_value_u128
-                                        && adder_count[leapfrog as usize] >= adder_cost
-                                    {
-                                        adder_count[leapfrog as usize] = adder_cost;
-                                        add_graph_type(
-                                            adder_structures,
+                                    if leapfrog != 0 && leapfrog <= max_value_u128 {
+                                        add_structure(
+                                            tables,
                                             leapfrog as usize,
+                                            adder_cost,
                                             GraphType::Leapfrog8_2(
                                                 term1, term2, term3, term4, term5, term6, term7,
                                                 term8,
@@ -1479,14 +1198,11 @@ fn leapfrog8_combinations(
                                             + t6 * (t1 * t3 + t2)
                                             + t1 * t8,
                                     );
-                                    if leapfrog != 0
-                                        && leapfrog <= max_value_u128
-                                        && adder_count[leapfrog as usize] >= adder_cost
-                                    {
-                                        adder_count[leapfrog as usize] = adder_cost;
-                                        add_graph_type(
-                                            adder_structures,
+                                    if leapfrog != 0 && leapfrog <= max_value_u128 {
+                                        add_structure(
+                                            tables,
                                             leapfrog as usize,
+                                            adder_cost,
                                             GraphType::Leapfrog8_3(
                                                 term1, term2, term3, term4, term5, term6, term7,
                                                 term8,
@@ -1498,14 +1214,11 @@ fn leapfrog8_combinations(
                                             .abs_diff(t6 * (t1 * t3 + t2))
                                             + t1 * t8,
                                     );
-                                    if leapfrog != 0
-                                        && leapfrog <= max_value_u128
-                                        && adder_count[leapfrog as usize] >= adder_cost
-                                    {
-                                        adder_count[leapfrog as usize] = adder_cost;
-                                        add_graph_type(
-                                            adder_structures,
+                                    if leapfrog != 0 && leapfrog <= max_value_u128 {
+                                        add_structure(
+                                            tables,
                                             leapfrog as usize,
+                                            adder_cost,
                                             GraphType::Leapfrog8_4(
                                                 term1, term2, term3, term4, term5, term6, term7,
                                                 term8,
@@ -1517,14 +1230,11 @@ fn leapfrog8_combinations(
                                             + t6 * ((t1 * t3).abs_diff(t2))
                                             + t1 * t8,
                                     );
-                                    if leapfrog != 0
-                                        && leapfrog <= max_value_u128
-                                        && adder_count[leapfrog as usize] >= adder_cost
-                                    {
-                                        adder_count[leapfrog as usize] = adder_cost;
-                                        add_graph_type(
-                                            adder_structures,
+                                    if leapfrog != 0 && leapfrog <= max_value_u128 {
+                                        add_structure(
+                                            tables,
                                             leapfrog as usize,
+                                            adder_cost,
                                             GraphType::Leapfrog8_5(
                                                 term1, term2, term3, term4, term5, term6, term7,
                                                 term8,
@@ -1536,14 +1246,11 @@ fn leapfrog8_combinations(
                                             .abs_diff(t6 * ((t1 * t3).abs_diff(t2)))
                                             + t1 * t8,
                                     );
-                                    if leapfrog != 0
-                                        && leapfrog <= max_value_u128
-                                        && adder_count[leapfrog as usize] >= adder_cost
-                                    {
-                                        adder_count[leapfrog as usize] = adder_cost;
-                                        add_graph_type(
-                                            adder_structures,
+                                    if leapfrog != 0 && leapfrog <= max_value_u128 {
+                                        add_structure(
+                                            tables,
                                             leapfrog as usize,
+                                            adder_cost,
                                             GraphType::Leapfrog8_6(
                                                 term1, term2, term3, term4, term5, term6, term7,
                                                 term8,
@@ -1555,14 +1262,11 @@ fn leapfrog8_combinations(
                                             .abs_diff(t6 * (t1 * t3 + t2))
                                             + t1 * t8,
                                     );
-                                    if leapfrog != 0
-                                        && leapfrog <= max_value_u128
-                                        && adder_count[leapfrog as usize] >= adder_cost
-                                    {
-                                        adder_count[leapfrog as usize] = adder_cost;
-                                        add_graph_type(
-                                            adder_structures,
+                                    if leapfrog != 0 && leapfrog <= max_value_u128 {
+                                        add_structure(
+                                            tables,
                                             leapfrog as usize,
+                                            adder_cost,
                                             GraphType::Leapfrog8_7(
                                                 term1, term2, term3, term4, term5, term6, term7,
                                                 term8,
@@ -1574,14 +1278,11 @@ fn leapfrog8_combinations(
                                             .abs_diff(t6 * ((t1 * t3).abs_diff(t2)))
                                             + t1 * t8,
                                     );
-                                    if leapfrog != 0
-                                        && leapfrog <= max_value_u128
-                                        && adder_count[leapfrog as usize] >= adder_cost
-                                    {
-                                        adder_count[leapfrog as usize] = adder_cost;
-                                        add_graph_type(
-                                            adder_structures,
+                                    if leapfrog != 0 && leapfrog <= max_value_u128 {
+                                        add_structure(
+                                            tables,
                                             leapfrog as usize,
+                                            adder_cost,
                                             GraphType::Leapfrog8_8(
                                                 term1, term2, term3, term4, term5, term6, term7,
                                                 term8,
@@ -1598,6 +1299,41 @@ fn leapfrog8_combinations(
     }
 }
 
+/// Record the depth of a structure and add it if it has the minimum number of adders
+fn add_structure(tables: &mut Tables, result: usize, adder_cost: u8, graph_type: GraphType) {
+    tables.record(result, adder_cost, &graph_type);
+    if !tables.depths_only && tables.adder_count[result] >= adder_cost {
+        tables.adder_count[result] = adder_cost;
+        add_graph_type(&mut tables.adder_structures, result, graph_type);
+    }
+}
+
+/// Add a structure together with its mirrored forms computing the same value
+///
+/// The passes only enumerate some of the mirrored forms, so adding the rest here
+/// keeps the structures of each integer closed under mirroring. All but one of
+/// each group are left out when saving, see `prune_symmetric`.
+fn add_symmetric_structure(
+    tables: &mut Tables,
+    result: usize,
+    adder_cost: u8,
+    graph_type: GraphType,
+) {
+    let mut group = vec![graph_type];
+    let mut i = 0;
+    while i < group.len() {
+        for mirrored in group[i].mirrored() {
+            if mirrored.value() == Some(result as u64) && !group.contains(&mirrored) {
+                group.push(mirrored);
+            }
+        }
+        i += 1;
+    }
+    for graph_type in group {
+        add_structure(tables, result, adder_cost, graph_type);
+    }
+}
+
 fn add_graph_type(adder_structures: &mut AdderStructures, result: usize, graph_type: GraphType) {
     if SAVE_GRAPH_TYPES {
         if let Some(structure) = &mut adder_structures[result] {
@@ -1608,644 +1344,180 @@ fn add_graph_type(adder_structures: &mut AdderStructures, result: usize, graph_t
     }
 }
 
-#[inline]
-fn findodd(n: usize) -> usize {
-    if n == 0 {
-        return 0;
+impl Tables {
+    fn record(&mut self, result: usize, adder_cost: u8, graph_type: &GraphType) {
+        self.depths
+            .record(&self.adder_count, result, adder_cost, graph_type);
     }
-    let shift = n.trailing_zeros();
-    n.shr(shift)
 }
 
-#[inline]
-fn findodd_u128(n: u128) -> u128 {
-    if n == 0 {
-        return 0;
-    }
-    let shift = n.trailing_zeros();
-    n >> shift
+/// Minimum adder depth of each integer for each number of adders
+///
+/// Each term of a structure may use a structure with more than its minimum
+/// number of adders when that one is shallower, so the depths are exact among
+/// the structures enumerated by the passes.
+struct DepthTable {
+    // Indexed by integer and number of adders, u8::MAX when not found
+    depths: Vec<[u8; MAX_DEPTH + 2]>,
+    // Set when an entry is lowered
+    changed: bool,
 }
 
-// prepare_data.rs - Run this once to create adder_cost.bin
-use std::fs::File;
-use std::io::{Result, Write};
-
-fn pack_sparse_vector(sparse_vec: &[u8]) -> (Vec<u8>, usize) {
-    let odd_count = sparse_vec.len().div_ceil(2);
-    let mut packed = Vec::new();
-    let mut bit_buffer = 0u32;
-    let mut bits_in_buffer = 0;
-
-    // Extract and pack values at odd indices
-    for i in (1..sparse_vec.len()).step_by(2) {
-        let val = sparse_vec[i] & 0b111; // Ensure only 3 bits
-        bit_buffer |= (val as u32) << bits_in_buffer;
-        bits_in_buffer += 3;
-
-        // Flush complete bytes
-        while bits_in_buffer >= 8 {
-            packed.push(bit_buffer as u8);
-            bit_buffer >>= 8;
-            bits_in_buffer -= 8;
+impl DepthTable {
+    fn new(table_max: usize) -> Self {
+        let mut depths = vec![[u8::MAX; MAX_DEPTH + 2]; table_max + 1];
+        depths[1][0] = 0;
+        DepthTable {
+            depths,
+            changed: false,
+        }
+    }
+
+    /// Record a structure of result with adder_cost adders when its terms use
+    /// their minimum number of adders
+    ///
+    /// The structure is also recorded with the additional adders of each choice
+    /// of shallower structures for the terms. Structures of integers that need
+    /// fewer adders are recorded as well, as they may be shallower.
+    fn record(
+        &mut self,
+        adder_count: &[u8],
+        result: usize,
+        adder_cost: u8,
+        graph_type: &GraphType,
+    ) {
+        let params = graph_type.params();
+        // Number of adders chosen for each distinct term, starting from the minimum
+        let mut choices: Vec<(usize, u8)> = Vec::with_capacity(params.len());
+        for &t in &params {
+            let odd = findodd(t);
+            let Some(&cost) = adder_count.get(odd) else {
+                return;
+            };
+            if cost != 0 && !choices.iter().any(|&(term, _)| term == odd) {
+                choices.push((odd, cost));
+            }
+        }
+
+        loop {
+            let extra: u8 = params
+                .iter()
+                .map(|&t| {
+                    let odd = findodd(t);
+                    choices
+                        .iter()
+                        .find(|&&(term, _)| term == odd)
+                        .map_or(0, |&(_, cost)| cost - adder_count[odd])
+                })
+                .sum();
+            let total = adder_cost + extra;
+            if total as usize <= MAX_DEPTH {
+                let depth = graph_type
+                    .evaluate(&mut DepthEvaluator {
+                        depths: self,
+                        choices: &choices,
+                    })
+                    .unwrap_or(u8::MAX);
+                let entry = &mut self.depths[result][total as usize];
+                if depth < *entry {
+                    *entry = depth;
+                    self.changed = true;
+                }
+            }
+
+            // Next choice, with the first term changing fastest
+            let mut i = 0;
+            loop {
+                let Some((term, cost)) = choices.get_mut(i) else {
+                    return;
+                };
+                if let Some(next) = self.shallower_cost(*term, *cost) {
+                    *cost = next;
+                    break;
+                }
+                *cost = adder_count[*term];
+                i += 1;
+            }
         }
     }
 
-    // Flush remaining bits
-    if bits_in_buffer > 0 {
-        packed.push(bit_buffer as u8);
+    /// Smallest number of adders above cost giving a shallower structure of t
+    fn shallower_cost(&self, t: usize, cost: u8) -> Option<u8> {
+        let depths = &self.depths[t];
+        let depth = depths[cost as usize];
+        (cost + 1..=MAX_DEPTH as u8).find(|&more| depths[more as usize] < depth)
     }
 
-    (packed, odd_count)
+    /// Adder cost of every integer with depth at most max_depth, UNKNOWN_COST when not found
+    fn costs(&self, max_depth: u8) -> Vec<u8> {
+        self.depths
+            .iter()
+            .map(|depths| {
+                (0..=MAX_DEPTH as u8)
+                    .find(|&cost| depths[cost as usize] <= max_depth)
+                    .unwrap_or(UNKNOWN_COST)
+            })
+            .collect()
+    }
 }
 
-fn save_packed_data(path: &str, packed: &[u8], count: usize) -> Result<()> {
-    let mut f = File::create(path)?;
-    // Write count as u64 little-endian
-    f.write_all(&(count as u64).to_le_bytes())?;
-    // Write packed data
-    f.write_all(packed)?;
-    Ok(())
+/// Adder depth of a structure, with the chosen number of adders of each term
+struct DepthEvaluator<'a> {
+    depths: &'a DepthTable,
+    choices: &'a [(usize, u8)],
 }
 
-// Custom serialization with varint encoding
-fn serialize_graph_types(types: &[Vec<GraphType>]) -> Vec<u8> {
-    let mut buf = Vec::new();
-
-    // Write number of entries (varint)
-    let mut count_buf = varint_encode::usize_buffer();
-    let count_bytes = varint_encode::usize(types.len(), &mut count_buf);
-    buf.extend_from_slice(count_bytes);
-
-    for type_vec in types {
-        // Write length of this Vec (varint)
-        let mut len_buf = varint_encode::usize_buffer();
-        let len_bytes = varint_encode::usize(type_vec.len(), &mut len_buf);
-        buf.extend_from_slice(len_bytes);
-
-        for gt in type_vec {
-            match gt {
-                GraphType::Adder(a, b) => {
-                    buf.push(0); // variant tag
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                }
-                GraphType::Subtractor(a, b) => {
-                    buf.push(1);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                }
-                GraphType::Cascade(a, b) => {
-                    buf.push(2);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                }
-                GraphType::Leapfrog4_1(a, b, c, d) => {
-                    buf.push(3);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                    let mut c_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*c, &mut c_buf));
-                    let mut d_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*d, &mut d_buf));
-                }
-                GraphType::Leapfrog4_2(a, b, c, d) => {
-                    buf.push(4);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                    let mut c_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*c, &mut c_buf));
-                    let mut d_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*d, &mut d_buf));
-                }
-                GraphType::Leapfrog4_3(a, b, c, d) => {
-                    buf.push(5);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                    let mut c_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*c, &mut c_buf));
-                    let mut d_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*d, &mut d_buf));
-                }
-                GraphType::Leapfrog4_4(a, b, c, d) => {
-                    buf.push(6);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                    let mut c_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*c, &mut c_buf));
-                    let mut d_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*d, &mut d_buf));
-                }
-                GraphType::Leapfrog5_1(a, b, c, d, e) => {
-                    buf.push(7);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                    let mut c_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*c, &mut c_buf));
-                    let mut d_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*d, &mut d_buf));
-                    let mut e_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*e, &mut e_buf));
-                }
-                GraphType::Leapfrog5_2(a, b, c, d, e) => {
-                    buf.push(8);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                    let mut c_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*c, &mut c_buf));
-                    let mut d_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*d, &mut d_buf));
-                    let mut e_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*e, &mut e_buf));
-                }
-                GraphType::Leapfrog5_3(a, b, c, d, e) => {
-                    buf.push(9);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                    let mut c_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*c, &mut c_buf));
-                    let mut d_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*d, &mut d_buf));
-                    let mut e_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*e, &mut e_buf));
-                }
-                GraphType::Leapfrog5_4(a, b, c, d, e) => {
-                    buf.push(10);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                    let mut c_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*c, &mut c_buf));
-                    let mut d_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*d, &mut d_buf));
-                    let mut e_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*e, &mut e_buf));
-                }
-                GraphType::Leapfrog7_1(a, b, c, d, e, f, g) => {
-                    buf.push(11);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                    let mut c_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*c, &mut c_buf));
-                    let mut d_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*d, &mut d_buf));
-                    let mut e_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*e, &mut e_buf));
-                    let mut f_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*f, &mut f_buf));
-                    let mut g_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*g, &mut g_buf));
-                }
-                GraphType::Leapfrog7_2(a, b, c, d, e, f, g) => {
-                    buf.push(12);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                    let mut c_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*c, &mut c_buf));
-                    let mut d_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*d, &mut d_buf));
-                    let mut e_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*e, &mut e_buf));
-                    let mut f_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*f, &mut f_buf));
-                    let mut g_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*g, &mut g_buf));
-                }
-                GraphType::Leapfrog7_3(a, b, c, d, e, f, g) => {
-                    buf.push(13);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                    let mut c_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*c, &mut c_buf));
-                    let mut d_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*d, &mut d_buf));
-                    let mut e_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*e, &mut e_buf));
-                    let mut f_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*f, &mut f_buf));
-                    let mut g_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*g, &mut g_buf));
-                }
-                GraphType::Leapfrog7_4(a, b, c, d, e, f, g) => {
-                    buf.push(14);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                    let mut c_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*c, &mut c_buf));
-                    let mut d_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*d, &mut d_buf));
-                    let mut e_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*e, &mut e_buf));
-                    let mut f_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*f, &mut f_buf));
-                    let mut g_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*g, &mut g_buf));
-                }
-                GraphType::Leapfrog7_5(a, b, c, d, e, f, g) => {
-                    buf.push(15);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                    let mut c_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*c, &mut c_buf));
-                    let mut d_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*d, &mut d_buf));
-                    let mut e_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*e, &mut e_buf));
-                    let mut f_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*f, &mut f_buf));
-                    let mut g_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*g, &mut g_buf));
-                }
-                GraphType::Leapfrog7_6(a, b, c, d, e, f, g) => {
-                    buf.push(16);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                    let mut c_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*c, &mut c_buf));
-                    let mut d_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*d, &mut d_buf));
-                    let mut e_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*e, &mut e_buf));
-                    let mut f_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*f, &mut f_buf));
-                    let mut g_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*g, &mut g_buf));
-                }
-                GraphType::Leapfrog7_7(a, b, c, d, e, f, g) => {
-                    buf.push(17);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                    let mut c_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*c, &mut c_buf));
-                    let mut d_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*d, &mut d_buf));
-                    let mut e_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*e, &mut e_buf));
-                    let mut f_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*f, &mut f_buf));
-                    let mut g_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*g, &mut g_buf));
-                }
-                GraphType::Leapfrog7_8(a, b, c, d, e, f, g) => {
-                    buf.push(18);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                    let mut c_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*c, &mut c_buf));
-                    let mut d_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*d, &mut d_buf));
-                    let mut e_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*e, &mut e_buf));
-                    let mut f_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*f, &mut f_buf));
-                    let mut g_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*g, &mut g_buf));
-                }
-                GraphType::Leapfrog8_1(a, b, c, d, e, f, g, h) => {
-                    buf.push(19);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                    let mut c_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*c, &mut c_buf));
-                    let mut d_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*d, &mut d_buf));
-                    let mut e_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*e, &mut e_buf));
-                    let mut f_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*f, &mut f_buf));
-                    let mut g_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*g, &mut g_buf));
-                    let mut h_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*h, &mut h_buf));
-                }
-                GraphType::Leapfrog8_2(a, b, c, d, e, f, g, h) => {
-                    buf.push(20);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                    let mut c_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*c, &mut c_buf));
-                    let mut d_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*d, &mut d_buf));
-                    let mut e_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*e, &mut e_buf));
-                    let mut f_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*f, &mut f_buf));
-                    let mut g_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*g, &mut g_buf));
-                    let mut h_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*h, &mut h_buf));
-                }
-                GraphType::Leapfrog8_3(a, b, c, d, e, f, g, h) => {
-                    buf.push(21);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                    let mut c_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*c, &mut c_buf));
-                    let mut d_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*d, &mut d_buf));
-                    let mut e_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*e, &mut e_buf));
-                    let mut f_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*f, &mut f_buf));
-                    let mut g_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*g, &mut g_buf));
-                    let mut h_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*h, &mut h_buf));
-                }
-                GraphType::Leapfrog8_4(a, b, c, d, e, f, g, h) => {
-                    buf.push(22);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                    let mut c_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*c, &mut c_buf));
-                    let mut d_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*d, &mut d_buf));
-                    let mut e_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*e, &mut e_buf));
-                    let mut f_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*f, &mut f_buf));
-                    let mut g_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*g, &mut g_buf));
-                    let mut h_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*h, &mut h_buf));
-                }
-                GraphType::Leapfrog8_5(a, b, c, d, e, f, g, h) => {
-                    buf.push(23);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                    let mut c_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*c, &mut c_buf));
-                    let mut d_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*d, &mut d_buf));
-                    let mut e_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*e, &mut e_buf));
-                    let mut f_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*f, &mut f_buf));
-                    let mut g_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*g, &mut g_buf));
-                    let mut h_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*h, &mut h_buf));
-                }
-                GraphType::Leapfrog8_6(a, b, c, d, e, f, g, h) => {
-                    buf.push(24);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                    let mut c_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*c, &mut c_buf));
-                    let mut d_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*d, &mut d_buf));
-                    let mut e_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*e, &mut e_buf));
-                    let mut f_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*f, &mut f_buf));
-                    let mut g_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*g, &mut g_buf));
-                    let mut h_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*h, &mut h_buf));
-                }
-                GraphType::Leapfrog8_7(a, b, c, d, e, f, g, h) => {
-                    buf.push(25);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                    let mut c_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*c, &mut c_buf));
-                    let mut d_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*d, &mut d_buf));
-                    let mut e_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*e, &mut e_buf));
-                    let mut f_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*f, &mut f_buf));
-                    let mut g_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*g, &mut g_buf));
-                    let mut h_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*h, &mut h_buf));
-                }
-                GraphType::Leapfrog8_8(a, b, c, d, e, f, g, h) => {
-                    buf.push(26);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                    let mut c_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*c, &mut c_buf));
-                    let mut d_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*d, &mut d_buf));
-                    let mut e_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*e, &mut e_buf));
-                    let mut f_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*f, &mut f_buf));
-                    let mut g_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*g, &mut g_buf));
-                    let mut h_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*h, &mut h_buf));
-                }
-                GraphType::Leapfrog8_9(a, b, c, d, e, f, g, h) => {
-                    buf.push(27);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                    let mut c_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*c, &mut c_buf));
-                    let mut d_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*d, &mut d_buf));
-                    let mut e_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*e, &mut e_buf));
-                    let mut f_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*f, &mut f_buf));
-                    let mut g_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*g, &mut g_buf));
-                    let mut h_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*h, &mut h_buf));
-                }
-                GraphType::Leapfrog8_10(a, b, c, d, e, f, g, h) => {
-                    buf.push(28);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                    let mut c_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*c, &mut c_buf));
-                    let mut d_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*d, &mut d_buf));
-                    let mut e_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*e, &mut e_buf));
-                    let mut f_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*f, &mut f_buf));
-                    let mut g_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*g, &mut g_buf));
-                    let mut h_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*h, &mut h_buf));
-                }
-                GraphType::Leapfrog8_11(a, b, c, d, e, f, g, h) => {
-                    buf.push(29);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                    let mut c_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*c, &mut c_buf));
-                    let mut d_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*d, &mut d_buf));
-                    let mut e_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*e, &mut e_buf));
-                    let mut f_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*f, &mut f_buf));
-                    let mut g_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*g, &mut g_buf));
-                    let mut h_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*h, &mut h_buf));
-                }
-                GraphType::Leapfrog8_12(a, b, c, d, e, f, g, h) => {
-                    buf.push(30);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                    let mut c_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*c, &mut c_buf));
-                    let mut d_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*d, &mut d_buf));
-                    let mut e_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*e, &mut e_buf));
-                    let mut f_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*f, &mut f_buf));
-                    let mut g_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*g, &mut g_buf));
-                    let mut h_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*h, &mut h_buf));
-                }
-                GraphType::Leapfrog8_13(a, b, c, d, e, f, g, h) => {
-                    buf.push(31);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                    let mut c_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*c, &mut c_buf));
-                    let mut d_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*d, &mut d_buf));
-                    let mut e_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*e, &mut e_buf));
-                    let mut f_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*f, &mut f_buf));
-                    let mut g_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*g, &mut g_buf));
-                    let mut h_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*h, &mut h_buf));
-                }
-                GraphType::Leapfrog8_14(a, b, c, d, e, f, g, h) => {
-                    buf.push(32);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                    let mut c_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*c, &mut c_buf));
-                    let mut d_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*d, &mut d_buf));
-                    let mut e_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*e, &mut e_buf));
-                    let mut f_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*f, &mut f_buf));
-                    let mut g_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*g, &mut g_buf));
-                    let mut h_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*h, &mut h_buf));
-                }
-                GraphType::Leapfrog8_15(a, b, c, d, e, f, g, h) => {
-                    buf.push(33);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                    let mut c_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*c, &mut c_buf));
-                    let mut d_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*d, &mut d_buf));
-                    let mut e_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*e, &mut e_buf));
-                    let mut f_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*f, &mut f_buf));
-                    let mut g_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*g, &mut g_buf));
-                    let mut h_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*h, &mut h_buf));
-                }
-                GraphType::Leapfrog8_16(a, b, c, d, e, f, g, h) => {
-                    buf.push(34);
-                    let mut a_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*a, &mut a_buf));
-                    let mut b_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*b, &mut b_buf));
-                    let mut c_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*c, &mut c_buf));
-                    let mut d_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*d, &mut d_buf));
-                    let mut e_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*e, &mut e_buf));
-                    let mut f_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*f, &mut f_buf));
-                    let mut g_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*g, &mut g_buf));
-                    let mut h_buf = varint_encode::usize_buffer();
-                    buf.extend_from_slice(varint_encode::usize(*h, &mut h_buf));
-                }
-            }
-        }
+impl DepthEvaluator<'_> {
+    fn depth(&self, t: usize) -> Result<u8, ()> {
+        let odd = findodd(t);
+        // Powers of two need no adders and are not among the choices
+        let cost = self
+            .choices
+            .iter()
+            .find(|&&(term, _)| term == odd)
+            .map_or(0, |&(_, cost)| cost);
+        let depth = self.depths.depths[odd][cost as usize];
+        if depth == u8::MAX { Err(()) } else { Ok(depth) }
+    }
+}
+
+impl Evaluator for DepthEvaluator<'_> {
+    type Value = u8;
+    type Error = ();
+
+    fn constant(&mut self, t: usize) -> Result<u8, ()> {
+        self.depth(t)
+    }
+
+    fn multiply(&mut self, value: &u8, t: usize) -> Result<u8, ()> {
+        Ok(value + self.depth(t)?)
+    }
+
+    fn add(&mut self, a: &u8, b: &u8) -> Result<u8, ()> {
+        Ok(a.max(b) + 1)
+    }
+
+    fn abs_diff(&mut self, a: &u8, b: &u8) -> Result<u8, ()> {
+        Ok(a.max(b) + 1)
     }
+}
 
-    buf
+#[inline]
+fn findodd(n: usize) -> usize {
+    if n == 0 {
+        return 0;
+    }
+    let shift = n.trailing_zeros();
+    n.shr(shift)
+}
+
+#[inline]
+fn findodd_u128(n: u128) -> u128 {
+    if n == 0 {
+        return 0;
+    }
+    let shift = n.trailing_zeros();
+    n >> shift
 }
 
 /// Extract all indices with a specific adder cost
@@ -2276,3 +1548,80 @@ fn create_shifted_variants(values: &[usize], max_value: usize) -> Vec<usize> {
     }
     shifted
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use constant_multiplication::CostTable;
+    use std::path::PathBuf;
+
+    /// Directory removed when dropped, also when an assertion fails
+    struct TempDir(PathBuf);
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn expand_symmetric_restores_pruned_structures() {
+        let generated = generate(12, 2);
+        let dir =
+            TempDir(std::env::temp_dir().join(format!("cm-generator-test-{}", std::process::id())));
+        std::fs::create_dir_all(&dir.0).unwrap();
+        write_tables(&dir.0, &generated).unwrap();
+        let table = CostTable::open(&dir.0).unwrap();
+
+        let unpruned = collect_graph_types(&generated.tables.adder_structures, false);
+        let (mut stored_count, mut unpruned_count) = (0, 0);
+        for (i, all) in unpruned.iter().enumerate().skip(1) {
+            let n = 2 * i as u64 + 1;
+            let expanded = table.expand_symmetric(n).unwrap();
+            assert_eq!(expanded.len(), all.len(), "{n}");
+            assert_eq!(
+                expanded.iter().collect::<HashSet<_>>(),
+                all.iter().collect::<HashSet<_>>(),
+                "{n}"
+            );
+            stored_count += table.graph_types(n).unwrap().len();
+            unpruned_count += all.len();
+        }
+        assert!(stored_count < unpruned_count);
+    }
+
+    #[test]
+    fn terms_may_use_shallower_structures_with_more_adders() {
+        let mut adder_count = vec![UNKNOWN_COST; 64];
+        adder_count[1] = 0;
+        adder_count[45] = 2;
+        let mut depths = DepthTable::new(63);
+        depths.depths[45][2] = 2;
+        // A made up structure of 45 with one more adder and depth 1
+        depths.depths[45][3] = 1;
+
+        // 45 + 1 = 2 * 23
+        depths.record(&adder_count, 23, 3, &GraphType::Adder(45, 1));
+        assert_eq!(depths.depths[23][3], 3);
+        assert_eq!(depths.depths[23][4], 2);
+        assert_eq!(depths.costs(2)[23], 4);
+        assert_eq!(depths.costs(3)[23], 3);
+        assert_eq!(depths.costs(1)[23], UNKNOWN_COST);
+    }
+
+    #[test]
+    fn depth_costs_are_between_the_adder_cost_and_unknown() {
+        let generated = generate(12, 2);
+        let adder_count = &generated.tables.adder_count;
+        let costs: Vec<Vec<u8>> = (1..=MAX_DEPTH as u8)
+            .map(|max_depth| generated.tables.depths.costs(max_depth))
+            .collect();
+        for n in (1..adder_count.len()).step_by(2) {
+            for pair in costs.windows(2) {
+                assert!(pair[1][n] <= pair[0][n], "{n}");
+            }
+            assert!(costs[0][n] >= adder_count[n], "{n}");
+            assert_eq!(costs[MAX_DEPTH - 1][n], adder_count[n], "{n}");
+        }
+    }
+}